@@ -1,6 +1,6 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::Read;
 use std::path::Path;
 use std::{env, fs};
 
@@ -11,10 +11,11 @@ use simple_logger::SimpleLogger;
 use document::meta_data::{DocumentEncoding, DocumentMetaData};
 use document::style::DocumentStyles;
 use document::Document;
-use export::ExportType;
+use export::{ExportOptions, ExportType};
 use layout::options::LayoutOptions;
 use metadata::read_meta_data;
 use script::parse_document_structure;
+use unit::DistanceUnit;
 
 const BANNER: &str = "\
 -------------------
@@ -23,20 +24,25 @@ const BANNER: &str = "\
 -------------------";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Resolution of PNG exports in dots per inch.
+const PNG_DPI: usize = 150;
+
+/// Names of the export formats accepted by `--format`.
+const FORMAT_NAMES: &str = "pdf, pdf-a, svg, png, html, epub, text, markdown, json";
+
 fn main() -> Result<(), Box<dyn Error>> {
     SimpleLogger::new().init().unwrap();
 
     println!("{}", BANNER);
     println!("      v{}\n", VERSION);
 
-    // TODO Use a proper command line parser
-    let args: Vec<String> = env::args().collect();
-    let file_path = args.get(1).expect("Expected file path as first argument");
+    let arguments = parse_arguments(env::args().skip(1))?;
+    let file_path = &arguments.script_path;
 
     // TODO For now we simply search the folder of the script file for a meta data file with the name "metadata.toml"
     let meta_data_file_path = Path::new(file_path)
         .parent()
-        .unwrap()
+        .ok_or_else(|| format!("Script path '{}' does not name a file", file_path))?
         .join("meta_data.toml");
     let meta_data = if meta_data_file_path.exists() {
         let meta_data_src = fs::read_to_string(meta_data_file_path)?;
@@ -47,17 +53,16 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("Meta data: {:#?}", meta_data);
 
-    let styles = if args.len() > 2 {
-        let style_file_path = args.get(2).unwrap();
+    // All further arguments are style files layered in the given order, later ones take precedence
+    let mut styles = DocumentStyles::new();
+    for style_file_path in &arguments.style_paths {
         let style_src = read_to_string_with_encoding(style_file_path, &meta_data.encoding)
-            .expect("Could not read style file");
-        style::parse(&style_src)?
-    } else {
-        DocumentStyles::new()
-    };
+            .map_err(|e| format!("Could not read style file '{}': {}", style_file_path, e))?;
+        style::parse_into(&mut styles, &style_src, Some(Path::new(style_file_path)))?;
+    }
 
     let script = read_to_string_with_encoding(file_path, &meta_data.encoding)
-        .expect("Could not read script file");
+        .map_err(|e| format!("Could not read script file '{}': {}", file_path, e))?;
     let document_structure = parse_document_structure(&script)?;
 
     let document = Document {
@@ -69,11 +74,133 @@ fn main() -> Result<(), Box<dyn Error>> {
     let layout_options = LayoutOptions::default();
     let document_layout = layout::layout(&document, layout_options)?;
 
-    export::export(document_layout, ExportType::PDF)?;
+    let export_options = ExportOptions::default();
+    match &arguments.output_path {
+        Some(output_path) => export::export_to_path(
+            &document,
+            document_layout,
+            arguments.export_type,
+            export_options,
+            Path::new(output_path),
+        )?,
+        None => export::export(
+            &document,
+            document_layout,
+            arguments.export_type,
+            export_options,
+        )?,
+    }
 
     Ok(())
 }
 
+struct Arguments {
+    script_path: String,
+    style_paths: Vec<String>,
+
+    /// File to export to instead of the default file in the working directory.
+    output_path: Option<String>,
+
+    /// Format given by `--format`, otherwise derived from the extension of the output path and PDF by default.
+    export_type: ExportType,
+}
+
+// TODO Use a proper command line parser
+fn parse_arguments(mut args: impl Iterator<Item = String>) -> Result<Arguments, Box<dyn Error>> {
+    let mut paths = Vec::new();
+    let mut output_path = None;
+    let mut format = None;
+    while let Some(arg) = args.next() {
+        if arg == "-o" || arg == "--output" {
+            let path = args
+                .next()
+                .ok_or(format!("Expected output path after '{}'", arg))?;
+            output_path = Some(path);
+        } else if let Some(path) = arg.strip_prefix("--output=") {
+            output_path = Some(path.to_owned());
+        } else if arg == "-f" || arg == "--format" {
+            let name = args
+                .next()
+                .ok_or(format!("Expected export format after '{}'", arg))?;
+            format = Some(name);
+        } else if let Some(name) = arg.strip_prefix("--format=") {
+            format = Some(name.to_owned());
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    let export_type = match (&format, &output_path) {
+        (Some(name), _) => export_type_from_name(name)?,
+        (None, Some(path)) => export_type_from_path(path)?,
+        (None, None) => ExportType::PDF,
+    };
+
+    let mut paths = paths.into_iter();
+    let script_path = paths.next().ok_or("Expected file path as first argument")?;
+
+    Ok(Arguments {
+        script_path,
+        style_paths: paths.collect(),
+        output_path,
+        export_type,
+    })
+}
+
+fn export_type_from_name(name: &str) -> Result<ExportType, Box<dyn Error>> {
+    let export_type = match name.to_ascii_lowercase().as_str() {
+        "pdf" => ExportType::PDF,
+        "pdf-a" => ExportType::PdfA,
+        "svg" => ExportType::Svg,
+        "png" => ExportType::Png { dpi: PNG_DPI },
+        "html" => ExportType::Html,
+        "epub" => ExportType::Epub,
+        "text" => ExportType::Text,
+        "markdown" => ExportType::Markdown,
+        "json" => ExportType::LayoutJson {
+            unit: DistanceUnit::Points,
+        },
+        _ => {
+            return Err(format!(
+                "Unknown export format '{}', expected one of: {}",
+                name, FORMAT_NAMES
+            )
+            .into())
+        }
+    };
+
+    Ok(export_type)
+}
+
+/// Export type matching the extension of the output path, thus `-o out.html` exports HTML.
+fn export_type_from_path(path: &str) -> Result<ExportType, Box<dyn Error>> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    let name = match extension.as_deref() {
+        Some("pdf") => "pdf",
+        Some("svg") => "svg",
+        Some("png") => "png",
+        Some("html" | "htm" | "xhtml") => "html",
+        Some("epub") => "epub",
+        Some("txt") => "text",
+        Some("md" | "markdown") => "markdown",
+        Some("json") => "json",
+        _ => {
+            return Err(format!(
+                "Cannot derive the export format from the output path '{}', \
+                use --format with one of: {}",
+                path, FORMAT_NAMES
+            )
+            .into())
+        }
+    };
+
+    export_type_from_name(name)
+}
+
 fn read_to_string_with_encoding(
     path: &str,
     file_encoding: &DocumentEncoding,
@@ -85,17 +212,215 @@ fn read_to_string_with_encoding(
     }
 
     let file = File::open(path)?;
-    let decode_reader_bytes = DecodeReaderBytesBuilder::new()
+    let mut reader = DecodeReaderBytesBuilder::new()
         .encoding(Some(encoding.unwrap_or(UTF_8)))
         .build(file);
-    let reader = BufReader::new(decode_reader_bytes);
 
+    // Read the file as a whole, since line breaks are significant for example in code blocks
     let mut result = String::new();
-    for read_result in reader.lines() {
-        if let Ok(line) = read_result {
-            result.push_str(&line);
+    reader.read_to_string(&mut result)?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Arguments, Box<dyn Error>> {
+        parse_arguments(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn should_parse_script_and_style_paths() {
+        // Given: a script followed by style files
+        let args = ["doc.lsc", "base.lst", "custom.lst"];
+
+        // When: parsing the arguments
+        let arguments = parse(&args).unwrap();
+
+        // Then: the first path is the script and the others are styles in the given order
+        assert_eq!(arguments.script_path, "doc.lsc");
+        assert_eq!(arguments.style_paths, vec!["base.lst", "custom.lst"]);
+        assert_eq!(arguments.output_path, None);
+        assert!(matches!(arguments.export_type, ExportType::PDF));
+    }
+
+    #[test]
+    fn should_parse_output_path_in_any_position() {
+        // Given: the output option before, between and after the paths in both notations
+        let args_list: [&[&str]; 4] = [
+            &["-o", "out/doc.pdf", "doc.lsc", "base.lst"],
+            &["doc.lsc", "--output", "out/doc.pdf", "base.lst"],
+            &["doc.lsc", "base.lst", "--output=out/doc.pdf"],
+            &[
+                "doc.lsc",
+                "-o",
+                "first.pdf",
+                "base.lst",
+                "-o",
+                "out/doc.pdf",
+            ],
+        ];
+
+        for args in args_list {
+            // When: parsing the arguments
+            let arguments = parse(args).unwrap();
+
+            // Then: the output path is taken from the (last) option and the paths keep their order
+            assert_eq!(arguments.output_path.as_deref(), Some("out/doc.pdf"));
+            assert_eq!(arguments.script_path, "doc.lsc");
+            assert_eq!(arguments.style_paths, vec!["base.lst"]);
         }
     }
 
-    Ok(result)
+    #[test]
+    fn should_fail_for_missing_option_values() {
+        // Given: options without a value
+        let args_list: [&[&str]; 3] = [
+            &["doc.lsc", "-o"],
+            &["doc.lsc", "--output"],
+            &["doc.lsc", "--format"],
+        ];
+
+        for args in args_list {
+            // When: parsing the arguments
+            let result = parse(args);
+
+            // Then: an error is returned
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn should_fail_without_script_path() {
+        // Given: only an output option
+        let args = ["-o", "doc.pdf"];
+
+        // When: parsing the arguments
+        let result = parse(&args);
+
+        // Then: an error is returned
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_derive_format_from_output_extension() {
+        // Given: output paths with the extensions of the formats
+        let cases = [
+            ("out.PDF", "pdf"),
+            ("out.svg", "svg"),
+            ("out.png", "png"),
+            ("site/index.html", "html"),
+            ("book.epub", "epub"),
+            ("out.txt", "text"),
+            ("README.md", "markdown"),
+            ("layout.json", "json"),
+        ];
+
+        for (path, format) in cases {
+            // When: parsing the arguments
+            let arguments = parse(&["doc.lsc", "-o", path]).unwrap();
+
+            // Then: the format matches the extension
+            assert_eq!(format_name(&arguments.export_type), format, "{}", path);
+        }
+    }
+
+    #[test]
+    fn should_fail_for_unknown_output_extension() {
+        // Given: output paths without a known extension
+        let paths = ["out.docx", "out"];
+
+        for path in paths {
+            // When: parsing the arguments
+            let result = parse(&["doc.lsc", "-o", path]);
+
+            // Then: an error is returned instead of writing PDF into the file
+            assert!(result.is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn should_prefer_format_option_over_extension() {
+        // Given: a format option and an output path with an unknown extension
+        let args = ["doc.lsc", "--format=pdf-a", "-o", "archive.bin"];
+
+        // When: parsing the arguments
+        let arguments = parse(&args).unwrap();
+
+        // Then: the format of the option is used
+        assert_eq!(format_name(&arguments.export_type), "pdf-a");
+    }
+
+    #[test]
+    fn should_parse_format_without_output_path() {
+        // Given: a format option without an output path
+        let args = ["-f", "html", "doc.lsc"];
+
+        // When: parsing the arguments
+        let arguments = parse(&args).unwrap();
+
+        // Then: the document is exported as HTML to the default file
+        assert_eq!(format_name(&arguments.export_type), "html");
+        assert_eq!(arguments.export_type.default_file_name(), "out.html");
+    }
+
+    #[test]
+    fn should_fail_for_unknown_format() {
+        // Given: an unknown format name
+        let args = ["doc.lsc", "--format", "docx"];
+
+        // When: parsing the arguments
+        let result = parse(&args);
+
+        // Then: the error lists the known formats
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains(FORMAT_NAMES), "{}", error);
+    }
+
+    #[test]
+    fn should_keep_line_breaks_when_reading_files() {
+        // Given: a file with several lines, indentation and an empty line
+        let content = "<code-block>\nfn main() {\n    run();\n}\n\n</code-block>\n";
+        let path = env::temp_dir().join(format!("letter-read-{}.lsc", std::process::id()));
+        fs::write(&path, content).unwrap();
+
+        // When: reading the file
+        let result =
+            read_to_string_with_encoding(path.to_str().unwrap(), &DocumentEncoding::default());
+        fs::remove_file(&path).unwrap();
+
+        // Then: the content is read exactly as written
+        assert_eq!(result.unwrap(), content);
+    }
+
+    #[test]
+    fn should_decode_files_with_encoding_of_meta_data() {
+        // Given: a file with two lines encoded in ISO-8859-1
+        let path = env::temp_dir().join(format!("letter-latin1-{}.lsc", std::process::id()));
+        fs::write(&path, b"Gr\xfc\xdfe\r\nStra\xdfe\n").unwrap();
+
+        // When: reading the file with the encoding of the meta data
+        let result =
+            read_to_string_with_encoding(path.to_str().unwrap(), &DocumentEncoding::new("latin1"));
+        fs::remove_file(&path).unwrap();
+
+        // Then: the characters are decoded and the line breaks are kept
+        assert_eq!(result.unwrap(), "Grüße\r\nStraße\n");
+    }
+
+    fn format_name(export_type: &ExportType) -> &'static str {
+        match export_type {
+            ExportType::PDF => "pdf",
+            ExportType::PdfA => "pdf-a",
+            ExportType::Svg => "svg",
+            ExportType::Png { .. } => "png",
+            ExportType::Html => "html",
+            ExportType::Epub => "epub",
+            ExportType::Text => "text",
+            ExportType::Markdown => "markdown",
+            ExportType::LayoutJson { .. } => "json",
+        }
+    }
 }
@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::structure::SourcePosition;
+
+/// A problem found in a source file (for example a style sheet),
+/// pointing to the location it occurred at.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+
+    /// Name of the source file, if known.
+    pub file: Option<String>,
+
+    pub position: Option<SourcePosition>,
+
+    /// Hint on how to fix the problem, for example a suggestion for a misspelled property.
+    pub help: Option<String>,
+}
+
+/// Several diagnostics collected in one pass over a source file.
+#[derive(Debug, Clone)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            file: None,
+            position: None,
+            help: None,
+        }
+    }
+
+    pub fn with_file(mut self, file: Option<String>) -> Self {
+        self.file = file;
+        self
+    }
+
+    pub fn at(mut self, position: SourcePosition) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn with_help(mut self, help: Option<String>) -> Self {
+        self.help = help;
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, &self.position) {
+            (Some(file), Some(position)) => {
+                write!(f, "{}:{}:{}: ", file, position.line, position.column)?
+            }
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(position)) => write!(f, "{}:{}: ", position.line, position.column)?,
+            (None, None) => {}
+        }
+
+        write!(f, "{}", self.message)?;
+
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {}", help)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for Diagnostic {}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl Error for Diagnostics {}
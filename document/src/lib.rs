@@ -2,6 +2,7 @@ use crate::meta_data::DocumentMetaData;
 use crate::structure::DocumentStructure;
 use crate::style::DocumentStyles;
 
+pub mod diagnostic;
 pub mod meta_data;
 pub mod structure;
 pub mod style;
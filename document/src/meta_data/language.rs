@@ -17,6 +17,14 @@ impl DocumentLanguage {
             region_code: region_code.map(|code| code.to_string()),
         }
     }
+
+    /// Language tag as used by PDF and HTML, for example "en-US".
+    pub fn tag(&self) -> String {
+        match &self.region_code {
+            Some(region_code) => format!("{}-{}", self.language_code, region_code),
+            None => self.language_code.clone(),
+        }
+    }
 }
 
 impl Default for DocumentLanguage {
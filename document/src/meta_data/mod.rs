@@ -12,6 +12,9 @@ pub use version::DocumentVersion;
 
 #[derive(Default, Debug)]
 pub struct DocumentMetaData {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Vec<String>,
     pub encoding: DocumentEncoding,
     pub language: DocumentLanguage,
     pub authors: Vec<Author>,
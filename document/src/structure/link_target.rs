@@ -0,0 +1,9 @@
+/// Destination of a link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// External resource given by its URI, for example `https://example.com`.
+    Uri(String),
+
+    /// Node within the document given by the value of its `id` attribute.
+    Anchor(String),
+}
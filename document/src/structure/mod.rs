@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::fmt::Write;
 
+pub use link_target::LinkTarget;
 pub use node::DocumentNode;
 pub use source_position::SourcePosition;
 pub use value::DocumentNodeValue;
 
+mod link_target;
 mod node;
 mod source_position;
 mod value;
@@ -76,6 +78,27 @@ impl DocumentStructure {
         }
     }
 
+    /// Concatenated text of all text nodes below the given node (including itself).
+    pub fn text_content(&self, id: NodeId) -> String {
+        let mut result = String::new();
+
+        self.fill_text_content(&mut result, id);
+
+        result
+    }
+
+    fn fill_text_content(&self, result: &mut String, id: NodeId) {
+        if let Some(node) = self.get_node(id) {
+            if let DocumentNodeValue::Text(text) = &node.value {
+                result.push_str(text);
+            }
+
+            for child_node_id in &node.children {
+                self.fill_text_content(result, *child_node_id);
+            }
+        }
+    }
+
     pub fn insert(&mut self, parent: NodeId, mut node: DocumentNode) {
         if let Some(parent_node) = self.nodes.get_mut(&parent) {
             node.parent = Some(parent);
@@ -4,6 +4,7 @@ use crate::structure::{DocumentNodeValue, NodeId, SourcePosition};
 use crate::style::ClassName;
 
 const CLASS_ATTRIBUTE: &'static str = "class";
+const ANCHOR_ATTRIBUTE: &'static str = "id";
 
 #[derive(Debug)]
 pub struct DocumentNode {
@@ -54,4 +55,11 @@ impl DocumentNode {
     pub fn class_name(&self) -> Option<&ClassName> {
         self.attributes.get(CLASS_ATTRIBUTE)
     }
+
+    /// Name under which the node may be referenced by links within the document.
+    pub fn anchor_name(&self) -> Option<&str> {
+        self.attributes
+            .get(ANCHOR_ATTRIBUTE)
+            .map(|name| name.as_str())
+    }
 }
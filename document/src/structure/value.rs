@@ -1,6 +1,8 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+use crate::structure::LinkTarget;
+
 /// Node values with special meanings in the context of a document.
 #[derive(Debug)]
 pub enum DocumentNodeValue {
@@ -17,9 +19,25 @@ pub enum DocumentNodeValue {
     List,
     ListItem,
     Table,
+    TableRow,
+    TableCell,
     Break,
     Bold,
-    Italic, // TODO Header, Footer elements
+    Italic,
+    Underline,
+    StrikeThrough,
+    Superscript,
+    Subscript,
+    Code,
+
+    /// Preformatted block of code whose white space and line breaks are kept.
+    CodeBlock,
+
+    /// Highlighted token of a code block, its class names the kind of token (for example `keyword`).
+    CodeToken,
+
+    /// Inline link to an external resource or to another node of the document.
+    Link(LinkTarget), // TODO Header, Footer elements
 }
 
 impl Display for DocumentNodeValue {
@@ -0,0 +1,152 @@
+use unit::Distance;
+
+/// A distance as given in a style sheet.
+/// Relative distances depend on the context they are applied in
+/// and can thus only be resolved during layout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StyleDistance {
+    Absolute(Distance),
+
+    /// Relative to the font size of the styled node.
+    Em(f64),
+
+    /// Relative to the font size of the document root.
+    Rem(f64),
+
+    /// Relative to the x-height of the current font.
+    Ex(f64),
+
+    /// Relative to the advance width of the "0" character in the current font.
+    Ch(f64),
+
+    /// Percentage of the containing bounds width or height.
+    Percent(f64),
+
+    /// Arithmetic expression given by `calc(...)`, for example `calc(100% - 2cm)`.
+    Calc(Box<DistanceExpression>),
+}
+
+/// Expression tree of a `calc(...)` distance.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DistanceExpression {
+    Distance(StyleDistance),
+    Number(f64),
+    Add(Box<DistanceExpression>, Box<DistanceExpression>),
+    Subtract(Box<DistanceExpression>, Box<DistanceExpression>),
+    Multiply(Box<DistanceExpression>, Box<DistanceExpression>),
+    Divide(Box<DistanceExpression>, Box<DistanceExpression>),
+}
+
+/// Value of a (sub-)expression of a `calc(...)` distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceExpressionValue {
+    Number(f64),
+    Distance(Distance),
+}
+
+impl StyleDistance {
+    pub fn is_font_metric_relative(&self) -> bool {
+        match self {
+            StyleDistance::Ex(_) | StyleDistance::Ch(_) => true,
+            StyleDistance::Calc(expression) => expression.is_font_metric_relative(),
+            _ => false,
+        }
+    }
+
+    pub fn as_absolute(&self) -> Option<Distance> {
+        match self {
+            StyleDistance::Absolute(distance) => Some(*distance),
+            StyleDistance::Calc(expression) => {
+                let value = expression.evaluate(&|distance: &StyleDistance| {
+                    distance.as_absolute().ok_or(String::new())
+                });
+
+                match value {
+                    Ok(DistanceExpressionValue::Distance(distance)) => Some(distance),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl DistanceExpression {
+    /// Evaluate the expression using the given function to resolve the contained distances.
+    /// Fails when units are mixed incompatibly, for example when adding a number to a distance
+    /// or multiplying two distances.
+    pub fn evaluate<F, E>(&self, resolve: &F) -> Result<DistanceExpressionValue, E>
+    where
+        F: Fn(&StyleDistance) -> Result<Distance, E>,
+        E: From<String>,
+    {
+        use DistanceExpressionValue::{Distance as D, Number as N};
+
+        let value = match self {
+            DistanceExpression::Distance(distance) => D(resolve(distance)?),
+            DistanceExpression::Number(number) => N(*number),
+            DistanceExpression::Add(left, right) => {
+                match (left.evaluate(resolve)?, right.evaluate(resolve)?) {
+                    (D(left), D(right)) => D(left + right),
+                    (N(left), N(right)) => N(left + right),
+                    _ => {
+                        return Err("Cannot add a number and a distance in calc()"
+                            .to_owned()
+                            .into())
+                    }
+                }
+            }
+            DistanceExpression::Subtract(left, right) => {
+                match (left.evaluate(resolve)?, right.evaluate(resolve)?) {
+                    (D(left), D(right)) => D(left - right),
+                    (N(left), N(right)) => N(left - right),
+                    _ => {
+                        return Err("Cannot subtract a number and a distance in calc()"
+                            .to_owned()
+                            .into())
+                    }
+                }
+            }
+            DistanceExpression::Multiply(left, right) => {
+                match (left.evaluate(resolve)?, right.evaluate(resolve)?) {
+                    (D(distance), N(factor)) | (N(factor), D(distance)) => D(distance * factor),
+                    (N(left), N(right)) => N(left * right),
+                    (D(_), D(_)) => {
+                        return Err("Cannot multiply two distances in calc()".to_owned().into())
+                    }
+                }
+            }
+            DistanceExpression::Divide(left, right) => {
+                match (left.evaluate(resolve)?, right.evaluate(resolve)?) {
+                    (_, D(_)) => {
+                        return Err("Cannot divide by a distance in calc()".to_owned().into())
+                    }
+                    (_, N(0.0)) => return Err("Division by zero in calc()".to_owned().into()),
+                    (D(distance), N(divisor)) => D(distance / divisor),
+                    (N(left), N(right)) => N(left / right),
+                }
+            }
+        };
+
+        Ok(value)
+    }
+
+    pub fn is_font_metric_relative(&self) -> bool {
+        match self {
+            DistanceExpression::Distance(distance) => distance.is_font_metric_relative(),
+            DistanceExpression::Number(_) => false,
+            DistanceExpression::Add(left, right)
+            | DistanceExpression::Subtract(left, right)
+            | DistanceExpression::Multiply(left, right)
+            | DistanceExpression::Divide(left, right) => {
+                left.is_font_metric_relative() || right.is_font_metric_relative()
+            }
+        }
+    }
+}
+
+impl From<Distance> for StyleDistance {
+    fn from(distance: Distance) -> Self {
+        StyleDistance::Absolute(distance)
+    }
+}
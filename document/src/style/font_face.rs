@@ -0,0 +1,25 @@
+use crate::style::{FontStyle, FontWeight};
+
+/// Font file registered for a font family by a `@font-face` rule in a style sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+
+    /// Path of the font file, already resolved relative to the declaring style sheet.
+    pub path: String,
+
+    /// Index of the face within a font collection file.
+    pub index: u32,
+
+    /// Range of weights covered by the font file, for example `100` to `900` for a variable font.
+    pub weight: (FontWeight, FontWeight),
+
+    pub style: FontStyle,
+}
+
+impl FontFace {
+    /// Whether the font face belongs to the given family name (case-insensitive).
+    pub fn is_of_family(&self, family: &str) -> bool {
+        self.family.eq_ignore_ascii_case(family)
+    }
+}
@@ -6,7 +6,10 @@ use unit::Distance;
 use unit::DistanceUnit::{Centimeter, Millimeter, Points};
 
 pub use crate::style::class::ClassName;
+pub use crate::style::color::Color;
 pub use crate::style::definition::StyleDefinition;
+pub use crate::style::distance::{DistanceExpression, DistanceExpressionValue, StyleDistance};
+pub use crate::style::font_face::FontFace;
 pub use crate::style::font_stretch::FontStretch;
 pub use crate::style::font_style::FontStyle;
 pub use crate::style::font_weight::FontWeight;
@@ -16,9 +19,16 @@ pub use crate::style::pseudo_class::PseudoClass;
 use crate::style::resolver::StyleResolver;
 pub use crate::style::resolver::StyleResolvingContext;
 pub use crate::style::text_alignment::TextAlignment;
+pub use crate::style::text_decoration::TextDecoration;
+pub use crate::style::text_position::TextPosition;
+pub use crate::style::text_transform::TextTransform;
+pub use crate::style::white_space::WhiteSpace;
 
 mod class;
+mod color;
 mod definition;
+mod distance;
+mod font_face;
 mod font_family;
 mod font_stretch;
 mod font_style;
@@ -29,16 +39,37 @@ mod node;
 mod pseudo_class;
 mod resolver;
 mod text_alignment;
+mod text_decoration;
+mod text_position;
+mod text_transform;
+mod white_space;
 
 const ROOT_NODE_NAME: &'static str = "document";
 const HEADING_NODE_NAME: &'static str = "heading";
 const BOLD_NODE_NAME: &'static str = "b";
 const ITALIC_NODE_NAME: &'static str = "i";
+const UNDERLINE_NODE_NAME: &'static str = "u";
+const STRIKE_THROUGH_NODE_NAME: &'static str = "s";
+const SUPERSCRIPT_NODE_NAME: &'static str = "sup";
+const SUBSCRIPT_NODE_NAME: &'static str = "sub";
+const LINK_NODE_NAME: &'static str = "link";
+const LINK_COLOR: Color = Color::new(0, 82, 204);
+const CODE_NODE_NAME: &'static str = "code";
+const CODE_BLOCK_NODE_NAMES: [&'static str; 2] = ["pre", "code-block"];
+const CODE_TOKEN_NODE_NAME: &'static str = "code-token";
+const CODE_TOKEN_COLORS: [(&'static str, Color); 5] = [
+    ("keyword", Color::new(166, 38, 164)),
+    ("string", Color::new(80, 161, 79)),
+    ("comment", Color::new(160, 161, 167)),
+    ("number", Color::new(152, 104, 1)),
+    ("type", Color::new(193, 132, 1)),
+];
 
 pub struct DocumentStyles {
     styles: HashMap<StyleId, StyleDefinition>,
     resolver: StyleResolver,
     style_id_counter: StyleId,
+    font_faces: Vec<FontFace>,
 }
 
 impl DocumentStyles {
@@ -47,6 +78,7 @@ impl DocumentStyles {
             styles: HashMap::new(),
             resolver: StyleResolver::new(),
             style_id_counter: 0,
+            font_faces: Vec::new(),
         };
 
         fill_default_styles(&mut result);
@@ -76,6 +108,19 @@ impl DocumentStyles {
             .collect()
     }
 
+    /// Font faces in the order of their registration.
+    pub fn font_faces(&self) -> &[FontFace] {
+        &self.font_faces
+    }
+
+    pub fn register_font_face(&mut self, font_face: FontFace) {
+        self.font_faces.push(font_face);
+    }
+
+    pub fn register_root_style_definition(&mut self, style_definition: StyleDefinition) {
+        self.register_style_definition(&ROOT_NODE_NAME.into(), None, None, style_definition);
+    }
+
     pub fn register_style_definition(
         &mut self,
         node_name: &NodeName,
@@ -99,20 +144,21 @@ fn fill_default_styles(styles: &mut DocumentStyles) {
         None,
         StyleDefinition {
             styles: vec![
-                Style::FontSize(Distance::new(12.0, Points)),
+                Style::FontSize(Distance::new(12.0, Points).into()),
                 Style::LineHeight(1.25),
-                Style::MarginTop(Distance::new(2.0, Centimeter)),
-                Style::MarginBottom(Distance::new(2.0, Centimeter)),
-                Style::MarginLeft(Distance::new(2.0, Centimeter)),
-                Style::MarginRight(Distance::new(2.0, Centimeter)),
-                Style::Width(Distance::new(210.0, Millimeter)),
-                Style::Height(Distance::new(297.0, Millimeter)),
+                Style::MarginTop(Distance::new(2.0, Centimeter).into()),
+                Style::MarginBottom(Distance::new(2.0, Centimeter).into()),
+                Style::MarginLeft(Distance::new(2.0, Centimeter).into()),
+                Style::MarginRight(Distance::new(2.0, Centimeter).into()),
+                Style::Width(Distance::new(210.0, Millimeter).into()),
+                Style::Height(Distance::new(297.0, Millimeter).into()),
             ],
         },
     );
 
     fill_default_heading_styles(styles);
     fill_default_font_styling(styles);
+    fill_default_code_styles(styles);
 }
 
 fn fill_default_heading_styles(styles: &mut DocumentStyles) {
@@ -132,9 +178,9 @@ fn fill_default_heading_styles(styles: &mut DocumentStyles) {
         None,
         StyleDefinition {
             styles: vec![
-                Style::FontSize(fallback_heading_settings.font_size),
-                Style::MarginTop(fallback_heading_settings.margin_top),
-                Style::MarginBottom(fallback_heading_settings.margin_bottom),
+                Style::FontSize(fallback_heading_settings.font_size.into()),
+                Style::MarginTop(fallback_heading_settings.margin_top.into()),
+                Style::MarginBottom(fallback_heading_settings.margin_bottom.into()),
             ],
         },
     );
@@ -173,9 +219,9 @@ fn fill_default_heading_styles(styles: &mut DocumentStyles) {
             Some(PseudoClass::Level(level)),
             StyleDefinition {
                 styles: vec![
-                    Style::FontSize(settings.font_size),
-                    Style::MarginTop(settings.margin_top),
-                    Style::MarginBottom(settings.margin_bottom),
+                    Style::FontSize(settings.font_size.into()),
+                    Style::MarginTop(settings.margin_top.into()),
+                    Style::MarginBottom(settings.margin_bottom.into()),
                 ],
             },
         )
@@ -215,31 +261,165 @@ fn fill_default_font_styling(styles: &mut DocumentStyles) {
             ],
         },
     );
+    styles.register_style_definition(
+        &UNDERLINE_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![Style::TextDecoration(TextDecoration::Underline)],
+        },
+    );
+    styles.register_style_definition(
+        &STRIKE_THROUGH_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![Style::TextDecoration(TextDecoration::StrikeThrough)],
+        },
+    );
+    styles.register_style_definition(
+        &SUPERSCRIPT_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![Style::TextPosition(TextPosition::Superscript)],
+        },
+    );
+    styles.register_style_definition(
+        &SUBSCRIPT_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![Style::TextPosition(TextPosition::Subscript)],
+        },
+    );
+    styles.register_style_definition(
+        &LINK_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![
+                Style::Color(LINK_COLOR),
+                Style::TextDecoration(TextDecoration::Underline),
+            ],
+        },
+    );
+}
+
+fn fill_default_code_styles(styles: &mut DocumentStyles) {
+    styles.register_style_definition(
+        &CODE_NODE_NAME.into(),
+        None,
+        None,
+        StyleDefinition {
+            styles: vec![Style::FontFamily(FontFamilySource::Type(
+                FontFamilyType::Monospace,
+            ))],
+        },
+    );
+
+    for node_name in CODE_BLOCK_NODE_NAMES {
+        styles.register_style_definition(
+            &node_name.into(),
+            None,
+            None,
+            StyleDefinition {
+                styles: vec![
+                    Style::FontFamily(FontFamilySource::Type(FontFamilyType::Monospace)),
+                    Style::TextAlignment(TextAlignment::Left),
+                    Style::FirstLineIndent(Distance::zero().into()),
+                    Style::WhiteSpace(WhiteSpace::PreWrap),
+                ],
+            },
+        );
+    }
+
+    // Tokens of highlighted code blocks (for example `code-token.keyword`)
+    for (class_name, color) in CODE_TOKEN_COLORS {
+        styles.register_style_definition(
+            &CODE_TOKEN_NODE_NAME.into(),
+            Some(&class_name.into()),
+            None,
+            StyleDefinition {
+                styles: vec![Style::Color(color)],
+            },
+        );
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum Style {
-    Width(Distance),
-    Height(Distance),
+    Width(StyleDistance),
+    Height(StyleDistance),
 
-    MarginTop(Distance),
-    MarginRight(Distance),
-    MarginBottom(Distance),
-    MarginLeft(Distance),
+    MarginTop(StyleDistance),
+    MarginRight(StyleDistance),
+    MarginBottom(StyleDistance),
+    MarginLeft(StyleDistance),
 
-    PaddingTop(Distance),
-    PaddingRight(Distance),
-    PaddingBottom(Distance),
-    PaddingLeft(Distance),
+    PaddingTop(StyleDistance),
+    PaddingRight(StyleDistance),
+    PaddingBottom(StyleDistance),
+    PaddingLeft(StyleDistance),
 
-    FontSize(Distance),
+    FontSize(StyleDistance),
     FontFamily(FontFamilySource),
     FontVariationSettings(FontVariationSettings),
     FontWeight(FontWeight),
     FontStretch(FontStretch),
     FontStyle(FontStyle),
+    TextPosition(TextPosition),
+
+    /// Color of the text, black by default.
+    Color(Color),
 
     LineHeight(f64),
     TextAlignment(TextAlignment),
-    FirstLineIndent(Distance),
+    FirstLineIndent(StyleDistance),
+    LetterSpacing(StyleDistance),
+
+    /// Factor the width of white space between words is scaled with.
+    WordSpacing(f64),
+    TextTransform(TextTransform),
+    WhiteSpace(WhiteSpace),
+    TextDecoration(TextDecoration),
+
+    /// Color of the lines drawn for the text decoration, defaults to the color of the text.
+    TextDecorationColor(Color),
+
+    /// Declaration of a custom property (for example `--accent: #e4002b;`)
+    /// that is inherited by all descendants of the styled node.
+    CustomProperty {
+        name: String,
+        value: String,
+    },
+
+    /// A property whose value references variables and can thus only be parsed
+    /// once the custom properties of the styled node are known during layout.
+    Deferred {
+        key: String,
+        value: String,
+    },
+}
+
+impl Style {
+    /// The distance of a distance-valued style.
+    pub fn distance(&self) -> Option<&StyleDistance> {
+        match self {
+            Style::Width(distance)
+            | Style::Height(distance)
+            | Style::MarginTop(distance)
+            | Style::MarginRight(distance)
+            | Style::MarginBottom(distance)
+            | Style::MarginLeft(distance)
+            | Style::PaddingTop(distance)
+            | Style::PaddingRight(distance)
+            | Style::PaddingBottom(distance)
+            | Style::PaddingLeft(distance)
+            | Style::FontSize(distance)
+            | Style::FirstLineIndent(distance)
+            | Style::LetterSpacing(distance) => Some(distance),
+            _ => None,
+        }
+    }
 }
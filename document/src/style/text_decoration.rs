@@ -0,0 +1,7 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextDecoration {
+    None,
+    Underline,
+    StrikeThrough,
+    Overline,
+}
@@ -0,0 +1,6 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextPosition {
+    Normal,
+    Superscript,
+    Subscript,
+}
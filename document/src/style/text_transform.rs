@@ -0,0 +1,10 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    Capitalize,
+
+    /// Lowercase letters are rendered as small capitals using the fonts `smcp` feature.
+    SmallCaps,
+}
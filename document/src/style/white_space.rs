@@ -0,0 +1,12 @@
+/// Handling of white space and line breaks within the text of a node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhiteSpace {
+    /// Sequences of white space and line breaks collapse into a single space.
+    Normal,
+
+    /// White space and line breaks are kept, lines exceeding the available width overflow.
+    Pre,
+
+    /// White space and line breaks are kept, lines exceeding the available width are wrapped.
+    PreWrap,
+}
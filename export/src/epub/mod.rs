@@ -0,0 +1,494 @@
+//! Export of the document as EPUB 3 e-book packaging the HTML rendering of the document
+//! together with a navigation document built from the sections, the fonts and the images.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use document::structure::{DocumentNode, DocumentNodeValue, DocumentStructure};
+use document::Document;
+
+use crate::epub::zip::ZipWriter;
+use crate::hash::{content_id, to_uuid};
+use crate::html::{section_id, FALLBACK_TITLE};
+use crate::pdf::metadata::DateTime;
+use crate::result::ExportResult;
+use crate::xml::escape_xml;
+
+mod zip;
+
+/// Directory of the container holding the publication, paths of the package document are relative to it.
+const PUBLICATION_DIRECTORY: &str = "EPUB";
+const PACKAGE_FILE_NAME: &str = "package.opf";
+const CONTENT_FILE_NAME: &str = "content.xhtml";
+const NAVIGATION_FILE_NAME: &str = "nav.xhtml";
+const CSS_FILE_NAME: &str = "style.css";
+
+const CONTAINER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+<rootfiles>\n\
+<rootfile full-path=\"EPUB/package.opf\" media-type=\"application/oebps-package+xml\"/>\n\
+</rootfiles>\n\
+</container>\n";
+
+pub(crate) fn export_as_epub(document: &Document) -> ExportResult<Vec<u8>> {
+    // Reading systems only show images contained in the publication
+    if let Some(source) = find_remote_image(&document.structure, document.structure.root()) {
+        return Err(format!(
+            "Remote image '{}' cannot be exported as EPUB, download it and refer to the local file",
+            source
+        )
+        .into());
+    }
+
+    let page = crate::html::render(document, CSS_FILE_NAME)?;
+
+    let mut manifest = vec![
+        ManifestItem::new(NAVIGATION_FILE_NAME, Some("nav")),
+        ManifestItem::new(CONTENT_FILE_NAME, None),
+        ManifestItem::new(CSS_FILE_NAME, None),
+    ];
+    manifest.extend(
+        page.resources
+            .files()
+            .iter()
+            .map(|resource| ManifestItem::new(&resource.path, None)),
+    );
+
+    let mut zip = ZipWriter::default();
+
+    // The mimetype must be the first and uncompressed file of the container
+    zip.add_stored("mimetype", b"application/epub+zip");
+    zip.add_deflated("META-INF/container.xml", CONTAINER.as_bytes())?;
+    zip.add_deflated(
+        &publication_path(PACKAGE_FILE_NAME),
+        to_package_document(document, &manifest)?.as_bytes(),
+    )?;
+    zip.add_deflated(
+        &publication_path(NAVIGATION_FILE_NAME),
+        to_navigation_document(document)?.as_bytes(),
+    )?;
+    zip.add_deflated(
+        &publication_path(CONTENT_FILE_NAME),
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", page.html).as_bytes(),
+    )?;
+    zip.add_deflated(&publication_path(CSS_FILE_NAME), page.css.as_bytes())?;
+    for resource in page.resources.files() {
+        zip.add_deflated(&publication_path(&resource.path), &resource.read()?)?;
+    }
+
+    Ok(zip.finish())
+}
+
+/// Source of the first image below the node that is not a local file.
+fn find_remote_image<'a>(
+    structure: &'a DocumentStructure,
+    node: &'a DocumentNode,
+) -> Option<&'a str> {
+    if let DocumentNodeValue::Image { source, .. } = &node.value {
+        if source.contains("://") {
+            return Some(source);
+        }
+    }
+
+    node.children()
+        .iter()
+        .filter_map(|child_id| structure.get_node(*child_id))
+        .find_map(|child| find_remote_image(structure, child))
+}
+
+fn publication_path(path: &str) -> String {
+    format!("{}/{}", PUBLICATION_DIRECTORY, path)
+}
+
+struct ManifestItem {
+    path: String,
+    media_type: &'static str,
+    properties: Option<&'static str>,
+}
+
+impl ManifestItem {
+    fn new(path: &str, properties: Option<&'static str>) -> Self {
+        Self {
+            path: path.to_owned(),
+            media_type: media_type(path),
+            properties,
+        }
+    }
+}
+
+fn media_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "xhtml" => "application/xhtml+xml",
+        "css" => "text/css",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Package document with the meta data, the manifest of all files and the reading order.
+fn to_package_document(document: &Document, manifest: &[ManifestItem]) -> ExportResult<String> {
+    let meta_data = &document.meta_data;
+    let title = meta_data.title.as_deref().unwrap_or(FALLBACK_TITLE);
+
+    // The identifier stays the same for all exports of the same version of the publication
+    let mut identity = Vec::new();
+    identity.extend_from_slice(title.as_bytes());
+    identity.extend_from_slice(meta_data.version.value.as_bytes());
+    for author in &meta_data.authors {
+        identity.extend_from_slice(author.name.as_bytes());
+    }
+
+    let mut opf = String::new();
+    writeln!(opf, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        opf,
+        "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"uid\" xml:lang=\"{}\">",
+        escape_xml(&meta_data.language.tag())
+    )?;
+    writeln!(
+        opf,
+        "<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">"
+    )?;
+    writeln!(
+        opf,
+        "<dc:identifier id=\"uid\">urn:uuid:{}</dc:identifier>",
+        to_uuid(&content_id(&identity))
+    )?;
+    writeln!(opf, "<dc:title>{}</dc:title>", escape_xml(title))?;
+    writeln!(
+        opf,
+        "<dc:language>{}</dc:language>",
+        escape_xml(&meta_data.language.tag())
+    )?;
+    for author in &meta_data.authors {
+        writeln!(opf, "<dc:creator>{}</dc:creator>", escape_xml(&author.name))?;
+    }
+    if let Some(subject) = &meta_data.subject {
+        writeln!(
+            opf,
+            "<dc:description>{}</dc:description>",
+            escape_xml(subject)
+        )?;
+    }
+    for keyword in &meta_data.keywords {
+        writeln!(opf, "<dc:subject>{}</dc:subject>", escape_xml(keyword))?;
+    }
+    writeln!(
+        opf,
+        "<meta property=\"dcterms:hasVersion\">{}</meta>",
+        escape_xml(&meta_data.version.value)
+    )?;
+    writeln!(
+        opf,
+        "<meta property=\"dcterms:modified\">{}</meta>",
+        DateTime::now().to_xmp_date()
+    )?;
+    writeln!(opf, "</metadata>")?;
+
+    writeln!(opf, "<manifest>")?;
+    for (index, item) in manifest.iter().enumerate() {
+        write!(
+            opf,
+            "<item id=\"item-{}\" href=\"{}\" media-type=\"{}\"",
+            index,
+            escape_xml(&item.path),
+            item.media_type
+        )?;
+        if let Some(properties) = item.properties {
+            write!(opf, " properties=\"{}\"", properties)?;
+        }
+        writeln!(opf, "/>")?;
+    }
+    writeln!(opf, "</manifest>")?;
+
+    let content_index = manifest
+        .iter()
+        .position(|item| item.path == CONTENT_FILE_NAME)
+        .unwrap_or_default();
+    writeln!(opf, "<spine>")?;
+    writeln!(opf, "<itemref idref=\"item-{}\"/>", content_index)?;
+    writeln!(opf, "</spine>")?;
+    writeln!(opf, "</package>")?;
+
+    Ok(opf)
+}
+
+/// Navigation document with the table of contents built from the section tree.
+fn to_navigation_document(document: &Document) -> ExportResult<String> {
+    let meta_data = &document.meta_data;
+    let title = meta_data.title.as_deref().unwrap_or(FALLBACK_TITLE);
+
+    let mut entries = String::new();
+    write_navigation_entries(&mut entries, &document.structure, document.structure.root())?;
+
+    // The list of contents must not be empty
+    if entries.is_empty() {
+        writeln!(
+            entries,
+            "<li><a href=\"{}\">{}</a></li>",
+            CONTENT_FILE_NAME,
+            escape_xml(title)
+        )?;
+    }
+
+    let mut nav = String::new();
+    writeln!(nav, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(nav, "<!DOCTYPE html>")?;
+    writeln!(
+        nav,
+        "<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{0}\" xml:lang=\"{0}\">",
+        escape_xml(&meta_data.language.tag())
+    )?;
+    writeln!(nav, "<head>")?;
+    writeln!(nav, "<meta charset=\"utf-8\"/>")?;
+    writeln!(nav, "<title>{}</title>", escape_xml(title))?;
+    writeln!(nav, "</head>")?;
+    writeln!(nav, "<body>")?;
+    writeln!(nav, "<nav epub:type=\"toc\" id=\"toc\">")?;
+    writeln!(nav, "<h1>{}</h1>", escape_xml(title))?;
+    write!(nav, "<ol>\n{}</ol>\n", entries)?;
+    writeln!(nav, "</nav>")?;
+    writeln!(nav, "</body>")?;
+    writeln!(nav, "</html>")?;
+
+    Ok(nav)
+}
+
+/// Write an entry for each section below the node titled by its heading.
+/// Sections without a heading are left out and their subsections take their place.
+fn write_navigation_entries(
+    entries: &mut String,
+    structure: &DocumentStructure,
+    node: &DocumentNode,
+) -> ExportResult<()> {
+    for child in node
+        .children()
+        .iter()
+        .filter_map(|child_id| structure.get_node(*child_id))
+    {
+        if !matches!(child.value, DocumentNodeValue::Section) {
+            continue;
+        }
+
+        let mut subsections = String::new();
+        write_navigation_entries(&mut subsections, structure, child)?;
+
+        match section_title(structure, child) {
+            Some(title) => {
+                write!(
+                    entries,
+                    "<li><a href=\"{}#{}\">{}</a>",
+                    CONTENT_FILE_NAME,
+                    escape_xml(&section_id(child)),
+                    escape_xml(&title)
+                )?;
+                if !subsections.is_empty() {
+                    write!(entries, "\n<ol>\n{}</ol>\n", subsections)?;
+                }
+                writeln!(entries, "</li>")?;
+            }
+            None => entries.push_str(&subsections),
+        }
+    }
+
+    Ok(())
+}
+
+/// Text of the first heading of the section with white space collapsed.
+fn section_title(structure: &DocumentStructure, section: &DocumentNode) -> Option<String> {
+    let heading = section
+        .children()
+        .iter()
+        .filter_map(|child_id| structure.get_node(*child_id))
+        .find(|child| matches!(child.value, DocumentNodeValue::Heading))?;
+
+    let text = structure.text_content(heading.id);
+    let title = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    Some(title).filter(|title| !title.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::epub::zip::tests::{read_entries, ZipEntry};
+    use crate::test_document::{add_node, add_text, document_of};
+
+    use super::*;
+
+    fn document(image_source: &str) -> Document {
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let section = add_node(&mut structure, root, "section", DocumentNodeValue::Section);
+        let heading = add_node(
+            &mut structure,
+            section,
+            "heading",
+            DocumentNodeValue::Heading,
+        );
+        add_text(&mut structure, heading, "Tom & Jerry");
+        let paragraph = add_node(
+            &mut structure,
+            section,
+            "paragraph",
+            DocumentNodeValue::Paragraph,
+        );
+        add_node(
+            &mut structure,
+            paragraph,
+            "image",
+            DocumentNodeValue::Image {
+                source: image_source.to_owned(),
+                width: None,
+                height: None,
+            },
+        );
+
+        let mut document = document_of(structure, "");
+        document.meta_data.title = Some("<Cartoons>".to_owned());
+
+        document
+    }
+
+    fn entry<'a>(entries: &'a [ZipEntry], name: &str) -> &'a str {
+        let entry = entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .unwrap_or_else(|| panic!("Missing entry '{}'", name));
+
+        std::str::from_utf8(&entry.content).unwrap()
+    }
+
+    /// Values of the attribute in the order they appear in the XML.
+    fn attribute_values<'a>(xml: &'a str, attribute: &str) -> Vec<&'a str> {
+        let prefix = format!(" {}=\"", attribute);
+        xml.match_indices(&prefix)
+            .map(|(index, _)| {
+                let start = index + prefix.len();
+                let end = start + xml[start..].find('"').unwrap();
+                &xml[start..end]
+            })
+            .collect()
+    }
+
+    /// Check that every start tag is closed by a matching end tag and that nothing follows the root.
+    fn assert_well_formed(xml: &str) {
+        let mut open_tags = Vec::new();
+        let mut has_root = false;
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let end = start + rest[start..].find('>').expect("Unterminated tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+
+            if tag.starts_with('?') || tag.starts_with('!') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open_tags.pop(), Some(name.trim()), "in {}", xml);
+                continue;
+            }
+
+            assert!(!open_tags.is_empty() || !has_root, "Second root in {}", xml);
+            has_root = true;
+            if !tag.ends_with('/') {
+                open_tags.push(tag.split_whitespace().next().unwrap());
+            }
+        }
+
+        assert!(has_root && open_tags.is_empty(), "Unclosed {:?}", open_tags);
+    }
+
+    #[test]
+    fn should_start_with_stored_mimetype() {
+        // Given: a document
+        let document = document("");
+
+        // When: exporting it as EPUB
+        let entries = read_entries(&export_as_epub(&document).unwrap());
+
+        // Then: the uncompressed mimetype is the first file of the container
+        assert_eq!(entries[0].name, "mimetype");
+        assert_eq!(entries[0].method, 0);
+        assert_eq!(entries[0].content, b"application/epub+zip");
+    }
+
+    #[test]
+    fn should_reference_contained_files_only() {
+        // Given: a document
+        let document = document("");
+
+        // When: exporting it as EPUB
+        let entries = read_entries(&export_as_epub(&document).unwrap());
+
+        // Then: the container refers to the package document
+        let container = entry(&entries, "META-INF/container.xml");
+        let package_path = attribute_values(container, "full-path");
+        assert_eq!(package_path, vec!["EPUB/package.opf"]);
+
+        // And: each manifest item is a file of the container
+        let package = entry(&entries, package_path[0]);
+        let hrefs = attribute_values(package, "href");
+        assert!(hrefs.contains(&"content.xhtml") && hrefs.contains(&"nav.xhtml"));
+        for href in &hrefs {
+            entry(&entries, &publication_path(href));
+        }
+
+        // And: the spine refers to the manifest item of the content
+        let ids = attribute_values(package, "id");
+        let content_id = ids[hrefs
+            .iter()
+            .position(|href| *href == "content.xhtml")
+            .unwrap()
+            + 1];
+        assert_eq!(attribute_values(package, "idref"), vec![content_id]);
+    }
+
+    #[test]
+    fn should_write_well_formed_documents() {
+        // Given: a document with markup characters in its title and heading
+        let document = document("");
+
+        // When: exporting it as EPUB
+        let entries = read_entries(&export_as_epub(&document).unwrap());
+
+        // Then: the XML files are well-formed and the characters are escaped
+        for name in [
+            "META-INF/container.xml",
+            "EPUB/package.opf",
+            "EPUB/nav.xhtml",
+            "EPUB/content.xhtml",
+        ] {
+            assert_well_formed(entry(&entries, name));
+        }
+        let nav = entry(&entries, "EPUB/nav.xhtml");
+        assert!(nav.contains("<title>&lt;Cartoons&gt;</title>"));
+        assert!(nav.contains(">Tom &amp; Jerry</a>"));
+    }
+
+    #[test]
+    fn should_fail_for_remote_images() {
+        // Given: a document with an image from the web
+        let document = document("https://example.com/image.png");
+
+        // When: exporting it as EPUB
+        let result = export_as_epub(&document);
+
+        // Then: an error names the image
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("https://example.com/image.png"), "{}", error);
+    }
+}
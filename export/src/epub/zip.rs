@@ -0,0 +1,260 @@
+use std::io::Write;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::result::ExportResult;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+/// Version 2.0 of the ZIP format introduced compression with deflate.
+const VERSION: u16 = 20;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// Flag marking the file name as UTF-8.
+const UTF8_FLAG: u16 = 0x0800;
+
+/// Modification date of all files (1980-01-01 00:00), which keeps the archive reproducible.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// Minimal writer of ZIP archives as used by EPUB containers.
+#[derive(Default)]
+pub(crate) struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entry_count: u16,
+}
+
+impl ZipWriter {
+    /// Add the file without compression, which EPUB requires for the `mimetype` file.
+    pub(crate) fn add_stored(&mut self, name: &str, content: &[u8]) {
+        self.add_entry(name, content, content.to_vec(), METHOD_STORED);
+    }
+
+    pub(crate) fn add_deflated(&mut self, name: &str, content: &[u8]) -> ExportResult<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content)?;
+        let compressed = encoder.finish()?;
+
+        self.add_entry(name, content, compressed, METHOD_DEFLATED);
+
+        Ok(())
+    }
+
+    fn add_entry(&mut self, name: &str, content: &[u8], compressed: Vec<u8>, method: u16) {
+        let flags = if name.is_ascii() { 0 } else { UTF8_FLAG };
+        let crc = crc32fast::hash(content);
+        let offset = self.data.len() as u32;
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&flags.to_le_bytes());
+        header.extend_from_slice(&method.to_le_bytes());
+        header.extend_from_slice(&DOS_TIME.to_le_bytes());
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // Extra field length
+
+        self.data
+            .extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        self.data.extend_from_slice(&VERSION.to_le_bytes());
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(&compressed);
+
+        let directory = &mut self.central_directory;
+        directory.extend_from_slice(&CENTRAL_DIRECTORY_HEADER_SIGNATURE.to_le_bytes());
+        directory.extend_from_slice(&VERSION.to_le_bytes()); // Version made by
+        directory.extend_from_slice(&VERSION.to_le_bytes()); // Version needed to extract
+        directory.extend_from_slice(&header);
+        directory.extend_from_slice(&0u16.to_le_bytes()); // File comment length
+        directory.extend_from_slice(&0u16.to_le_bytes()); // Disk number
+        directory.extend_from_slice(&0u16.to_le_bytes()); // Internal file attributes
+        directory.extend_from_slice(&0u32.to_le_bytes()); // External file attributes
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+
+        self.entry_count += 1;
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        let offset = self.data.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.data.extend_from_slice(&self.central_directory);
+
+        self.data
+            .extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // Number of this disk
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // Disk with the central directory
+        self.data.extend_from_slice(&self.entry_count.to_le_bytes());
+        self.data.extend_from_slice(&self.entry_count.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // Comment length
+
+        self.data
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Read;
+
+    use flate2::read::DeflateDecoder;
+
+    use super::*;
+
+    /// Entry of an archive with its name, compression method and uncompressed content.
+    pub(crate) struct ZipEntry {
+        pub(crate) name: String,
+        pub(crate) method: u16,
+        pub(crate) content: Vec<u8>,
+    }
+
+    fn u16_at(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    }
+
+    /// Read the entries of the archive in the order of their local file headers,
+    /// checking the checksums and that the central directory lists each entry.
+    pub(crate) fn read_entries(data: &[u8]) -> Vec<ZipEntry> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while u32_at(data, offset) == LOCAL_FILE_HEADER_SIGNATURE {
+            let method = u16_at(data, offset + 8);
+            let crc = u32_at(data, offset + 14);
+            let compressed_size = u32_at(data, offset + 18) as usize;
+            let size = u32_at(data, offset + 22) as usize;
+            let name_length = u16_at(data, offset + 26) as usize;
+            let extra_length = u16_at(data, offset + 28) as usize;
+
+            let name_start = offset + 30;
+            let data_start = name_start + name_length + extra_length;
+            let name =
+                String::from_utf8(data[name_start..name_start + name_length].to_vec()).unwrap();
+            let compressed = &data[data_start..data_start + compressed_size];
+            let content = match method {
+                METHOD_STORED => compressed.to_vec(),
+                METHOD_DEFLATED => {
+                    let mut content = Vec::new();
+                    DeflateDecoder::new(compressed)
+                        .read_to_end(&mut content)
+                        .unwrap();
+                    content
+                }
+                _ => panic!("Unexpected compression method {}", method),
+            };
+            assert_eq!(content.len(), size, "Size of '{}'", name);
+            assert_eq!(crc32fast::hash(&content), crc, "Checksum of '{}'", name);
+
+            entries.push(ZipEntry {
+                name,
+                method,
+                content,
+            });
+            offset = data_start + compressed_size;
+        }
+
+        let end = data.len() - 22;
+        assert_eq!(u32_at(data, end), END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        assert_eq!(u16_at(data, end + 10) as usize, entries.len());
+        assert_eq!(u32_at(data, end + 16) as usize, offset);
+
+        let mut directory_offset = offset;
+        for entry in &entries {
+            assert_eq!(
+                u32_at(data, directory_offset),
+                CENTRAL_DIRECTORY_HEADER_SIGNATURE
+            );
+            let name_length = u16_at(data, directory_offset + 28) as usize;
+            let name_start = directory_offset + 46;
+            assert_eq!(
+                &data[name_start..name_start + name_length],
+                entry.name.as_bytes()
+            );
+            directory_offset = name_start + name_length;
+        }
+        assert_eq!(directory_offset, end);
+
+        entries
+    }
+
+    #[test]
+    fn should_write_entries_in_order() {
+        // Given: a stored and a deflated file
+        let mut zip = ZipWriter::default();
+        zip.add_stored("mimetype", b"application/epub+zip");
+        zip.add_deflated("EPUB/content.xhtml", "<p>Hello</p>".repeat(10).as_bytes())
+            .unwrap();
+
+        // When: finishing the archive
+        let data = zip.finish();
+
+        // Then: both files are contained with their compression methods and contents
+        let entries = read_entries(&data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "mimetype");
+        assert_eq!(entries[0].method, METHOD_STORED);
+        assert_eq!(entries[0].content, b"application/epub+zip");
+        assert_eq!(entries[1].name, "EPUB/content.xhtml");
+        assert_eq!(entries[1].method, METHOD_DEFLATED);
+        assert_eq!(entries[1].content, "<p>Hello</p>".repeat(10).as_bytes());
+    }
+
+    #[test]
+    fn should_place_stored_file_name_at_fixed_offset() {
+        // Given: an archive starting with the stored mimetype file
+        let mut zip = ZipWriter::default();
+        zip.add_stored("mimetype", b"application/epub+zip");
+
+        // When: finishing the archive
+        let data = zip.finish();
+
+        // Then: the name and content follow the local file header directly, as EPUB requires
+        assert_eq!(&data[30..38], b"mimetype");
+        assert_eq!(&data[38..58], b"application/epub+zip");
+    }
+
+    #[test]
+    fn should_mark_non_ascii_file_names_as_utf8() {
+        // Given: a file with a non-ASCII name
+        let mut zip = ZipWriter::default();
+        zip.add_stored("EPUB/images/0-größe.png", b"");
+
+        // When: finishing the archive
+        let data = zip.finish();
+
+        // Then: the UTF-8 flag is set and the name is kept
+        assert_eq!(u16_at(&data, 6), UTF8_FLAG);
+        assert_eq!(read_entries(&data)[0].name, "EPUB/images/0-größe.png");
+    }
+
+    #[test]
+    fn should_write_empty_archive() {
+        // Given: an archive without files
+        let zip = ZipWriter::default();
+
+        // When: finishing the archive
+        let data = zip.finish();
+
+        // Then: only the end of the central directory is written
+        assert_eq!(data.len(), 22);
+        assert!(read_entries(&data).is_empty());
+    }
+}
@@ -1,3 +1,50 @@
+use unit::DistanceUnit;
+
 pub enum ExportType {
-    PDF
+    PDF,
+
+    /// PDF conforming to PDF/A-2b for long-term archiving.
+    PdfA,
+
+    /// One SVG file per page with the glyphs drawn as outline paths.
+    Svg,
+
+    /// One PNG image per page rasterised with the given resolution in dots per inch.
+    Png {
+        dpi: usize,
+    },
+
+    /// Web page with semantic HTML and a style sheet translated from the document styles.
+    Html,
+
+    /// EPUB 3 e-book packaging the HTML rendering of the document.
+    Epub,
+
+    /// Plain text with paragraphs wrapped at the configured text width.
+    Text,
+
+    /// Markdown with paragraphs wrapped at the configured text width.
+    Markdown,
+
+    /// JSON dump of the pages and elements of the layout with all distances given in the unit,
+    /// for example for snapshot tests.
+    LayoutJson {
+        unit: DistanceUnit,
+    },
+}
+
+impl ExportType {
+    /// Name of the file in the working directory exported to when no output path is given.
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            ExportType::PDF | ExportType::PdfA => "out.pdf",
+            ExportType::Svg => "out.svg",
+            ExportType::Png { .. } => "out.png",
+            ExportType::Html => "out.html",
+            ExportType::Epub => "out.epub",
+            ExportType::Text => "out.txt",
+            ExportType::Markdown => "out.md",
+            ExportType::LayoutJson { .. } => "out.json",
+        }
+    }
 }
@@ -0,0 +1,76 @@
+use document::style::Color;
+
+/// Format the number with at most three decimal places and without trailing zeros,
+/// which keeps the output stable against tiny floating point differences.
+pub(crate) fn number(value: f64) -> String {
+    let formatted = format!("{:.3}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+
+    match trimmed {
+        "-0" | "" => "0".to_owned(),
+        _ => trimmed.to_owned(),
+    }
+}
+
+/// Hexadecimal notation of the color as used by CSS and SVG, for example `#e4002b`.
+pub(crate) fn to_hex(color: Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        color.red(),
+        color.green(),
+        color.blue()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_numbers_with_at_most_three_decimal_places() {
+        // Given: Numbers with more than three decimal places
+        let values = [1.0 / 3.0, 2.0 / 3.0, 12.3456, 0.0005];
+
+        // When: Formatting the numbers
+        let formatted: Vec<String> = values.iter().map(|value| number(*value)).collect();
+
+        // Then: The numbers are rounded to three decimal places
+        assert_eq!(formatted, vec!["0.333", "0.667", "12.346", "0.001"]);
+    }
+
+    #[test]
+    fn should_format_numbers_without_trailing_zeros() {
+        // Given: Whole numbers and numbers with trailing zeros
+        let values = [0.0, 1.0, 12.5, 100.0, -2.25];
+
+        // When: Formatting the numbers
+        let formatted: Vec<String> = values.iter().map(|value| number(*value)).collect();
+
+        // Then: Trailing zeros and decimal points are removed
+        assert_eq!(formatted, vec!["0", "1", "12.5", "100", "-2.25"]);
+    }
+
+    #[test]
+    fn should_format_negative_zero_as_zero() {
+        // Given: Negative zero and negative numbers rounding to zero
+        let values = [-0.0, -0.0004, -0.0000001];
+
+        // When: Formatting the numbers
+        let formatted: Vec<String> = values.iter().map(|value| number(*value)).collect();
+
+        // Then: All numbers are formatted as zero without sign
+        assert_eq!(formatted, vec!["0", "0", "0"]);
+    }
+
+    #[test]
+    fn should_format_color_as_hex() {
+        // Given: A color with components below and above 16
+        let color = Color::new(228, 0, 11);
+
+        // When: Formatting the color
+        let hex = to_hex(color);
+
+        // Then: Each component takes two lowercase digits
+        assert_eq!(hex, "#e4000b");
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use font::{FontId, FontVariationId, GlyphOutlines};
+use layout::element::content::TextSliceContent;
+use layout::element::DocumentLayout;
+
+use crate::result::ExportResult;
+
+/// Outlines of the glyphs of each font variation, loaded once for all pages of an export.
+#[derive(Default)]
+pub(crate) struct GlyphOutlinesCache {
+    outlines: HashMap<(FontId, FontVariationId), GlyphOutlines>,
+}
+
+impl GlyphOutlinesCache {
+    /// Outlines of the font variation the text slice is set in.
+    pub(crate) fn get(
+        &mut self,
+        document_layout: &DocumentLayout,
+        content: &TextSliceContent,
+    ) -> ExportResult<&GlyphOutlines> {
+        let outlines = match self.outlines.entry((content.font, content.font_variation)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(
+                document_layout
+                    .get_font(&content.font)
+                    .and_then(|font| font.glyph_outlines(&content.font_variation))
+                    .ok_or("Subsetted font data must be present during export")?,
+            ),
+        };
+
+        Ok(outlines)
+    }
+}
@@ -0,0 +1,35 @@
+/// Offset bases of the two 64-bit FNV-1a hashes making up a 128-bit ID.
+const OFFSET_BASES: [u64; 2] = [0xcbf29ce484222325, 0x6c62272e07bb0142];
+
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 128-bit ID derived from the given content, thus exporting the same content again results in the same ID.
+pub(crate) fn content_id(content: &[u8]) -> Vec<u8> {
+    OFFSET_BASES
+        .iter()
+        .flat_map(|offset_basis| fnv1a(content, *offset_basis).to_be_bytes())
+        .collect()
+}
+
+fn fnv1a(bytes: &[u8], offset_basis: u64) -> u64 {
+    bytes.iter().fold(offset_basis, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Format the 16 bytes of the ID as UUID, for example `0a1b2c3d-...`.
+pub(crate) fn to_uuid(id: &[u8]) -> String {
+    let hex: String = id.iter().map(|byte| format!("{:02x}", byte)).collect();
+    if hex.len() != 32 {
+        return hex;
+    }
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use document::style::{
+    DistanceExpression, FontFace, FontFamilySource, FontStyle, Style, StyleDistance, TextAlignment,
+    TextDecoration, TextPosition, TextTransform, WhiteSpace,
+};
+use unit::DistanceUnit;
+
+use crate::format::{number, to_hex};
+use crate::html::Resources;
+use crate::result::ExportResult;
+
+/// Directory next to the HTML file the used font files are copied to.
+const FONT_DIRECTORY: &str = "fonts";
+
+/// Style sheet collecting one rule per distinct set of resolved styles.
+/// Elements with the same resolved styles share the class of the rule.
+#[derive(Default)]
+pub(crate) struct StyleSheet {
+    rules: Vec<(String, Vec<String>)>,
+    classes: HashMap<Vec<String>, String>,
+
+    /// Font families referenced by name in the order of their first use.
+    used_families: Vec<String>,
+
+    /// Font files referenced by path, with the family name generated for them.
+    font_paths: Vec<(String, String)>,
+}
+
+impl StyleSheet {
+    /// Translate the resolved styles of a node and return the class name of the matching rule.
+    /// The root node styles the page, so its size and margins set the width and padding of the body.
+    pub(crate) fn add(&mut self, styles: &[Style], is_root: bool) -> Option<String> {
+        let declarations: Vec<String> = styles
+            .iter()
+            .filter_map(|style| self.declaration(style, is_root))
+            .collect();
+        if declarations.is_empty() {
+            return None;
+        }
+
+        if let Some(class_name) = self.classes.get(&declarations) {
+            return Some(class_name.clone());
+        }
+
+        let class_name = format!("style-{}", self.rules.len());
+        self.classes
+            .insert(declarations.clone(), class_name.clone());
+        self.rules.push((class_name.clone(), declarations));
+
+        Some(class_name)
+    }
+
+    fn declaration(&mut self, style: &Style, is_root: bool) -> Option<String> {
+        let declaration = match style {
+            Style::Width(distance) if is_root => {
+                format!("max-width: {}", to_css_distance(distance))
+            }
+            Style::Height(_) if is_root => return None,
+            Style::MarginTop(distance) if is_root => {
+                format!("padding-top: {}", to_css_distance(distance))
+            }
+            Style::MarginRight(distance) if is_root => {
+                format!("padding-right: {}", to_css_distance(distance))
+            }
+            Style::MarginBottom(distance) if is_root => {
+                format!("padding-bottom: {}", to_css_distance(distance))
+            }
+            Style::MarginLeft(distance) if is_root => {
+                format!("padding-left: {}", to_css_distance(distance))
+            }
+            Style::Width(distance) => format!("width: {}", to_css_distance(distance)),
+            Style::Height(distance) => format!("height: {}", to_css_distance(distance)),
+            Style::MarginTop(distance) => format!("margin-top: {}", to_css_distance(distance)),
+            Style::MarginRight(distance) => format!("margin-right: {}", to_css_distance(distance)),
+            Style::MarginBottom(distance) => {
+                format!("margin-bottom: {}", to_css_distance(distance))
+            }
+            Style::MarginLeft(distance) => format!("margin-left: {}", to_css_distance(distance)),
+            Style::PaddingTop(distance) => format!("padding-top: {}", to_css_distance(distance)),
+            Style::PaddingRight(distance) => {
+                format!("padding-right: {}", to_css_distance(distance))
+            }
+            Style::PaddingBottom(distance) => {
+                format!("padding-bottom: {}", to_css_distance(distance))
+            }
+            Style::PaddingLeft(distance) => format!("padding-left: {}", to_css_distance(distance)),
+            Style::FontSize(distance) => format!("font-size: {}", to_css_distance(distance)),
+            Style::FontFamily(font_family) => {
+                format!("font-family: {}", self.font_family(font_family)?)
+            }
+            Style::FontVariationSettings(settings) => {
+                let variations: Vec<String> = settings
+                    .variations
+                    .iter()
+                    .map(|variation| format!("\"{}\" {}", variation.name, variation.value))
+                    .collect();
+                format!("font-variation-settings: {}", variations.join(", "))
+            }
+            Style::FontWeight(weight) => format!("font-weight: {}", weight),
+            Style::FontStretch(stretch) => format!("font-stretch: {}%", stretch * 100.0),
+            Style::FontStyle(font_style) => {
+                format!("font-style: {}", to_css_font_style(*font_style))
+            }
+            Style::TextPosition(position) => format!(
+                "vertical-align: {}",
+                match position {
+                    TextPosition::Normal => "baseline",
+                    TextPosition::Superscript => "super",
+                    TextPosition::Subscript => "sub",
+                }
+            ),
+            Style::Color(color) => format!("color: {}", to_hex(*color)),
+            Style::LineHeight(line_height) => format!("line-height: {}", line_height),
+            Style::TextAlignment(alignment) => format!(
+                "text-align: {}",
+                match alignment {
+                    TextAlignment::Left => "left",
+                    TextAlignment::Center => "center",
+                    TextAlignment::Right => "right",
+                    TextAlignment::Justify => "justify",
+                }
+            ),
+            Style::FirstLineIndent(distance) => {
+                format!("text-indent: {}", to_css_distance(distance))
+            }
+            Style::LetterSpacing(distance) => {
+                format!("letter-spacing: {}", to_css_distance(distance))
+            }
+            // The factor scales the white space between words, which is about a quarter em wide
+            Style::WordSpacing(factor) => format!("word-spacing: {}em", (factor - 1.0) * 0.25),
+            Style::TextTransform(TextTransform::SmallCaps) => {
+                "font-variant-caps: small-caps".to_owned()
+            }
+            Style::TextTransform(transform) => format!(
+                "text-transform: {}",
+                match transform {
+                    TextTransform::Uppercase => "uppercase",
+                    TextTransform::Lowercase => "lowercase",
+                    TextTransform::Capitalize => "capitalize",
+                    TextTransform::None | TextTransform::SmallCaps => "none",
+                }
+            ),
+            Style::WhiteSpace(white_space) => format!(
+                "white-space: {}",
+                match white_space {
+                    WhiteSpace::Normal => "normal",
+                    WhiteSpace::Pre => "pre",
+                    WhiteSpace::PreWrap => "pre-wrap",
+                }
+            ),
+            Style::TextDecoration(decoration) => format!(
+                "text-decoration-line: {}",
+                match decoration {
+                    TextDecoration::None => "none",
+                    TextDecoration::Underline => "underline",
+                    TextDecoration::StrikeThrough => "line-through",
+                    TextDecoration::Overline => "overline",
+                }
+            ),
+            Style::TextDecorationColor(color) => {
+                format!("text-decoration-color: {}", to_hex(*color))
+            }
+            // Variables have already been resolved into the styles they are used in
+            Style::CustomProperty { .. } | Style::Deferred { .. } => return None,
+        };
+
+        Some(declaration)
+    }
+
+    fn font_family(&mut self, font_family: &FontFamilySource) -> Option<String> {
+        match font_family {
+            FontFamilySource::Default => None,
+            FontFamilySource::Type(font_family_type) => Some(font_family_type.name().to_owned()),
+            FontFamilySource::Name(name) => {
+                if !self.used_families.contains(name) {
+                    self.used_families.push(name.clone());
+                }
+                Some(format!("\"{}\"", name))
+            }
+            FontFamilySource::Path(path) => {
+                let family = match self.font_paths.iter().find(|(p, _)| p == path) {
+                    Some((_, family)) => family.clone(),
+                    None => {
+                        let family = format!("font-{}", self.font_paths.len());
+                        self.font_paths.push((path.clone(), family.clone()));
+                        family
+                    }
+                };
+                Some(format!("\"{}\"", family))
+            }
+        }
+    }
+
+    /// Write the style sheet, the font files it links are added to the resources of the page.
+    pub(crate) fn write(
+        &self,
+        font_faces: &[FontFace],
+        resources: &mut Resources,
+    ) -> ExportResult<String> {
+        let mut css = String::new();
+
+        for family in &self.used_families {
+            for font_face in font_faces.iter().filter(|face| face.is_of_family(family)) {
+                css.push_str(&format!(
+                    "@font-face {{\n  font-family: \"{}\";\n  src: url(\"{}\");\n  \
+                    font-weight: {} {};\n  font-style: {};\n}}\n\n",
+                    family,
+                    resources.add(FONT_DIRECTORY, &font_face.path)?,
+                    font_face.weight.0,
+                    font_face.weight.1,
+                    to_css_font_style(font_face.style)
+                ));
+            }
+        }
+        for (path, family) in &self.font_paths {
+            css.push_str(&format!(
+                "@font-face {{\n  font-family: \"{}\";\n  src: url(\"{}\");\n}}\n\n",
+                family,
+                resources.add(FONT_DIRECTORY, path)?
+            ));
+        }
+
+        for (class_name, declarations) in &self.rules {
+            css.push_str(&format!(".{} {{\n", class_name));
+            for declaration in declarations {
+                css.push_str(&format!("  {};\n", declaration));
+            }
+            css.push_str("}\n\n");
+        }
+
+        Ok(css)
+    }
+}
+
+fn to_css_distance(distance: &StyleDistance) -> String {
+    match distance {
+        StyleDistance::Absolute(distance) => {
+            format!("{}pt", number(distance.value(DistanceUnit::Points)))
+        }
+        StyleDistance::Em(value) => format!("{}em", value),
+        StyleDistance::Rem(value) => format!("{}rem", value),
+        StyleDistance::Ex(value) => format!("{}ex", value),
+        StyleDistance::Ch(value) => format!("{}ch", value),
+        StyleDistance::Percent(value) => format!("{}%", value),
+        StyleDistance::Calc(expression) => {
+            format!("calc({})", to_css_expression(expression, false))
+        }
+    }
+}
+
+/// Nested operations are put in parentheses, since the expression tree does not know about precedence.
+fn to_css_expression(expression: &DistanceExpression, nested: bool) -> String {
+    let operation = |left: &DistanceExpression, operator: &str, right: &DistanceExpression| {
+        let operation = format!(
+            "{} {} {}",
+            to_css_expression(left, true),
+            operator,
+            to_css_expression(right, true)
+        );
+
+        if nested {
+            format!("({})", operation)
+        } else {
+            operation
+        }
+    };
+
+    match expression {
+        DistanceExpression::Distance(distance) => to_css_distance(distance),
+        DistanceExpression::Number(number) => number.to_string(),
+        DistanceExpression::Add(left, right) => operation(left, "+", right),
+        DistanceExpression::Subtract(left, right) => operation(left, "-", right),
+        DistanceExpression::Multiply(left, right) => operation(left, "*", right),
+        DistanceExpression::Divide(left, right) => operation(left, "/", right),
+    }
+}
+
+fn to_css_font_style(font_style: FontStyle) -> &'static str {
+    match font_style {
+        FontStyle::Normal => "normal",
+        FontStyle::Italic => "italic",
+        FontStyle::Oblique => "oblique",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_share_class_of_same_styles() {
+        // Given: a style sheet with a rule for bold text
+        let mut style_sheet = StyleSheet::default();
+        let bold = style_sheet.add(&[Style::FontWeight(700.0)], false);
+
+        // When: adding the same, different and no translatable styles
+        let same = style_sheet.add(&[Style::FontWeight(700.0)], false);
+        let different = style_sheet.add(&[Style::FontWeight(400.0)], false);
+        let none = style_sheet.add(&[], false);
+
+        // Then: same styles share the class and no styles need no class
+        assert_eq!(bold.as_deref(), Some("style-0"));
+        assert_eq!(same.as_deref(), Some("style-0"));
+        assert_eq!(different.as_deref(), Some("style-1"));
+        assert_eq!(none, None);
+    }
+}
@@ -0,0 +1,628 @@
+//! Export of the document as web page with semantic HTML and a style sheet translated from the document styles.
+//! Unlike the other exporters it works from the document structure rather than the paginated layout.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use document::structure::{DocumentNode, DocumentNodeValue, DocumentStructure, LinkTarget};
+use document::style::{NodeName, StyleResolvingContext};
+use document::Document;
+use style::resolve_variables;
+
+use crate::html::css::StyleSheet;
+use crate::node::{is_block, ALT_ATTRIBUTE, MAX_HEADING_LEVEL};
+use crate::output::{companion_file_name, ExportedFile};
+use crate::result::ExportResult;
+use crate::xml::escape_xml;
+
+mod css;
+
+/// Title of documents without a title, since HTML pages require one.
+pub(crate) const FALLBACK_TITLE: &str = "Untitled";
+
+/// Directory next to the HTML file the images are copied to.
+const IMAGE_DIRECTORY: &str = "images";
+
+/// Export the page with the given file name, the style sheet is named after it.
+pub(crate) fn export_as_html(
+    document: &Document,
+    file_name: &str,
+) -> ExportResult<Vec<ExportedFile>> {
+    let css_file_name = companion_file_name(file_name, "css");
+    let page = render(document, &css_file_name)?;
+
+    let mut files = vec![
+        ExportedFile::new(file_name, page.html.into_bytes()),
+        ExportedFile::new(&css_file_name, page.css.into_bytes()),
+    ];
+    for resource in page.resources.files() {
+        files.push(ExportedFile::new(&resource.path, resource.read()?));
+    }
+
+    Ok(files)
+}
+
+/// Document rendered as HTML page, which is also well-formed XHTML.
+pub(crate) struct HtmlPage {
+    pub(crate) html: String,
+    pub(crate) css: String,
+
+    /// Fonts and images the page links to.
+    pub(crate) resources: Resources,
+}
+
+/// Render the document as HTML page linking the style sheet at the given path.
+pub(crate) fn render(document: &Document, css_path: &str) -> ExportResult<HtmlPage> {
+    let mut ctx = HtmlContext {
+        document,
+        style_sheet: StyleSheet::default(),
+        resources: Resources::default(),
+        section_level: 0,
+        custom_properties: HashMap::new(),
+    };
+
+    let mut body = String::new();
+    ctx.write_node(&mut body, document.structure.root())?;
+
+    let meta_data = &document.meta_data;
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(
+        html,
+        "<html xmlns=\"http://www.w3.org/1999/xhtml\" lang=\"{0}\" xml:lang=\"{0}\">",
+        escape_xml(&meta_data.language.tag())
+    )?;
+    writeln!(html, "<head>")?;
+    writeln!(html, "<meta charset=\"utf-8\"/>")?;
+    writeln!(
+        html,
+        "<title>{}</title>",
+        escape_xml(meta_data.title.as_deref().unwrap_or(FALLBACK_TITLE))
+    )?;
+    let authors: Vec<&str> = meta_data
+        .authors
+        .iter()
+        .map(|author| author.name.as_str())
+        .collect();
+    if !authors.is_empty() {
+        writeln!(
+            html,
+            "<meta name=\"author\" content=\"{}\"/>",
+            escape_xml(&authors.join(", "))
+        )?;
+    }
+    if let Some(subject) = &meta_data.subject {
+        writeln!(
+            html,
+            "<meta name=\"description\" content=\"{}\"/>",
+            escape_xml(subject)
+        )?;
+    }
+    if !meta_data.keywords.is_empty() {
+        writeln!(
+            html,
+            "<meta name=\"keywords\" content=\"{}\"/>",
+            escape_xml(&meta_data.keywords.join(", "))
+        )?;
+    }
+    writeln!(
+        html,
+        "<link rel=\"stylesheet\" href=\"{}\"/>",
+        escape_xml(css_path)
+    )?;
+    writeln!(html, "</head>")?;
+    html.push_str(&body);
+    writeln!(html, "</html>")?;
+
+    let css = ctx
+        .style_sheet
+        .write(document.styles.font_faces(), &mut ctx.resources)?;
+
+    Ok(HtmlPage {
+        html,
+        css,
+        resources: ctx.resources,
+    })
+}
+
+/// File the page links to with its path relative to the page and the path of the file it is copied from.
+pub(crate) struct Resource {
+    pub(crate) path: String,
+    pub(crate) source: String,
+}
+
+impl Resource {
+    pub(crate) fn read(&self) -> ExportResult<Vec<u8>> {
+        std::fs::read(&self.source)
+            .map_err(|e| format!("Could not read '{}': {}", self.source, e).into())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Resources {
+    files: Vec<Resource>,
+}
+
+impl Resources {
+    /// Add the file at the source path to the given directory and return its path relative to the page.
+    /// The file name is prefixed by a counter, since files from different directories may share a name.
+    fn add(&mut self, directory: &str, source: &str) -> ExportResult<String> {
+        if let Some(resource) = self.files.iter().find(|file| file.source == source) {
+            return Ok(resource.path.clone());
+        }
+
+        let file_name = Path::new(source)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(format!("Path '{}' does not name a file", source))?;
+        let path = format!("{}/{}-{}", directory, self.files.len(), file_name);
+        self.files.push(Resource {
+            path: path.clone(),
+            source: source.to_owned(),
+        });
+
+        Ok(path)
+    }
+
+    pub(crate) fn files(&self) -> &[Resource] {
+        &self.files
+    }
+}
+
+/// ID of the element of a section, which is its anchor name if it has one.
+pub(crate) fn section_id(node: &DocumentNode) -> String {
+    match node.anchor_name() {
+        Some(anchor_name) => anchor_name.to_owned(),
+        None => format!("section-{}", node.id),
+    }
+}
+
+struct HtmlContext<'a> {
+    document: &'a Document,
+    style_sheet: StyleSheet,
+    resources: Resources,
+
+    /// Number of sections the current node is nested in.
+    section_level: usize,
+
+    /// Custom properties of the current node including the ones inherited from its ancestors.
+    custom_properties: HashMap<String, String>,
+}
+
+impl<'a> HtmlContext<'a> {
+    fn write_node(&mut self, html: &mut String, node: &DocumentNode) -> ExportResult<()> {
+        if let DocumentNodeValue::Text(text) = &node.value {
+            html.push_str(&escape_xml(text));
+            return Ok(());
+        }
+
+        // Custom properties declared for the node are only inherited by its descendants
+        let inherited_custom_properties = self.custom_properties.clone();
+        let result = self.write_element(html, node);
+        self.custom_properties = inherited_custom_properties;
+
+        result
+    }
+
+    fn write_element(&mut self, html: &mut String, node: &DocumentNode) -> ExportResult<()> {
+        let (tag, mut attributes) = self.tag_of(node)?;
+        if let DocumentNodeValue::Section = node.value {
+            attributes.push(("id", section_id(node)));
+        } else if let Some(anchor_name) = node.anchor_name() {
+            attributes.push(("id", anchor_name.to_owned()));
+        }
+        let class_names: Vec<String> = node
+            .class_name()
+            .cloned()
+            .into_iter()
+            .chain(self.resolve_style_class(node)?)
+            .collect();
+        if !class_names.is_empty() {
+            attributes.push(("class", class_names.join(" ")));
+        }
+
+        write!(html, "<{}", tag)?;
+        for (name, value) in &attributes {
+            write!(html, " {}=\"{}\"", name, escape_xml(value))?;
+        }
+
+        // Void elements have neither content nor an end tag
+        if let DocumentNodeValue::Break | DocumentNodeValue::Image { .. } = node.value {
+            html.push_str("/>");
+            return Ok(());
+        }
+        html.push('>');
+        if is_container(&node.value) {
+            html.push('\n');
+        }
+
+        let is_section = matches!(node.value, DocumentNodeValue::Section);
+        if is_section {
+            self.section_level += 1;
+        }
+        for child_id in node.children() {
+            if let Some(child) = self.structure().get_node(*child_id) {
+                self.write_node(html, child)?;
+            }
+        }
+        if is_section {
+            self.section_level -= 1;
+        }
+
+        write!(html, "</{}>", tag)?;
+        if is_block(&node.value) {
+            html.push('\n');
+        }
+
+        Ok(())
+    }
+
+    fn structure(&self) -> &'a DocumentStructure {
+        &self.document.structure
+    }
+
+    /// Element and attributes the node is represented by.
+    /// Images from local files are added to the resources of the page.
+    fn tag_of(
+        &mut self,
+        node: &DocumentNode,
+    ) -> ExportResult<(String, Vec<(&'static str, String)>)> {
+        let tag = match &node.value {
+            DocumentNodeValue::DocumentRoot => "body",
+            DocumentNodeValue::Section => "section",
+            DocumentNodeValue::Heading => {
+                let level = self.section_level.clamp(1, MAX_HEADING_LEVEL);
+                return Ok((format!("h{}", level), Vec::new()));
+            }
+            DocumentNodeValue::Paragraph => "p",
+            DocumentNodeValue::Image { source, .. } => {
+                let alt = node
+                    .attributes
+                    .get(ALT_ATTRIBUTE)
+                    .cloned()
+                    .unwrap_or_default();
+                let source = if source.is_empty() || source.contains("://") {
+                    source.clone()
+                } else {
+                    self.resources.add(IMAGE_DIRECTORY, source)?
+                };
+
+                return Ok(("img".to_owned(), vec![("src", source), ("alt", alt)]));
+            }
+            // Lists do not distinguish between ordered and unordered lists yet
+            DocumentNodeValue::List => "ul",
+            DocumentNodeValue::ListItem => "li",
+            DocumentNodeValue::Table => "table",
+            DocumentNodeValue::TableRow => "tr",
+            DocumentNodeValue::TableCell => "td",
+            DocumentNodeValue::Break => "br",
+            DocumentNodeValue::Bold => "b",
+            DocumentNodeValue::Italic => "i",
+            DocumentNodeValue::Underline => "u",
+            DocumentNodeValue::StrikeThrough => "s",
+            DocumentNodeValue::Superscript => "sup",
+            DocumentNodeValue::Subscript => "sub",
+            DocumentNodeValue::Code => "code",
+            DocumentNodeValue::CodeBlock => "pre",
+            DocumentNodeValue::CodeToken | DocumentNodeValue::Text(_) => "span",
+            DocumentNodeValue::Link(target) => {
+                let href = match target {
+                    LinkTarget::Uri(uri) => uri.clone(),
+                    LinkTarget::Anchor(name) => format!("#{}", name),
+                };
+                return Ok(("a".to_owned(), vec![("href", href)]));
+            }
+        };
+
+        Ok((tag.to_owned(), Vec::new()))
+    }
+
+    /// Resolve the styles of the node the same way the layout does and return the class of the matching rule.
+    /// Variables are resolved against the custom properties of the node and the variables of the document.
+    fn resolve_style_class(&mut self, node: &DocumentNode) -> ExportResult<Option<String>> {
+        let node_name: NodeName = match node.name() {
+            Some(name) => name.into(),
+            None => return Ok(None),
+        };
+        let styles = self.document.styles.resolve(
+            &node_name,
+            node.class_name(),
+            StyleResolvingContext {
+                level: self.section_level,
+            },
+        );
+        let styles = resolve_variables(
+            &styles,
+            &mut self.custom_properties,
+            &self.document.meta_data.variables,
+        )?;
+
+        let is_root = node.parent.is_none();
+        Ok(self.style_sheet.add(&styles, is_root))
+    }
+}
+
+/// Blocks that contain other blocks start their content on a new line.
+fn is_container(value: &DocumentNodeValue) -> bool {
+    matches!(
+        value,
+        DocumentNodeValue::DocumentRoot
+            | DocumentNodeValue::Section
+            | DocumentNodeValue::List
+            | DocumentNodeValue::Table
+            | DocumentNodeValue::TableRow
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use document::structure::{DocumentNodeValue, DocumentStructure};
+    use document::Document;
+
+    use crate::test_document::{add_node, add_node_with_attributes, add_text, document_of};
+
+    use super::*;
+
+    /// Content of the body element of the page.
+    fn body(page: &HtmlPage) -> &str {
+        let start = page.html.find("<body").unwrap();
+        let end = page.html.find("</body>").unwrap() + "</body>\n".len();
+
+        &page.html[start..end]
+    }
+
+    fn variables_document(styles: &str) -> Document {
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        add_node(
+            &mut structure,
+            root,
+            "paragraph",
+            DocumentNodeValue::Paragraph,
+        );
+        add_node(
+            &mut structure,
+            root,
+            "code-block",
+            DocumentNodeValue::CodeBlock,
+        );
+
+        let mut document = document_of(structure, styles);
+        document.meta_data.variables.set("code-color", "#0000ff");
+
+        document
+    }
+
+    #[test]
+    fn should_resolve_variables_of_styles() {
+        // Given: styles using an inherited custom property and a document variable
+        let document = variables_document(
+            "document { --accent: #e4002b; } \
+            paragraph { font.color: var(--accent); } \
+            code-block { font.color: $code-color; }",
+        );
+
+        // When: rendering the document
+        let page = render(&document, "out.css").unwrap();
+
+        // Then: the style sheet contains the resolved colors
+        assert!(page.css.contains("color: #e4002b"), "{}", page.css);
+        assert!(page.css.contains("color: #0000ff"), "{}", page.css);
+    }
+
+    #[test]
+    fn should_fail_for_undefined_variables() {
+        // Given: styles using a custom property declared on no ancestor
+        let document = variables_document(
+            "paragraph { --accent: #e4002b; } \
+            code-block { font.color: var(--accent); }",
+        );
+
+        // When: rendering the document
+        let result = render(&document, "out.css");
+
+        // Then: an error is returned, as the custom property is only inherited by descendants
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_render_heading_levels_of_nested_sections() {
+        // Given: seven nested sections with a heading each
+        let mut structure = DocumentStructure::new();
+        let mut parent = structure.root().id;
+        for level in 1..=7 {
+            let section = add_node_with_attributes(
+                &mut structure,
+                parent,
+                "section",
+                DocumentNodeValue::Section,
+                &[("id", &format!("level-{}", level))],
+            );
+            let heading = add_node(
+                &mut structure,
+                section,
+                "heading",
+                DocumentNodeValue::Heading,
+            );
+            add_text(&mut structure, heading, &format!("Level {}", level));
+            parent = section;
+        }
+        let document = document_of(structure, "");
+
+        // When: rendering the document
+        let page = render(&document, "out.css").unwrap();
+
+        // Then: the heading level follows the section level and stops at six
+        let mut expected = String::from("<body class=\"style-0\">\n");
+        for level in 1..=7 {
+            expected.push_str(&format!(
+                "<section id=\"level-{0}\">\n<h{1} class=\"style-{2}\">Level {0}</h{1}>\n",
+                level,
+                level.min(6),
+                level.min(5)
+            ));
+        }
+        expected.push_str(&"</section>\n".repeat(7));
+        expected.push_str("</body>\n");
+        assert_eq!(body(&page), expected);
+    }
+
+    #[test]
+    fn should_render_paragraphs_lists_and_tables() {
+        // Given: a paragraph with inline formatting, a list and a table
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let paragraph = add_node(
+            &mut structure,
+            root,
+            "paragraph",
+            DocumentNodeValue::Paragraph,
+        );
+        add_text(&mut structure, paragraph, "Tom ");
+        let bold = add_node(&mut structure, paragraph, "b", DocumentNodeValue::Bold);
+        add_text(&mut structure, bold, "&");
+        add_text(&mut structure, paragraph, " <Jerry>");
+        let list = add_node(&mut structure, root, "list", DocumentNodeValue::List);
+        for item in ["One", "Two"] {
+            let list_item = add_node(
+                &mut structure,
+                list,
+                "list-item",
+                DocumentNodeValue::ListItem,
+            );
+            add_text(&mut structure, list_item, item);
+        }
+        let table = add_node(&mut structure, root, "table", DocumentNodeValue::Table);
+        let row = add_node(
+            &mut structure,
+            table,
+            "table-row",
+            DocumentNodeValue::TableRow,
+        );
+        for cell_text in ["A", "B"] {
+            let cell = add_node(
+                &mut structure,
+                row,
+                "table-cell",
+                DocumentNodeValue::TableCell,
+            );
+            add_text(&mut structure, cell, cell_text);
+        }
+        let document = document_of(structure, "");
+
+        // When: rendering the document
+        let page = render(&document, "out.css").unwrap();
+
+        // Then: the nodes are rendered as their HTML elements with escaped text
+        assert_eq!(
+            body(&page),
+            "<body class=\"style-0\">\n\
+            <p>Tom <b class=\"style-1\">&amp;</b> &lt;Jerry&gt;</p>\n\
+            <ul>\n<li>One</li>\n<li>Two</li>\n</ul>\n\
+            <table>\n<tr>\n<td>A</td>\n<td>B</td>\n</tr>\n</table>\n\
+            </body>\n"
+        );
+    }
+
+    #[test]
+    fn should_copy_images_into_image_directory() {
+        // Given: a local image with an alternate description and a remote image without
+        let image_path =
+            std::env::temp_dir().join(format!("letter-html-{}.png", std::process::id()));
+        std::fs::write(&image_path, b"image").unwrap();
+        let image_path = image_path.to_str().unwrap().to_owned();
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let paragraph = add_node(
+            &mut structure,
+            root,
+            "paragraph",
+            DocumentNodeValue::Paragraph,
+        );
+        for source in [image_path.as_str(), "https://example.com/logo.png"] {
+            let alt = if source == image_path {
+                "A \"cat\""
+            } else {
+                ""
+            };
+            add_node_with_attributes(
+                &mut structure,
+                paragraph,
+                "image",
+                DocumentNodeValue::Image {
+                    source: source.to_owned(),
+                    width: None,
+                    height: None,
+                },
+                &[("alt", alt)],
+            );
+        }
+        let document = document_of(structure, "");
+
+        // When: exporting the document as HTML
+        let files = export_as_html(&document, "out.html");
+        std::fs::remove_file(&image_path).unwrap();
+        let files = files.unwrap();
+
+        // Then: the local image is copied next to the page and referenced relative to it
+        let image_name = format!("images/0-letter-html-{}.png", std::process::id());
+        let html = String::from_utf8(files[0].content.clone()).unwrap();
+        assert!(html.contains(&format!(
+            "<p><img src=\"{}\" alt=\"A &quot;cat&quot;\"/>\
+            <img src=\"https://example.com/logo.png\" alt=\"\"/></p>",
+            image_name
+        )));
+
+        // And: the page, the style sheet and the image make up the export
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["out.html", "out.css", image_name.as_str()]);
+        assert_eq!(files[2].content, b"image");
+    }
+
+    #[test]
+    fn should_write_font_faces_of_used_families() {
+        // Given: a registered font face used by name, an unused one and a font file used by its path
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let heading = add_node(&mut structure, root, "heading", DocumentNodeValue::Heading);
+        add_text(&mut structure, heading, "Title");
+        let document = document_of(
+            structure,
+            "@font-face { family: \"Brand\"; src: url(fonts/Brand.otf); weight: 300 700; style: italic; } \
+            @font-face { family: \"Unused\"; src: url(fonts/Unused.otf); } \
+            document { font.family: \"Brand\"; } \
+            heading { font.family: url(fonts/Display.ttf); }",
+        );
+
+        // When: rendering the document
+        let page = render(&document, "out.css").unwrap();
+
+        // Then: only the used fonts are declared, the font file under a generated family name
+        assert!(
+            page.css.starts_with(
+                "@font-face {\n  font-family: \"Brand\";\n  src: url(\"fonts/0-Brand.otf\");\n  \
+                font-weight: 300 700;\n  font-style: italic;\n}\n\n\
+                @font-face {\n  font-family: \"font-0\";\n  src: url(\"fonts/1-Display.ttf\");\n}\n\n"
+            ),
+            "{}",
+            page.css
+        );
+        assert_eq!(
+            page.css.matches("font-family: \"font-0\";").count(),
+            2,
+            "{}",
+            page.css
+        );
+        assert!(!page.css.contains("Unused"), "{}", page.css);
+
+        // And: the font files are added to the resources
+        let paths: Vec<&str> = page
+            .resources
+            .files()
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["fonts/0-Brand.otf", "fonts/1-Display.ttf"]);
+    }
+}
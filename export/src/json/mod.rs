@@ -0,0 +1,191 @@
+//! Export of the document layout as JSON, for example for snapshot tests of the layout or external tools.
+//! Pages, elements and object members are written in a fixed order, thus the same layout always results in the same file.
+
+use document::structure::LinkTarget;
+use layout::element::content::{LayoutElementContent, TextSliceContent};
+use layout::element::{DocumentLayout, LayoutElement, Page};
+use unit::{Distance, DistanceUnit};
+
+use crate::format::to_hex;
+use crate::json::value::JsonValue;
+use crate::result::ExportResult;
+
+mod value;
+
+/// Export the layout with all distances given in the unit.
+pub(crate) fn export_as_layout_json(
+    document_layout: DocumentLayout,
+    unit: DistanceUnit,
+) -> ExportResult<Vec<u8>> {
+    let ctx = JsonContext {
+        document_layout: &document_layout,
+        unit,
+    };
+
+    let pages = document_layout
+        .pages()
+        .iter()
+        .map(|page| ctx.page(page))
+        .collect();
+    let root = JsonValue::Object(vec![
+        ("unit", JsonValue::string(unit.shortform())),
+        ("pages", JsonValue::Array(pages)),
+    ]);
+
+    let mut json = String::new();
+    root.write(&mut json, 0)?;
+    json.push('\n');
+
+    Ok(json.into_bytes())
+}
+
+struct JsonContext<'a, 'b> {
+    document_layout: &'a DocumentLayout<'b>,
+    unit: DistanceUnit,
+}
+
+impl<'a, 'b> JsonContext<'a, 'b> {
+    fn page(&self, page: &Page) -> JsonValue {
+        let constraints = page.constraints();
+        let size = constraints.size();
+
+        let elements = page
+            .elements()
+            .iter()
+            .filter_map(|element_id| self.document_layout.element(element_id))
+            .map(|element| self.element(element))
+            .collect();
+
+        JsonValue::Object(vec![
+            ("number", JsonValue::integer(page.number() as u64)),
+            (
+                "constraints",
+                JsonValue::Object(vec![
+                    ("width", self.distance(size.width)),
+                    ("height", self.distance(size.height)),
+                    ("top", self.distance(constraints.top())),
+                    ("bottom", self.distance(constraints.bottom())),
+                    ("left", self.distance(constraints.left())),
+                    ("right", self.distance(constraints.right())),
+                ]),
+            ),
+            ("elements", JsonValue::Array(elements)),
+        ])
+    }
+
+    fn element(&self, element: &LayoutElement) -> JsonValue {
+        let bounds = element.bounds();
+        let position = bounds.position();
+        let size = bounds.size();
+
+        JsonValue::Object(vec![
+            ("id", JsonValue::integer(element.id().value() as u64)),
+            ("node", JsonValue::integer(element.node())),
+            (
+                "bounds",
+                JsonValue::Object(vec![
+                    ("x", self.distance(position.x())),
+                    ("y", self.distance(position.y())),
+                    ("width", self.distance(size.width)),
+                    ("height", self.distance(size.height)),
+                ]),
+            ),
+            ("content", self.content(element.content())),
+        ])
+    }
+
+    fn content(&self, content: &LayoutElementContent) -> JsonValue {
+        let members = match content {
+            LayoutElementContent::Page => vec![("type", JsonValue::string("page"))],
+            LayoutElementContent::TextSlice(content) => self.text_slice(content),
+            LayoutElementContent::Line(content) => vec![
+                ("type", JsonValue::string("line")),
+                ("thickness", self.distance(content.thickness)),
+                ("color", JsonValue::String(to_hex(content.color))),
+            ],
+            LayoutElementContent::Link(content) => {
+                let target = match &content.target {
+                    LinkTarget::Uri(uri) => ("uri", JsonValue::string(uri)),
+                    LinkTarget::Anchor(name) => ("anchor", JsonValue::string(name)),
+                };
+
+                vec![("type", JsonValue::string("link")), target]
+            }
+            // The layout does not know the source of images yet
+            LayoutElementContent::Image => vec![
+                ("type", JsonValue::string("image")),
+                ("source", JsonValue::Null),
+            ],
+        };
+
+        JsonValue::Object(members)
+    }
+
+    fn text_slice(&self, content: &TextSliceContent) -> Vec<(&'static str, JsonValue)> {
+        let family = self
+            .document_layout
+            .get_font(&content.font)
+            .and_then(|font| font.family_name())
+            .map_or(JsonValue::Null, JsonValue::String);
+        let glyphs = content
+            .glyphs
+            .iter()
+            .map(|glyph| JsonValue::integer(glyph.codepoint as u64))
+            .collect();
+
+        vec![
+            ("type", JsonValue::string("text")),
+            ("text", JsonValue::string(&content.text)),
+            (
+                "font",
+                JsonValue::Object(vec![
+                    ("id", JsonValue::integer(content.font)),
+                    ("variation", JsonValue::integer(content.font_variation)),
+                    ("family", family),
+                ]),
+            ),
+            ("font_size", self.distance(content.font_size)),
+            ("vertical_offset", self.distance(content.vertical_offset)),
+            ("color", JsonValue::String(to_hex(content.color))),
+            ("glyphs", JsonValue::Array(glyphs)),
+        ]
+    }
+
+    fn distance(&self, distance: Distance) -> JsonValue {
+        JsonValue::decimal(distance.value(self.unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_layout::sample_layout;
+
+    use super::*;
+
+    #[test]
+    fn should_match_snapshot_of_sample_layout() {
+        // Given: a layout of two pages with lines, links and text at fractional positions
+        let layout = sample_layout();
+
+        // When: exporting the layout as JSON in millimeters
+        let json = export_as_layout_json(layout, DistanceUnit::Millimeter).unwrap();
+
+        // Then: the file matches the snapshot
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json, include_str!("sample_layout.snapshot.json"));
+    }
+
+    #[test]
+    fn should_export_same_layout_to_same_file() {
+        // Given: the same layout built twice
+        let first_layout = sample_layout();
+        let second_layout = sample_layout();
+
+        // When: exporting both layouts as JSON in points
+        let first = export_as_layout_json(first_layout, DistanceUnit::Points).unwrap();
+        let second = export_as_layout_json(second_layout, DistanceUnit::Points).unwrap();
+
+        // Then: both files have the same bytes
+        assert_eq!(first, second);
+    }
+}
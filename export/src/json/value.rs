@@ -0,0 +1,92 @@
+use std::fmt::Write;
+
+use crate::format::number;
+use crate::result::ExportResult;
+
+/// JSON value whose object members keep the order they were added in, thus writing is deterministic.
+pub(crate) enum JsonValue {
+    Null,
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(&'static str, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Number rounded to three decimal places, which is more precise than needed to compare layouts.
+    pub(crate) fn decimal(value: f64) -> Self {
+        JsonValue::Number(number(value))
+    }
+
+    pub(crate) fn integer(value: u64) -> Self {
+        JsonValue::Number(value.to_string())
+    }
+
+    pub(crate) fn string(value: &str) -> Self {
+        JsonValue::String(value.to_owned())
+    }
+
+    /// Write the value indented by two spaces per level, so that changes show up as separate lines in diffs.
+    pub(crate) fn write(&self, json: &mut String, indentation: usize) -> ExportResult<()> {
+        match self {
+            JsonValue::Null => json.push_str("null"),
+            JsonValue::Number(number) => json.push_str(number),
+            JsonValue::String(value) => write_string(json, value)?,
+            JsonValue::Array(values) if values.is_empty() => json.push_str("[]"),
+            JsonValue::Array(values) => {
+                json.push_str("[\n");
+                for (index, value) in values.iter().enumerate() {
+                    indent(json, indentation + 1);
+                    value.write(json, indentation + 1)?;
+                    end_member(json, index + 1 == values.len());
+                }
+                indent(json, indentation);
+                json.push(']');
+            }
+            JsonValue::Object(members) if members.is_empty() => json.push_str("{}"),
+            JsonValue::Object(members) => {
+                json.push_str("{\n");
+                for (index, (name, value)) in members.iter().enumerate() {
+                    indent(json, indentation + 1);
+                    write_string(json, name)?;
+                    json.push_str(": ");
+                    value.write(json, indentation + 1)?;
+                    end_member(json, index + 1 == members.len());
+                }
+                indent(json, indentation);
+                json.push('}');
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn indent(json: &mut String, indentation: usize) {
+    json.push_str(&"  ".repeat(indentation));
+}
+
+fn end_member(json: &mut String, is_last: bool) {
+    if !is_last {
+        json.push(',');
+    }
+    json.push('\n');
+}
+
+fn write_string(json: &mut String, value: &str) -> ExportResult<()> {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32)?,
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    Ok(())
+}
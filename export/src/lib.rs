@@ -1,15 +1,331 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use document::Document;
 pub use export_type::ExportType;
 use layout::element::DocumentLayout;
+pub use options::ExportOptions;
 use result::ExportResult;
 
+use crate::output::ExportedFile;
+use crate::pdf::PdfConformance;
+use crate::text::TextFormat;
+
+mod epub;
 mod export_type;
+mod format;
+mod glyph_outlines;
+mod hash;
+mod html;
+mod json;
+mod node;
+mod options;
+mod output;
 mod pdf;
+mod png;
 mod result;
+mod svg;
+#[cfg(test)]
+mod test_document;
+#[cfg(test)]
+mod test_layout;
+mod text;
+mod xml;
+
+/// Export the document to the default file of the export type in the working directory, for example `out.pdf`.
+pub fn export(
+    document: &Document,
+    document_layout: DocumentLayout,
+    export_type: ExportType,
+    options: ExportOptions,
+) -> ExportResult<()> {
+    let path = Path::new(export_type.default_file_name());
+
+    export_to_path(document, document_layout, export_type, options, path)
+}
+
+/// Export the document to the file at the given path.
+/// Exports consisting of several files (the pages of SVG and PNG exports or the style sheet
+/// and resources of HTML exports) are written next to it and named after it.
+pub fn export_to_path(
+    document: &Document,
+    document_layout: DocumentLayout,
+    export_type: ExportType,
+    options: ExportOptions,
+    path: &Path,
+) -> ExportResult<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .ok_or(format!(
+            "Output path '{}' does not name a file",
+            path.display()
+        ))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    for file in export_files(document, document_layout, export_type, options, file_name)? {
+        let file_path = directory.join(&file.path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, file.content)
+            .map_err(|e| format!("Could not write '{}': {}", file_path.display(), e))?;
+    }
+
+    Ok(())
+}
 
-pub fn export(document_layout: DocumentLayout, export_type: ExportType) -> ExportResult<()> {
-    match export_type {
-        ExportType::PDF => pdf::export_as_pdf(document_layout),
-    }?;
+/// Export the document to the writer.
+/// Only exports consisting of a single file can be written, see [`export_to_bytes`].
+pub fn export_to_writer<W: Write>(
+    document: &Document,
+    document_layout: DocumentLayout,
+    export_type: ExportType,
+    options: ExportOptions,
+    writer: &mut W,
+) -> ExportResult<()> {
+    let bytes = export_to_bytes(document, document_layout, export_type, options)?;
+    writer.write_all(&bytes)?;
 
     Ok(())
 }
+
+/// Export the document into memory and return the bytes of the exported file.
+/// Fails for exports consisting of several files, like SVG and PNG exports
+/// of documents with more than one page or HTML exports with their style sheet.
+pub fn export_to_bytes(
+    document: &Document,
+    document_layout: DocumentLayout,
+    export_type: ExportType,
+    options: ExportOptions,
+) -> ExportResult<Vec<u8>> {
+    let file_name = export_type.default_file_name();
+    let mut files = export_files(document, document_layout, export_type, options, file_name)?;
+    if files.len() != 1 {
+        return Err(format!(
+            "The export consists of {} files and cannot be written as a single file",
+            files.len()
+        )
+        .into());
+    }
+
+    Ok(files.remove(0).content)
+}
+
+/// Export the document into memory, the name of the output file is used to name accompanying files.
+fn export_files(
+    document: &Document,
+    document_layout: DocumentLayout,
+    export_type: ExportType,
+    options: ExportOptions,
+    file_name: &str,
+) -> ExportResult<Vec<ExportedFile>> {
+    let single_file = |content: Vec<u8>| vec![ExportedFile::new(file_name, content)];
+
+    let files = match export_type {
+        ExportType::PDF => single_file(pdf::export_as_pdf(
+            document,
+            document_layout,
+            &options,
+            PdfConformance::Default,
+        )?),
+        ExportType::PdfA => single_file(pdf::export_as_pdf(
+            document,
+            document_layout,
+            &options,
+            PdfConformance::PdfA2b,
+        )?),
+        ExportType::Svg => svg::export_as_svg(document_layout, file_name)?,
+        ExportType::Png { dpi } => png::export_as_png(document_layout, dpi, file_name)?,
+        ExportType::Html => html::export_as_html(document, file_name)?,
+        ExportType::Epub => single_file(epub::export_as_epub(document)?),
+        ExportType::Text => {
+            single_file(text::export_as_text(document, &options, TextFormat::Plain)?)
+        }
+        ExportType::Markdown => single_file(text::export_as_text(
+            document,
+            &options,
+            TextFormat::Markdown,
+        )?),
+        ExportType::LayoutJson { unit } => {
+            single_file(json::export_as_layout_json(document_layout, unit)?)
+        }
+    };
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use document::meta_data::DocumentMetaData;
+    use document::structure::{DocumentNodeValue, DocumentStructure};
+    use document::style::DocumentStyles;
+
+    use crate::test_document::{add_node, add_node_with_attributes, document_of};
+    use crate::test_layout::sample_layout;
+
+    use super::*;
+
+    fn document() -> Document {
+        Document {
+            meta_data: DocumentMetaData::default(),
+            structure: DocumentStructure::new(),
+            styles: DocumentStyles::new(),
+        }
+    }
+
+    #[test]
+    fn should_fail_to_export_several_files_as_bytes() {
+        // Given: a layout of two pages exported as one SVG file per page
+        let document = document();
+
+        // When: exporting the layout into memory
+        let result = export_to_bytes(
+            &document,
+            sample_layout(),
+            ExportType::Svg,
+            ExportOptions::default(),
+        );
+
+        // Then: an error names the number of files
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("consists of 2 files"), "{}", error);
+    }
+
+    #[test]
+    fn should_export_single_file_as_bytes() {
+        // Given: a layout exported as a single JSON file
+        let document = document();
+
+        // When: exporting the layout into memory and into a writer
+        let bytes = export_to_bytes(
+            &document,
+            sample_layout(),
+            ExportType::LayoutJson {
+                unit: unit::DistanceUnit::Millimeter,
+            },
+            ExportOptions::default(),
+        )
+        .unwrap();
+        let mut written = Vec::new();
+        export_to_writer(
+            &document,
+            sample_layout(),
+            ExportType::LayoutJson {
+                unit: unit::DistanceUnit::Millimeter,
+            },
+            ExportOptions::default(),
+            &mut written,
+        )
+        .unwrap();
+
+        // Then: both contain the same file
+        assert!(bytes.starts_with(b"{\n"));
+        assert_eq!(bytes, written);
+    }
+
+    /// Empty directory of the test in the temporary directory of the system.
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("letter-{}-{}", name, std::process::id()));
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    #[test]
+    fn should_write_pages_next_to_path() {
+        // Given: a layout of two pages exported as one SVG file per page
+        let directory = temp_directory("export-svg");
+
+        // When: exporting the layout to a path in the directory
+        let result = export_to_path(
+            &document(),
+            sample_layout(),
+            ExportType::Svg,
+            ExportOptions::default(),
+            &directory.join("out.svg"),
+        );
+
+        // Then: the pages are written next to the path and named after it
+        let exists = |name: &str| directory.join(name).is_file();
+        let written = (exists("out-1.svg"), exists("out-2.svg"), exists("out.svg"));
+        fs::remove_dir_all(&directory).unwrap();
+        result.unwrap();
+        assert_eq!(written, (true, true, false));
+    }
+
+    #[test]
+    fn should_write_style_sheet_and_images_next_to_path() {
+        // Given: a document with a local image exported as HTML
+        let directory = temp_directory("export-html");
+        let image_path = directory.join("cat.png");
+        fs::write(&image_path, b"image").unwrap();
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let paragraph = add_node(
+            &mut structure,
+            root,
+            "paragraph",
+            DocumentNodeValue::Paragraph,
+        );
+        add_node_with_attributes(
+            &mut structure,
+            paragraph,
+            "image",
+            DocumentNodeValue::Image {
+                source: image_path.to_str().unwrap().to_owned(),
+                width: None,
+                height: None,
+            },
+            &[],
+        );
+        let document = document_of(structure, "");
+
+        // When: exporting the document to a path in a subdirectory
+        let result = export_to_path(
+            &document,
+            sample_layout(),
+            ExportType::Html,
+            ExportOptions::default(),
+            &directory.join("site").join("out.html"),
+        );
+
+        // Then: the page, its style sheet and the copied image are written to the subdirectory
+        let site = directory.join("site");
+        let exists = |name: &str| site.join(name).is_file();
+        let written = (
+            exists("out.html"),
+            exists("out.css"),
+            fs::read(site.join("images").join("0-cat.png")).ok(),
+        );
+        fs::remove_dir_all(&directory).unwrap();
+        result.unwrap();
+        assert_eq!(written, (true, true, Some(b"image".to_vec())));
+    }
+
+    #[test]
+    fn should_fail_for_path_without_file_name() {
+        // Given: a path ending with the root directory
+        let path = Path::new("/");
+
+        // When: exporting to the path
+        let result = export_to_path(
+            &document(),
+            sample_layout(),
+            ExportType::Text,
+            ExportOptions::default(),
+            path,
+        );
+
+        // Then: an error names the path
+        let error = result.err().unwrap().to_string();
+        assert_eq!(error, "Output path '/' does not name a file");
+    }
+}
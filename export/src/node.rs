@@ -0,0 +1,27 @@
+//! Properties of document nodes shared by the exports working from the document structure.
+
+use document::structure::DocumentNodeValue;
+
+/// Highest heading level of the exports, HTML (`<h1>` to `<h6>`), Markdown (`#` to `######`)
+/// and the standard structure types of PDF (`H1` to `H6`) all end at six.
+pub(crate) const MAX_HEADING_LEVEL: usize = 6;
+
+/// Attribute of images holding the alternate description shown or read out instead of the image.
+pub(crate) const ALT_ATTRIBUTE: &str = "alt";
+
+/// Nodes starting a new block rather than continuing the text of their parent.
+pub(crate) fn is_block(value: &DocumentNodeValue) -> bool {
+    matches!(
+        value,
+        DocumentNodeValue::DocumentRoot
+            | DocumentNodeValue::Section
+            | DocumentNodeValue::Heading
+            | DocumentNodeValue::Paragraph
+            | DocumentNodeValue::List
+            | DocumentNodeValue::ListItem
+            | DocumentNodeValue::Table
+            | DocumentNodeValue::TableRow
+            | DocumentNodeValue::TableCell
+            | DocumentNodeValue::CodeBlock
+    )
+}
@@ -0,0 +1,22 @@
+#[derive(Debug)]
+pub struct ExportOptions {
+    /// Maximum depth of headings included in the document outline (for example PDF bookmarks).
+    /// All headings are included when not set.
+    pub max_outline_depth: Option<usize>,
+
+    /// Depth up to which outline entries are initially expanded.
+    pub open_outline_depth: usize,
+
+    /// Width in characters paragraphs are wrapped at in plain text and Markdown exports.
+    pub text_width: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            max_outline_depth: None,
+            open_outline_depth: 1,
+            text_width: 80,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::Path;
+
+/// File produced by an export with its path relative to the directory of the output.
+pub(crate) struct ExportedFile {
+    pub(crate) path: String,
+    pub(crate) content: Vec<u8>,
+}
+
+impl ExportedFile {
+    pub(crate) fn new(path: &str, content: Vec<u8>) -> Self {
+        Self {
+            path: path.to_owned(),
+            content,
+        }
+    }
+}
+
+/// Name of one of several files of an export, for example `out-2.svg` for the second page of `out.svg`.
+pub(crate) fn numbered_file_name(file_name: &str, number: usize) -> String {
+    let (stem, extension) = split_file_name(file_name);
+    match extension {
+        Some(extension) => format!("{}-{}.{}", stem, number, extension),
+        None => format!("{}-{}", stem, number),
+    }
+}
+
+/// Name of a file accompanying the output with the given extension, for example `out.css` for `out.html`.
+pub(crate) fn companion_file_name(file_name: &str, extension: &str) -> String {
+    let (stem, _) = split_file_name(file_name);
+    format!("{}.{}", stem, extension)
+}
+
+fn split_file_name(file_name: &str) -> (&str, Option<&str>) {
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(file_name);
+    let extension = path.extension().and_then(|extension| extension.to_str());
+
+    (stem, extension)
+}
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+
+use lopdf::{dictionary, Object, ObjectId};
+
+use document::structure::{LinkTarget, NodeId};
+use layout::element::content::LayoutElementContent;
+use layout::element::{Anchor, Bounds, DocumentLayout};
+use unit::{Distance, DistanceUnit};
+
+use crate::result::ExportResult;
+
+/// Link annotation added to a page for the given node of the document.
+pub(crate) struct LinkAnnotation {
+    pub(crate) node: NodeId,
+    pub(crate) page_number: usize,
+    pub(crate) annotation_id: ObjectId,
+}
+
+/// Add a link annotation for each link area of the document layout to the pages of the PDF.
+/// External links open the URI while internal links jump to the location of the anchor.
+pub(crate) fn add_link_annotations(
+    pdf: &mut lopdf::Document,
+    document_layout: &DocumentLayout,
+) -> ExportResult<Vec<LinkAnnotation>> {
+    let page_ids = pdf.get_pages();
+    let mut annotations: HashMap<ObjectId, Vec<Object>> = HashMap::new();
+    let mut link_annotations = Vec::new();
+
+    for page in document_layout.pages() {
+        let page_id = page_ids[&(page.number() as u32)];
+        let page_height = page.constraints().size().height;
+
+        for element_id in page.elements() {
+            let element = match document_layout.element(element_id) {
+                Some(element) => element,
+                None => continue,
+            };
+
+            if let LayoutElementContent::Link(content) = element.content() {
+                let mut annotation = dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => to_pdf_rect(element.bounds(), page_height),
+                    "Border" => vec![0.into(), 0.into(), 0.into()],
+                };
+
+                match &content.target {
+                    LinkTarget::Uri(uri) => annotation.set(
+                        "A",
+                        dictionary! {
+                            "S" => "URI",
+                            "URI" => Object::string_literal(uri.as_str()),
+                        },
+                    ),
+                    LinkTarget::Anchor(name) => {
+                        let anchor = document_layout
+                            .anchor(name)
+                            .ok_or(format!("Link target '{}' does not exist", name))?;
+
+                        annotation.set("Dest", to_destination(anchor, &page_ids, document_layout));
+                    }
+                }
+
+                let annotation_id = pdf.add_object(annotation);
+                link_annotations.push(LinkAnnotation {
+                    node: element.node(),
+                    page_number: page.number(),
+                    annotation_id,
+                });
+                annotations
+                    .entry(page_id)
+                    .or_default()
+                    .push(Object::Reference(annotation_id));
+            }
+        }
+    }
+
+    for (page_id, page_annotations) in annotations {
+        pdf.get_object_mut(page_id)?
+            .as_dict_mut()?
+            .set("Annots", page_annotations);
+    }
+
+    Ok(link_annotations)
+}
+
+/// Destination that shows the location of the anchor at the top left of the window.
+pub(crate) fn to_destination(
+    anchor: &Anchor,
+    page_ids: &BTreeMap<u32, ObjectId>,
+    document_layout: &DocumentLayout,
+) -> Vec<Object> {
+    let page_id = page_ids[&(anchor.page_number() as u32)];
+    let page_height = document_layout.pages()[anchor.page_number() - 1]
+        .constraints()
+        .size()
+        .height;
+
+    vec![
+        Object::Reference(page_id),
+        "XYZ".into(),
+        to_points(anchor.position().x()).into(),
+        to_points(page_height - anchor.position().y()).into(),
+        Object::Null,
+    ]
+}
+
+/// Convert the bounds to a PDF rectangle, whose origin is at the bottom left of the page.
+fn to_pdf_rect(bounds: &Bounds, page_height: Distance) -> Vec<Object> {
+    let position = bounds.position();
+    let size = bounds.size();
+
+    vec![
+        to_points(position.x()).into(),
+        to_points(page_height - position.y() - size.height).into(),
+        to_points(position.x() + size.width).into(),
+        to_points(page_height - position.y()).into(),
+    ]
+}
+
+fn to_points(distance: Distance) -> f64 {
+    distance.value(DistanceUnit::Points)
+}
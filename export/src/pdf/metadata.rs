@@ -0,0 +1,339 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lopdf::{dictionary, Object, Stream, StringFormat};
+
+use document::meta_data::DocumentMetaData;
+
+use crate::hash::to_uuid;
+use crate::pdf::PdfConformance;
+use crate::result::ExportResult;
+use crate::xml::escape_xml;
+
+const CREATOR: &str = "Letter";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Write the document meta data to the PDF info dictionary as well as to an XMP metadata stream
+/// and set the natural language of the document.
+pub(crate) fn add_meta_data(
+    pdf: &mut lopdf::Document,
+    meta_data: &DocumentMetaData,
+    conformance: PdfConformance,
+) -> ExportResult<()> {
+    let date = DateTime::now();
+    let producer = format!("{} {}", CREATOR, VERSION);
+    let authors: Vec<&str> = meta_data
+        .authors
+        .iter()
+        .map(|author| author.name.as_str())
+        .collect();
+
+    let mut info = dictionary! {
+        "Creator" => to_text_string(CREATOR),
+        "Producer" => to_text_string(&producer),
+        "CreationDate" => Object::string_literal(date.to_pdf_date()),
+        "ModDate" => Object::string_literal(date.to_pdf_date()),
+    };
+    if let Some(title) = &meta_data.title {
+        info.set("Title", to_text_string(title));
+    }
+    if !authors.is_empty() {
+        info.set("Author", to_text_string(&authors.join(", ")));
+    }
+    if let Some(subject) = &meta_data.subject {
+        info.set("Subject", to_text_string(subject));
+    }
+    if !meta_data.keywords.is_empty() {
+        info.set("Keywords", to_text_string(&meta_data.keywords.join(", ")));
+    }
+
+    // Replace the info dictionary written by printpdf
+    match pdf
+        .trailer
+        .get(b"Info")
+        .and_then(|info| info.as_reference())
+    {
+        Ok(info_id) => {
+            pdf.objects.insert(info_id, Object::Dictionary(info));
+        }
+        Err(_) => {
+            let info_id = pdf.add_object(info);
+            pdf.trailer.set("Info", info_id);
+        }
+    }
+
+    let xmp_properties = match conformance {
+        PdfConformance::Default => to_xmp_properties(meta_data, &authors, &producer, &date),
+        PdfConformance::PdfA2b => {
+            // PDF/A requires the creators to match the author of the info dictionary
+            let author = authors.join(", ");
+            let creators: Vec<&str> = authors
+                .first()
+                .map(|_| author.as_str())
+                .into_iter()
+                .collect();
+
+            to_xmp_properties(meta_data, &creators, &producer, &date)
+                + &to_pdf_a_xmp_properties(pdf)
+        }
+    };
+    let xmp = to_xmp_metadata(&xmp_properties);
+    let metadata_id = pdf.add_object(Stream::new(
+        dictionary! {
+            "Type" => "Metadata",
+            "Subtype" => "XML",
+        },
+        xmp.into_bytes(),
+    ));
+
+    let catalog_id = pdf.trailer.get(b"Root")?.as_reference()?;
+    let catalog = pdf.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("Metadata", metadata_id);
+    catalog.set("Lang", to_text_string(&meta_data.language.tag()));
+
+    Ok(())
+}
+
+fn to_xmp_properties(
+    meta_data: &DocumentMetaData,
+    authors: &[&str],
+    producer: &str,
+    date: &DateTime,
+) -> String {
+    let mut properties = String::new();
+    if let Some(title) = &meta_data.title {
+        properties.push_str(&xmp_language_alternative("dc:title", title));
+    }
+    if !authors.is_empty() {
+        properties.push_str(&xmp_array("dc:creator", "rdf:Seq", authors));
+    }
+    if let Some(subject) = &meta_data.subject {
+        properties.push_str(&xmp_language_alternative("dc:description", subject));
+    }
+    if !meta_data.keywords.is_empty() {
+        let keywords: Vec<&str> = meta_data.keywords.iter().map(|k| k.as_str()).collect();
+        properties.push_str(&xmp_array("dc:subject", "rdf:Bag", &keywords));
+        properties.push_str(&xmp_property("pdf:Keywords", &keywords.join(", ")));
+    }
+    properties.push_str(&xmp_array(
+        "dc:language",
+        "rdf:Bag",
+        &[meta_data.language.tag().as_str()],
+    ));
+    properties.push_str(&xmp_property("pdf:Producer", producer));
+    properties.push_str(&xmp_property("xmp:CreatorTool", CREATOR));
+    properties.push_str(&xmp_property("xmp:CreateDate", &date.to_xmp_date()));
+    properties.push_str(&xmp_property("xmp:ModifyDate", &date.to_xmp_date()));
+    properties.push_str(&xmp_property("xmp:MetadataDate", &date.to_xmp_date()));
+    properties.push_str(&xmp_property("xmpMM:VersionID", &meta_data.version.value));
+
+    properties
+}
+
+/// Identification of the PDF/A conformance level.
+/// The document ID is taken from the file identifier of the PDF.
+fn to_pdf_a_xmp_properties(pdf: &lopdf::Document) -> String {
+    let mut properties = String::new();
+    properties.push_str(&xmp_property("pdfaid:part", "2"));
+    properties.push_str(&xmp_property("pdfaid:conformance", "B"));
+
+    let id = pdf
+        .trailer
+        .get(b"ID")
+        .and_then(|ids| ids.as_array())
+        .ok()
+        .and_then(|ids| ids.first())
+        .and_then(|id| id.as_str().ok());
+    if let Some(id) = id {
+        let uuid = format!("uuid:{}", to_uuid(id));
+        properties.push_str(&xmp_property("xmpMM:DocumentID", &uuid));
+        properties.push_str(&xmp_property("xmpMM:InstanceID", &uuid));
+    }
+
+    properties
+}
+
+fn to_xmp_metadata(properties: &str) -> String {
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+        <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+        <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+        <rdf:Description rdf:about=\"\" \
+        xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+        xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" \
+        xmlns:xmpMM=\"http://ns.adobe.com/xap/1.0/mm/\" \
+        xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\" \
+        xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n\
+        <dc:format>application/pdf</dc:format>\n\
+        {}\
+        </rdf:Description>\n\
+        </rdf:RDF>\n\
+        </x:xmpmeta>\n\
+        <?xpacket end=\"w\"?>",
+        properties
+    )
+}
+
+fn xmp_property(name: &str, value: &str) -> String {
+    format!("<{0}>{1}</{0}>\n", name, escape_xml(value))
+}
+
+fn xmp_language_alternative(name: &str, value: &str) -> String {
+    format!(
+        "<{0}><rdf:Alt><rdf:li xml:lang=\"x-default\">{1}</rdf:li></rdf:Alt></{0}>\n",
+        name,
+        escape_xml(value)
+    )
+}
+
+fn xmp_array(name: &str, kind: &str, values: &[&str]) -> String {
+    let items: String = values
+        .iter()
+        .map(|value| format!("<rdf:li>{}</rdf:li>", escape_xml(value)))
+        .collect();
+
+    format!("<{0}><{1}>{2}</{1}></{0}>\n", name, kind, items)
+}
+
+/// PDF text strings are either plain ASCII or UTF-16BE prefixed by a byte order mark.
+pub(crate) fn to_text_string(value: &str) -> Object {
+    if value.is_ascii() {
+        return Object::string_literal(value);
+    }
+
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in value.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Date and time in UTC.
+pub(crate) struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl DateTime {
+    pub(crate) fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+
+        Self::from_unix_timestamp(seconds)
+    }
+
+    /// Convert the seconds since the unix epoch to a date of the proleptic Gregorian calendar.
+    fn from_unix_timestamp(seconds: i64) -> Self {
+        let days = seconds.div_euclid(86400);
+        let seconds_of_day = seconds.rem_euclid(86400) as u32;
+
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds_of_day / 3600,
+            minute: seconds_of_day / 60 % 60,
+            second: seconds_of_day % 60,
+        }
+    }
+
+    /// Date format of PDF info dictionaries, for example `D:20230715143000Z`.
+    fn to_pdf_date(&self) -> String {
+        format!(
+            "D:{:04}{:02}{:02}{:02}{:02}{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    /// Date format of XMP metadata, for example `2023-07-15T14:30:00Z`.
+    pub(crate) fn to_xmp_date(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_unix_epoch() {
+        // Given: The start of the unix epoch
+        let seconds = 0;
+
+        // When: Converting the timestamp to a date
+        let date = DateTime::from_unix_timestamp(seconds);
+
+        // Then: The date is the first of January 1970
+        assert_eq!(date.to_xmp_date(), "1970-01-01T00:00:00Z");
+        assert_eq!(date.to_pdf_date(), "D:19700101000000Z");
+    }
+
+    #[test]
+    fn should_convert_leap_days() {
+        // Given: Timestamps on the leap days of a regular leap year and a leap century
+        let leap_year = 1709208000;
+        let leap_century = 951782400;
+
+        // When: Converting the timestamps to dates
+        let leap_year = DateTime::from_unix_timestamp(leap_year);
+        let leap_century = DateTime::from_unix_timestamp(leap_century);
+
+        // Then: The dates are the 29th of February
+        assert_eq!(leap_year.to_xmp_date(), "2024-02-29T12:00:00Z");
+        assert_eq!(leap_century.to_xmp_date(), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn should_convert_day_after_february_of_leap_year() {
+        // Given: The end of the leap day of 2024
+        let seconds = 1709251199;
+
+        // When: Converting the timestamp and the following second to dates
+        let last_second = DateTime::from_unix_timestamp(seconds);
+        let next_day = DateTime::from_unix_timestamp(seconds + 1);
+
+        // Then: The next day is the first of March
+        assert_eq!(last_second.to_xmp_date(), "2024-02-29T23:59:59Z");
+        assert_eq!(next_day.to_xmp_date(), "2024-03-01T00:00:00Z");
+    }
+
+    #[test]
+    fn should_convert_dates_before_1970() {
+        // Given: Timestamps before the unix epoch including a century without leap day
+        let last_second_of_1969 = -1;
+        let after_february_1900 = -2203891200;
+
+        // When: Converting the timestamps to dates
+        let last_second_of_1969 = DateTime::from_unix_timestamp(last_second_of_1969);
+        let after_february_1900 = DateTime::from_unix_timestamp(after_february_1900);
+
+        // Then: The dates are counted backwards from the epoch
+        assert_eq!(last_second_of_1969.to_xmp_date(), "1969-12-31T23:59:59Z");
+        assert_eq!(after_february_1900.to_xmp_date(), "1900-03-01T00:00:00Z");
+    }
+}
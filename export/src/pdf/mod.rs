@@ -1,18 +1,54 @@
 use std::collections::HashMap;
 use std::io::Cursor;
-use std::{fs::File, io::BufWriter};
 
-use printpdf::{Color, Greyscale, IndirectFontRef, Line, Mm, PdfDocument, Point};
+use lopdf::content::Operation;
+use lopdf::{dictionary, Object};
+use printpdf::{Color, Greyscale, IndirectFontRef, Line, Mm, PdfDocument, Point, Rgb};
 
+use document::structure::DocumentStructure;
+use document::style::Color as TextColor;
+use document::Document;
 use font::{FontId, FontVariationId};
-use layout::element::content::LayoutElementContent;
+use layout::element::content::{LayoutElementContent, TextSliceContent};
 use layout::element::{DocumentLayout, Page};
 use unit::{Distance, DistanceUnit};
 
+use crate::pdf::structure_tree::MarkedContents;
+use crate::pdf::to_unicode::GlyphTextMaps;
 use crate::result::ExportResult;
+use crate::ExportOptions;
 
-pub(crate) fn export_as_pdf(document_layout: DocumentLayout) -> ExportResult<()> {
-    let layout_constraints = document_layout.pages().first().unwrap().constraints();
+mod link;
+pub(crate) mod metadata;
+mod outline;
+mod pdf_a;
+mod structure_tree;
+mod to_unicode;
+
+/// Standard the exported PDF conforms to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PdfConformance {
+    Default,
+
+    /// PDF/A-2b for long-term archiving.
+    PdfA2b,
+}
+
+pub(crate) fn export_as_pdf(
+    letter_document: &Document,
+    document_layout: DocumentLayout,
+    options: &ExportOptions,
+    conformance: PdfConformance,
+) -> ExportResult<Vec<u8>> {
+    if let PdfConformance::PdfA2b = conformance {
+        pdf_a::check_requirements(&document_layout)?;
+    }
+
+    let layout_constraints = document_layout
+        .pages()
+        .first()
+        .ok_or("The document layout has no pages")?
+        .constraints();
 
     let initial_page_width = Mm(layout_constraints
         .size()
@@ -23,16 +59,17 @@ pub(crate) fn export_as_pdf(document_layout: DocumentLayout) -> ExportResult<()>
         .height
         .value(DistanceUnit::Millimeter));
 
-    let (document, page_index, layer_index) = PdfDocument::new(
-        "Letter test output",
-        initial_page_width,
-        initial_page_height,
-        "Layer 1",
-    );
+    let title = letter_document
+        .meta_data
+        .title
+        .as_deref()
+        .unwrap_or_default();
+    let (document, page_index, layer_index) =
+        PdfDocument::new(title, initial_page_width, initial_page_height, "Layer 1");
     let mut is_first_page = true;
     let mut pdf_page = document.get_page(page_index);
     let mut pdf_layer = pdf_page.get_layer(layer_index);
-    let mut font_cache = HashMap::new();
+    let mut drawing_ctx = DrawingContext::default();
 
     for page in document_layout.pages() {
         if !is_first_page {
@@ -49,19 +86,35 @@ pub(crate) fn export_as_pdf(document_layout: DocumentLayout) -> ExportResult<()>
         draw_elements_on_layer(
             &document,
             &pdf_layer,
+            &letter_document.structure,
             &document_layout,
             page,
-            &mut font_cache,
-        );
+            &mut drawing_ctx,
+        )?;
 
         is_first_page = false;
     }
 
-    document
-        .save(&mut BufWriter::new(File::create("out.pdf").unwrap()))
-        .unwrap();
+    // Features not supported by printpdf are added to the saved document afterwards
+    let mut pdf = lopdf::Document::load_mem(&document.save_to_bytes()?)?;
+    to_unicode::add_to_unicode_maps(&mut pdf, &drawing_ctx.glyph_text_maps)?;
+    let link_annotations = link::add_link_annotations(&mut pdf, &document_layout)?;
+    structure_tree::add_structure_tree(
+        &mut pdf,
+        &letter_document.structure,
+        &drawing_ctx.marked_contents,
+        &link_annotations,
+    )?;
+    if let PdfConformance::PdfA2b = conformance {
+        pdf_a::add_conformance(&mut pdf, &letter_document.meta_data)?;
+    }
+    metadata::add_meta_data(&mut pdf, &letter_document.meta_data, conformance)?;
+    outline::add_outline(&mut pdf, &document_layout, options)?;
 
-    Ok(())
+    let mut bytes = Vec::new();
+    pdf.save_to(&mut bytes)?;
+
+    Ok(bytes)
 }
 
 fn _draw_page_content_outline(pdf_layer: &printpdf::PdfLayerReference, page: &Page) {
@@ -98,10 +151,11 @@ fn _draw_page_content_outline(pdf_layer: &printpdf::PdfLayerReference, page: &Pa
 fn draw_elements_on_layer(
     document: &printpdf::PdfDocumentReference, // TODO Probably dont need to pass the document when the fonts are loaded using some kind of font manager
     pdf_layer: &printpdf::PdfLayerReference,
+    structure: &DocumentStructure,
     document_layout: &DocumentLayout,
     page: &Page,
-    font_cache: &mut HashMap<FontKey, IndirectFontRef>,
-) {
+    ctx: &mut DrawingContext,
+) -> ExportResult<()> {
     let layout_constraints = page.constraints();
     let page_height = layout_constraints
         .size()
@@ -120,16 +174,38 @@ fn draw_elements_on_layer(
                         content.font_variation,
                         document,
                         document_layout,
-                        font_cache,
-                    );
+                        &mut ctx.font_cache,
+                    )?;
+                    ctx.glyph_text_maps.add_glyphs(&font.name, &content.glyphs);
 
+                    // The text is marked as content of its structure element,
+                    // text that cannot be derived from the glyphs alone is attached as replacement text
+                    let mut properties = dictionary! {
+                        "MCID" => ctx.marked_contents.add(element.node(), page.number()),
+                    };
+                    if let Some(actual_text) = to_actual_text(content) {
+                        properties.set("ActualText", metadata::to_text_string(actual_text));
+                    }
+                    pdf_layer.add_operation(Operation::new(
+                        "BDC",
+                        vec![
+                            Object::Name(
+                                structure_tree::content_structure_type(element.node(), structure)
+                                    .into_bytes(),
+                            ),
+                            Object::Dictionary(properties),
+                        ],
+                    ));
+
+                    pdf_layer.set_fill_color(to_pdf_color(content.color));
                     pdf_layer.begin_text_section();
 
-                    pdf_layer.set_font(&font, font_size.value(DistanceUnit::Points));
+                    pdf_layer.set_font(&font.reference, font_size.value(DistanceUnit::Points));
                     pdf_layer.set_text_cursor(
                         Mm(position.x().value(DistanceUnit::Millimeter)),
                         Mm(page_height
-                            - (position.y() + font_size).value(DistanceUnit::Millimeter)),
+                            - (position.y() + font_size - content.vertical_offset)
+                                .value(DistanceUnit::Millimeter)),
                     );
 
                     // TODO Find "normal" codepoint width for each glyph for the current font
@@ -159,11 +235,62 @@ fn draw_elements_on_layer(
                         .write_positioned_codepoints(converted_advance_adjustments.zip(codepoints));
 
                     pdf_layer.end_text_section();
+                    pdf_layer.add_operation(Operation::new("EMC", Vec::new()));
+                }
+                LayoutElementContent::Line(content) => {
+                    let size = element.bounds().size();
+                    let x = position.x().value(DistanceUnit::Millimeter);
+                    let y = page_height
+                        - (position.y() + size.height / 2.0).value(DistanceUnit::Millimeter);
+                    let width = size.width.value(DistanceUnit::Millimeter);
+
+                    // Decoration lines are not part of the document structure
+                    pdf_layer.add_operation(Operation::new("BMC", vec!["Artifact".into()]));
+                    pdf_layer.set_outline_color(to_pdf_color(content.color));
+                    pdf_layer.set_outline_thickness(content.thickness.value(DistanceUnit::Points));
+                    pdf_layer.add_shape(Line {
+                        points: vec![
+                            (Point::new(Mm(x), Mm(y)), false),
+                            (Point::new(Mm(x + width), Mm(y)), false),
+                        ],
+                        is_closed: false,
+                        has_fill: false,
+                        has_stroke: true,
+                        is_clipping_path: false,
+                    });
+                    pdf_layer.add_operation(Operation::new("EMC", Vec::new()));
                 }
                 _ => {}
             };
         }
     }
+
+    Ok(())
+}
+
+fn to_pdf_color(color: TextColor) -> Color {
+    Color::Rgb(Rgb::new(
+        color.red() as f64 / 255.0,
+        color.green() as f64 / 255.0,
+        color.blue() as f64 / 255.0,
+        None,
+    ))
+}
+
+/// Replacement text for text slices whose glyphs do not map to their text one by one,
+/// like ligatures, glyphs composed of multiple glyphs or hyphens inserted at line breaks.
+fn to_actual_text(content: &TextSliceContent) -> Option<&str> {
+    let glyph_text: String = content
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.text.as_str())
+        .collect();
+    let is_mapped_one_by_one = content
+        .glyphs
+        .iter()
+        .all(|glyph| glyph.text.chars().count() == 1);
+
+    (!is_mapped_one_by_one || glyph_text != content.text).then_some(content.text.as_str())
 }
 
 fn load_font(
@@ -171,27 +298,47 @@ fn load_font(
     font_variation_id: FontVariationId,
     document: &printpdf::PdfDocumentReference,
     document_layout: &DocumentLayout,
-    font_cache: &mut HashMap<FontKey, IndirectFontRef>,
-) -> IndirectFontRef {
+    font_cache: &mut HashMap<FontKey, LoadedFont>,
+) -> ExportResult<LoadedFont> {
     let font_key = FontKey {
         font_id,
         font_variation_id,
     };
 
     if let Some(font) = font_cache.get(&font_key) {
-        return font.clone();
+        return Ok(font.clone());
     }
 
     let font_data = document_layout
         .get_font(&font_id)
         .and_then(|font| font.get_subsetted_font_data(&font_variation_id))
-        .expect("Subsetted font data must be present during export");
+        .ok_or("Subsetted font data must be present during export")?;
     let font_read_cursor = Cursor::new(font_data);
 
-    let font = document.add_external_font(font_read_cursor).unwrap();
+    // printpdf names the embedded fonts by the order in which they are added
+    let font = LoadedFont {
+        reference: document.add_external_font(font_read_cursor)?,
+        name: format!("F{}", font_cache.len()),
+    };
     font_cache.insert(font_key, font.clone());
 
-    font
+    Ok(font)
+}
+
+/// State collected while drawing the pages that is needed to complete the PDF afterwards.
+#[derive(Default)]
+struct DrawingContext {
+    font_cache: HashMap<FontKey, LoadedFont>,
+    glyph_text_maps: GlyphTextMaps,
+    marked_contents: MarkedContents,
+}
+
+#[derive(Debug, Clone)]
+struct LoadedFont {
+    reference: IndirectFontRef,
+
+    /// Name of the font in the PDF (the `BaseFont` of the font dictionary).
+    name: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -199,3 +346,82 @@ struct FontKey {
     font_id: FontId,
     font_variation_id: FontVariationId,
 }
+
+#[cfg(test)]
+mod tests {
+    use typeset::glyph_shaping::GlyphDetails;
+
+    use super::*;
+
+    fn text_slice(text: &str, glyph_texts: &[&str]) -> TextSliceContent {
+        let glyphs = glyph_texts
+            .iter()
+            .enumerate()
+            .map(|(index, glyph_text)| GlyphDetails {
+                codepoint: index as u32,
+                cluster: 0,
+                text: glyph_text.to_string(),
+                x_advance: Distance::zero(),
+                font_x_advance: Distance::zero(),
+            })
+            .collect();
+
+        TextSliceContent {
+            font: 0,
+            font_variation: 0,
+            font_size: Distance::new(12.0, DistanceUnit::Points),
+            vertical_offset: Distance::zero(),
+            color: TextColor::BLACK,
+            text: text.to_owned(),
+            glyphs,
+        }
+    }
+
+    #[test]
+    fn should_not_need_actual_text_for_glyphs_mapping_to_single_characters() {
+        // Given: A text slice with one glyph per character
+        let content = text_slice("cat", &["c", "a", "t"]);
+
+        // When: Determining the actual text
+        let actual_text = to_actual_text(&content);
+
+        // Then: No actual text is needed
+        assert_eq!(actual_text, None);
+    }
+
+    #[test]
+    fn should_use_actual_text_for_ligatures() {
+        // Given: A text slice with a ligature glyph for "fi"
+        let content = text_slice("find", &["fi", "n", "d"]);
+
+        // When: Determining the actual text
+        let actual_text = to_actual_text(&content);
+
+        // Then: The text of the slice is used as actual text
+        assert_eq!(actual_text, Some("find"));
+    }
+
+    #[test]
+    fn should_use_actual_text_for_clusters_of_multiple_glyphs() {
+        // Given: A text slice with a character shaped into a base glyph and a combining mark
+        let content = text_slice("é", &["é", ""]);
+
+        // When: Determining the actual text
+        let actual_text = to_actual_text(&content);
+
+        // Then: The text of the slice is used as actual text
+        assert_eq!(actual_text, Some("é"));
+    }
+
+    #[test]
+    fn should_use_empty_actual_text_for_inserted_hyphens() {
+        // Given: A hyphen inserted at a line break, which is not part of the text
+        let content = text_slice("", &["-"]);
+
+        // When: Determining the actual text
+        let actual_text = to_actual_text(&content);
+
+        // Then: The hyphen is replaced by an empty actual text
+        assert_eq!(actual_text, Some(""));
+    }
+}
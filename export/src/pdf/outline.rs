@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use lopdf::{dictionary, Dictionary, Object, ObjectId};
+
+use layout::element::{DocumentLayout, Heading};
+
+use crate::pdf::link::to_destination;
+use crate::pdf::metadata::to_text_string;
+use crate::result::ExportResult;
+use crate::ExportOptions;
+
+/// Entry of the outline tree with the heading it refers to and the entries of its subsections.
+struct OutlineEntry<'a> {
+    heading: &'a Heading,
+    children: Vec<OutlineEntry<'a>>,
+}
+
+/// Add an outline (also known as bookmarks) built from the headings of the document.
+pub(crate) fn add_outline(
+    pdf: &mut lopdf::Document,
+    document_layout: &DocumentLayout,
+    options: &ExportOptions,
+) -> ExportResult<()> {
+    let entries = to_outline_tree(document_layout.headings(), options.max_outline_depth);
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let page_ids = pdf.get_pages();
+    let outline_id = pdf.new_object_id();
+    let context = OutlineContext {
+        page_ids: &page_ids,
+        document_layout,
+        open_depth: options.open_outline_depth,
+    };
+    let (first_id, last_id) = add_outline_entries(pdf, &entries, outline_id, 1, &context);
+
+    pdf.objects.insert(
+        outline_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Outlines",
+            "First" => first_id,
+            "Last" => last_id,
+            "Count" => visible_entry_count(&entries, 1, context.open_depth) as i64,
+        }),
+    );
+
+    let catalog_id = pdf.trailer.get(b"Root")?.as_reference()?;
+    let catalog = pdf.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("Outlines", outline_id);
+    catalog.set("PageMode", "UseOutlines");
+
+    Ok(())
+}
+
+/// Nest the headings by their level.
+/// Headings skipping a level (for example a third-level heading directly in a first-level section)
+/// are placed below the last heading of a lower level.
+fn to_outline_tree(headings: &[Heading], max_depth: Option<usize>) -> Vec<OutlineEntry<'_>> {
+    let mut result: Vec<OutlineEntry> = Vec::new();
+
+    for heading in headings {
+        // Headings outside of sections are treated like top-level headings
+        let level = heading.level().max(1);
+        if max_depth.is_some_and(|max_depth| level > max_depth) {
+            continue;
+        }
+
+        let mut entries = &mut result;
+        for _ in 1..level {
+            if entries.is_empty() {
+                break;
+            }
+            entries = &mut entries.last_mut().unwrap().children;
+        }
+
+        entries.push(OutlineEntry {
+            heading,
+            children: Vec::new(),
+        });
+    }
+
+    result
+}
+
+struct OutlineContext<'a> {
+    page_ids: &'a BTreeMap<u32, ObjectId>,
+    document_layout: &'a DocumentLayout<'a>,
+    open_depth: usize,
+}
+
+/// Add the given sibling entries and their children, returns the IDs of the first and last entry.
+fn add_outline_entries(
+    pdf: &mut lopdf::Document,
+    entries: &[OutlineEntry],
+    parent_id: ObjectId,
+    depth: usize,
+    context: &OutlineContext,
+) -> (ObjectId, ObjectId) {
+    let ids: Vec<ObjectId> = entries.iter().map(|_| pdf.new_object_id()).collect();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let location = entry.heading.location();
+        let mut item = dictionary! {
+            "Title" => to_text_string(entry.heading.text()),
+            "Parent" => parent_id,
+            "Dest" => to_destination(location, context.page_ids, context.document_layout),
+        };
+        if index > 0 {
+            item.set("Prev", ids[index - 1]);
+        }
+        if index + 1 < ids.len() {
+            item.set("Next", ids[index + 1]);
+        }
+
+        if !entry.children.is_empty() {
+            add_children(pdf, &mut item, entry, ids[index], depth, context);
+        }
+
+        pdf.objects.insert(ids[index], Object::Dictionary(item));
+    }
+
+    (ids[0], ids[ids.len() - 1])
+}
+
+fn add_children(
+    pdf: &mut lopdf::Document,
+    item: &mut Dictionary,
+    entry: &OutlineEntry,
+    id: ObjectId,
+    depth: usize,
+    context: &OutlineContext,
+) {
+    let (first_id, last_id) = add_outline_entries(pdf, &entry.children, id, depth + 1, context);
+    item.set("First", first_id);
+    item.set("Last", last_id);
+
+    // A negative count marks the entry as closed
+    let count = visible_entry_count(&entry.children, depth + 1, context.open_depth) as i64;
+    let is_open = depth <= context.open_depth;
+    item.set("Count", if is_open { count } else { -count });
+}
+
+/// Number of entries visible when all of the given entries are visible.
+fn visible_entry_count(entries: &[OutlineEntry], depth: usize, open_depth: usize) -> usize {
+    entries
+        .iter()
+        .map(|entry| {
+            let is_open = depth <= open_depth;
+            if is_open {
+                1 + visible_entry_count(&entry.children, depth + 1, open_depth)
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use layout::element::{Anchor, Position};
+
+    use super::*;
+
+    fn headings(levels_and_texts: &[(usize, &str)]) -> Vec<Heading> {
+        levels_and_texts
+            .iter()
+            .map(|(level, text)| {
+                Heading::new(*level, text.to_string(), Anchor::new(1, Position::zero()))
+            })
+            .collect()
+    }
+
+    /// Describe the tree like `A(B, C), D` for comparison.
+    fn describe(entries: &[OutlineEntry]) -> String {
+        entries
+            .iter()
+            .map(|entry| {
+                if entry.children.is_empty() {
+                    entry.heading.text().to_owned()
+                } else {
+                    format!("{}({})", entry.heading.text(), describe(&entry.children))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[test]
+    fn should_nest_headings_by_level() {
+        // Given: Headings of two levels
+        let headings = headings(&[(1, "A"), (2, "B"), (2, "C"), (1, "D"), (2, "E")]);
+
+        // When: Building the outline tree
+        let tree = to_outline_tree(&headings, None);
+
+        // Then: Headings of the second level are placed below the preceding first-level heading
+        assert_eq!(describe(&tree), "A(B, C), D(E)");
+    }
+
+    #[test]
+    fn should_place_headings_skipping_a_level_below_last_heading_of_lower_level() {
+        // Given: A third-level heading directly following a first-level heading
+        let headings = headings(&[(1, "A"), (3, "B"), (2, "C"), (3, "D")]);
+
+        // When: Building the outline tree
+        let tree = to_outline_tree(&headings, None);
+
+        // Then: The third-level heading is placed below the first-level heading
+        assert_eq!(describe(&tree), "A(B, C(D))");
+    }
+
+    #[test]
+    fn should_treat_headings_outside_of_sections_as_top_level() {
+        // Given: A heading outside of any section followed by a second-level heading
+        let headings = headings(&[(0, "Title"), (2, "A"), (1, "B")]);
+
+        // When: Building the outline tree
+        let tree = to_outline_tree(&headings, None);
+
+        // Then: The heading outside of any section is a top-level entry
+        assert_eq!(describe(&tree), "Title(A), B");
+    }
+
+    #[test]
+    fn should_place_leading_nested_heading_at_top_level() {
+        // Given: A second-level heading without any preceding first-level heading
+        let headings = headings(&[(2, "A"), (1, "B")]);
+
+        // When: Building the outline tree
+        let tree = to_outline_tree(&headings, None);
+
+        // Then: The heading is placed at the top level
+        assert_eq!(describe(&tree), "A, B");
+    }
+
+    #[test]
+    fn should_omit_headings_deeper_than_max_depth() {
+        // Given: Headings of three levels
+        let headings = headings(&[(1, "A"), (2, "B"), (3, "C"), (1, "D")]);
+
+        // When: Building the outline tree limited to two levels
+        let tree = to_outline_tree(&headings, Some(2));
+
+        // Then: Third-level headings are omitted
+        assert_eq!(describe(&tree), "A(B), D");
+    }
+}
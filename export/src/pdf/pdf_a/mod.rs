@@ -0,0 +1,237 @@
+//! Conformance to PDF/A-2b for long-term archiving, which requires a self-contained document
+//! that is rendered the same way in the future: all fonts are embedded, colors are defined by
+//! an sRGB output intent and the document is neither encrypted nor uses transparency.
+
+use std::collections::HashSet;
+
+use lopdf::{dictionary, Dictionary, Object, ObjectId, Stream, StringFormat};
+
+use document::meta_data::DocumentMetaData;
+use layout::element::content::LayoutElementContent;
+use layout::element::DocumentLayout;
+
+use crate::hash::content_id;
+use crate::pdf::pdf_a::srgb::{srgb_profile, SRGB};
+use crate::result::ExportResult;
+
+mod srgb;
+
+/// PDF version followed by a comment of non-ASCII characters on the second line,
+/// which marks the file as binary as required by PDF/A.
+const PDF_VERSION_WITH_BINARY_COMMENT: &str = "1.7\n%\u{e2}\u{e3}\u{cf}\u{d3}";
+
+/// Print flag of annotations, which PDF/A requires to be set.
+const ANNOTATION_PRINT_FLAG: i64 = 4;
+
+/// Check the requirements of PDF/A that have to be met before the document is written.
+pub(crate) fn check_requirements(document_layout: &DocumentLayout) -> ExportResult<()> {
+    let mut checked_fonts = HashSet::new();
+
+    for page in document_layout.pages() {
+        for element_id in page.elements() {
+            let content = match document_layout.element(element_id).map(|e| e.content()) {
+                Some(LayoutElementContent::TextSlice(content)) => content,
+                _ => continue,
+            };
+
+            if !checked_fonts.insert(content.font) {
+                continue;
+            }
+
+            let font = document_layout
+                .get_font(&content.font)
+                .ok_or(format!("Font with ID '{}' is not available", content.font))?;
+            if !font.is_embedding_permitted() {
+                return Err(format!(
+                    "PDF/A requires all fonts to be embedded, \
+                    but the license of font '{}' does not permit embedding",
+                    font.family_name()
+                        .unwrap_or_else(|| content.font.to_string())
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Make the written PDF conform to PDF/A-2b.
+/// The XMP metadata identifying the conformance level is added together with the other metadata.
+pub(crate) fn add_conformance(
+    pdf: &mut lopdf::Document,
+    meta_data: &DocumentMetaData,
+) -> ExportResult<()> {
+    if pdf.trailer.has(b"Encrypt") {
+        return Err("PDF/A does not permit encrypted documents".into());
+    }
+    check_transparency(pdf)?;
+    check_fonts(pdf)?;
+
+    pdf.version = PDF_VERSION_WITH_BINARY_COMMENT.to_owned();
+
+    let id = Object::String(document_id(pdf, meta_data), StringFormat::Hexadecimal);
+    pdf.trailer.set("ID", vec![id.clone(), id]);
+
+    let profile_id = pdf.add_object(Stream::new(dictionary! { "N" => 3 }, srgb_profile()));
+    let output_intent = dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFA1",
+        "OutputConditionIdentifier" => Object::string_literal(SRGB),
+        "Info" => Object::string_literal(SRGB),
+        "DestOutputProfile" => profile_id,
+    };
+
+    let catalog_id = pdf.trailer.get(b"Root")?.as_reference()?;
+    let catalog = pdf.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("OutputIntents", vec![Object::Dictionary(output_intent)]);
+
+    // Optional content configurations need a name and must not change the visibility automatically
+    if let Ok(Object::Dictionary(configuration)) = catalog
+        .get_mut(b"OCProperties")
+        .and_then(Object::as_dict_mut)
+        .and_then(|properties| properties.get_mut(b"D"))
+    {
+        if !configuration.has(b"Name") {
+            configuration.set("Name", Object::string_literal("Layers"));
+        }
+        configuration.remove(b"AS");
+    }
+
+    for object in pdf.objects.values_mut() {
+        if let Object::Dictionary(dictionary) = object {
+            if dictionary.type_is(b"Annot") {
+                dictionary.set("F", ANNOTATION_PRINT_FLAG);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// PDF/A-2 permits transparency in principle, archived documents are kept free of it anyway
+/// since it is not supported by all renderers.
+fn check_transparency(pdf: &lopdf::Document) -> ExportResult<()> {
+    for object in pdf.objects.values() {
+        let dictionary = match object {
+            Object::Dictionary(dictionary) => dictionary,
+            Object::Stream(stream) => &stream.dict,
+            _ => continue,
+        };
+
+        let has_soft_mask = dictionary
+            .get(b"SMask")
+            .is_ok_and(|mask| mask.as_name().ok() != Some(b"None"));
+        let has_alpha = [b"CA", b"ca"].iter().any(|key| {
+            dictionary
+                .get(*key)
+                .is_ok_and(|alpha| to_number(alpha) < 1.0)
+        });
+        let is_transparency_group = dictionary
+            .get(b"Group")
+            .and_then(Object::as_dict)
+            .is_ok_and(|group| {
+                group.get(b"S").and_then(Object::as_name).ok() == Some(b"Transparency")
+            });
+
+        if has_soft_mask || has_alpha || is_transparency_group {
+            return Err("PDF/A documents must not use transparency".into());
+        }
+    }
+
+    Ok(())
+}
+
+fn to_number(object: &Object) -> f64 {
+    match object {
+        Object::Integer(value) => *value as f64,
+        Object::Real(value) => *value,
+        _ => 1.0,
+    }
+}
+
+/// All fonts need to be embedded and TrueType based composite fonts need to map CIDs to glyph IDs.
+fn check_fonts(pdf: &mut lopdf::Document) -> ExportResult<()> {
+    let font_ids: Vec<ObjectId> = pdf
+        .objects
+        .iter()
+        .filter(|(_, object)| matches!(object, Object::Dictionary(font) if font.type_is(b"Font")))
+        .map(|(id, _)| *id)
+        .collect();
+
+    for font_id in font_ids {
+        let font = pdf.get_object_mut(font_id)?.as_dict_mut()?;
+        let name = font
+            .get(b"BaseFont")
+            .and_then(Object::as_name_str)
+            .unwrap_or("unknown")
+            .to_owned();
+
+        let mut descriptor_ids = Vec::new();
+        let mut descendant_ids = Vec::new();
+        match font.get_mut(b"DescendantFonts") {
+            Ok(Object::Array(descendants)) => {
+                for descendant in descendants {
+                    match descendant {
+                        Object::Dictionary(descendant) => {
+                            descriptor_ids.extend(add_cid_to_gid_map(descendant));
+                        }
+                        Object::Reference(descendant_id) => descendant_ids.push(*descendant_id),
+                        _ => {}
+                    }
+                }
+            }
+            _ => descriptor_ids.extend(font.get(b"FontDescriptor").and_then(Object::as_reference)),
+        }
+        for descendant_id in descendant_ids {
+            let descendant = pdf.get_object_mut(descendant_id)?.as_dict_mut()?;
+            descriptor_ids.extend(add_cid_to_gid_map(descendant));
+        }
+
+        let is_embedded = !descriptor_ids.is_empty()
+            && descriptor_ids.iter().all(|descriptor_id| {
+                pdf.get_dictionary(*descriptor_id).is_ok_and(|descriptor| {
+                    [&b"FontFile"[..], b"FontFile2", b"FontFile3"]
+                        .iter()
+                        .any(|key| descriptor.has(key))
+                })
+            });
+        if !is_embedded {
+            return Err(format!(
+                "PDF/A requires all fonts to be embedded, but font '{}' is not",
+                name
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the identity CID to glyph ID mapping to TrueType based descendant fonts and
+/// return the ID of the font descriptor.
+fn add_cid_to_gid_map(descendant: &mut Dictionary) -> Option<ObjectId> {
+    let is_true_type =
+        descendant.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"CIDFontType2");
+    if is_true_type && !descendant.has(b"CIDToGIDMap") {
+        descendant.set("CIDToGIDMap", "Identity");
+    }
+
+    descendant
+        .get(b"FontDescriptor")
+        .and_then(Object::as_reference)
+        .ok()
+}
+
+/// Document ID derived from the meta data and the content of the pages,
+/// thus exporting the same document again results in the same ID.
+fn document_id(pdf: &lopdf::Document, meta_data: &DocumentMetaData) -> Vec<u8> {
+    let mut content = Vec::new();
+    content.extend_from_slice(meta_data.title.as_deref().unwrap_or_default().as_bytes());
+    content.extend_from_slice(meta_data.version.value.as_bytes());
+    for page_id in pdf.get_pages().values() {
+        content.extend(pdf.get_page_content(*page_id).unwrap_or_default());
+    }
+
+    content_id(&content)
+}
@@ -0,0 +1,129 @@
+//! Minimal ICC profile (version 2.1) of the sRGB color space,
+//! which is embedded as output intent of archived documents.
+
+/// Name of the color space used as profile description and output condition.
+pub(crate) const SRGB: &str = "sRGB IEC61966-2.1";
+
+const HEADER_SIZE: usize = 128;
+
+/// Illuminant of the profile connection space.
+const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+/// Colorants of the sRGB primaries chromatically adapted to D50.
+const RED: [f64; 3] = [0.4360747, 0.2225045, 0.0139322];
+const GREEN: [f64; 3] = [0.3850649, 0.7168786, 0.0971045];
+const BLUE: [f64; 3] = [0.1430804, 0.0606169, 0.7141733];
+
+/// Number of samples of the tone reproduction curve.
+const CURVE_SAMPLES: usize = 1024;
+
+pub(crate) fn srgb_profile() -> Vec<u8> {
+    let curve = curve_tag();
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", description_tag(SRGB)),
+        (b"cprt", text_tag("No copyright, use freely")),
+        (b"wtpt", xyz_tag(D50)),
+        (b"rXYZ", xyz_tag(RED)),
+        (b"gXYZ", xyz_tag(GREEN)),
+        (b"bXYZ", xyz_tag(BLUE)),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve),
+    ];
+
+    let tag_table_size = 4 + tags.len() * 12;
+    let mut tag_table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut tag_data = Vec::new();
+    for (signature, data) in &tags {
+        let offset = HEADER_SIZE + tag_table_size + tag_data.len();
+        tag_table.extend_from_slice(*signature);
+        tag_table.extend_from_slice(&(offset as u32).to_be_bytes());
+        tag_table.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+        // Tag data is aligned to four bytes
+        tag_data.extend_from_slice(data);
+        tag_data.resize(tag_data.len().div_ceil(4) * 4, 0);
+    }
+
+    let mut profile = header(HEADER_SIZE + tag_table_size + tag_data.len());
+    profile.extend_from_slice(&tag_table);
+    profile.extend_from_slice(&tag_data);
+
+    profile
+}
+
+fn header(profile_size: usize) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&(profile_size as u32).to_be_bytes());
+    header.extend_from_slice(&[0; 4]); // Preferred color management module
+    header.extend_from_slice(&[2, 0x10, 0, 0]); // Version 2.1
+    header.extend_from_slice(b"mntr"); // Display device profile
+    header.extend_from_slice(b"RGB ");
+    header.extend_from_slice(b"XYZ "); // Profile connection space
+    for value in [2023u16, 1, 1, 0, 0, 0] {
+        header.extend_from_slice(&value.to_be_bytes()); // Date of creation
+    }
+    header.extend_from_slice(b"acsp");
+    header.extend_from_slice(&[0; 24]); // Platform, flags, manufacturer, model and attributes
+    header.extend_from_slice(&[0; 4]); // Perceptual rendering intent
+    header.extend_from_slice(&xyz_number(D50));
+    header.resize(HEADER_SIZE, 0);
+
+    header
+}
+
+fn description_tag(description: &str) -> Vec<u8> {
+    let mut tag = b"desc".to_vec();
+    tag.extend_from_slice(&[0; 4]);
+    tag.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+    tag.extend_from_slice(description.as_bytes());
+    tag.push(0);
+
+    // Empty Unicode and ScriptCode descriptions
+    tag.extend_from_slice(&[0; 4 + 4 + 2 + 1 + 67]);
+
+    tag
+}
+
+fn text_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"text".to_vec();
+    tag.extend_from_slice(&[0; 4]);
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+
+    tag
+}
+
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ ".to_vec();
+    tag.extend_from_slice(&[0; 4]);
+    tag.extend_from_slice(&xyz_number(xyz));
+
+    tag
+}
+
+/// Sampled sRGB transfer function from encoded to linear values.
+fn curve_tag() -> Vec<u8> {
+    let mut tag = b"curv".to_vec();
+    tag.extend_from_slice(&[0; 4]);
+    tag.extend_from_slice(&(CURVE_SAMPLES as u32).to_be_bytes());
+    for sample in 0..CURVE_SAMPLES {
+        let encoded = sample as f64 / (CURVE_SAMPLES - 1) as f64;
+        let linear = if encoded <= 0.04045 {
+            encoded / 12.92
+        } else {
+            ((encoded + 0.055) / 1.055).powf(2.4)
+        };
+
+        tag.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+
+    tag
+}
+
+/// XYZ values as signed 15.16 fixed point numbers.
+fn xyz_number(xyz: [f64; 3]) -> Vec<u8> {
+    xyz.iter()
+        .flat_map(|value| ((value * 65536.0).round() as i32).to_be_bytes())
+        .collect()
+}
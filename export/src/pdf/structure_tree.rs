@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, HashMap};
+
+use lopdf::{dictionary, Dictionary, Object, ObjectId};
+
+use document::structure::{DocumentNode, DocumentNodeValue, DocumentStructure, NodeId};
+
+use crate::node::{ALT_ATTRIBUTE, MAX_HEADING_LEVEL};
+use crate::pdf::link::LinkAnnotation;
+use crate::pdf::metadata::to_text_string;
+use crate::result::ExportResult;
+
+/// Marked content sequences drawn for the nodes of the document, collected while drawing the pages.
+#[derive(Default)]
+pub(crate) struct MarkedContents {
+    /// Page number and marked content ID of the sequences of each node in drawing order.
+    sequences: HashMap<NodeId, Vec<(usize, i64)>>,
+    next_ids: HashMap<usize, i64>,
+}
+
+impl MarkedContents {
+    /// Register a marked content sequence of the node on the page and return its marked content ID.
+    pub(crate) fn add(&mut self, node: NodeId, page_number: usize) -> i64 {
+        let next_id = self.next_ids.entry(page_number).or_insert(0);
+        let id = *next_id;
+        *next_id += 1;
+
+        self.sequences
+            .entry(node)
+            .or_default()
+            .push((page_number, id));
+
+        id
+    }
+}
+
+/// Standard structure type of the node.
+/// Nodes without a structure type (like text or bold text) are part of the structure element
+/// of their closest ancestor with a structure type.
+fn structure_type(node: &DocumentNode, structure: &DocumentStructure) -> Option<String> {
+    let structure_type = match node.value {
+        DocumentNodeValue::DocumentRoot => "Document",
+        DocumentNodeValue::Section => "Sect",
+        DocumentNodeValue::Heading => return Some(heading_structure_type(node, structure)),
+        DocumentNodeValue::Paragraph => "P",
+        DocumentNodeValue::Image { .. } => "Figure",
+        DocumentNodeValue::List => "L",
+        DocumentNodeValue::ListItem => "LI",
+        DocumentNodeValue::Table => "Table",
+        DocumentNodeValue::TableRow => "TR",
+        DocumentNodeValue::TableCell => "TD",
+        DocumentNodeValue::Code | DocumentNodeValue::CodeBlock => "Code",
+        DocumentNodeValue::Link(_) => "Link",
+        _ => return None,
+    };
+
+    Some(structure_type.to_owned())
+}
+
+/// Headings are leveled by the number of sections they are nested in.
+fn heading_structure_type(node: &DocumentNode, structure: &DocumentStructure) -> String {
+    let level = structure
+        .get_path(node.id)
+        .iter()
+        .filter(|ancestor| matches!(ancestor.value, DocumentNodeValue::Section))
+        .count();
+
+    format!("H{}", level.clamp(1, MAX_HEADING_LEVEL))
+}
+
+/// Structure type of the element the content drawn for the given node belongs to.
+pub(crate) fn content_structure_type(node: NodeId, structure: &DocumentStructure) -> String {
+    for ancestor in structure.get_path(node).iter().rev() {
+        if let Some(structure_type) = structure_type(ancestor, structure) {
+            return match ancestor.value {
+                // The content of list items is placed in the list item body
+                DocumentNodeValue::ListItem => "LBody".to_owned(),
+                _ => structure_type,
+            };
+        }
+    }
+
+    "Document".to_owned()
+}
+
+/// Add the structure tree mirroring the document structure, which refers to the marked content
+/// sequences and link annotations in reading order, and mark the PDF as tagged.
+pub(crate) fn add_structure_tree(
+    pdf: &mut lopdf::Document,
+    structure: &DocumentStructure,
+    marked_contents: &MarkedContents,
+    link_annotations: &[LinkAnnotation],
+) -> ExportResult<()> {
+    let page_ids = pdf.get_pages();
+    let root_id = pdf.new_object_id();
+
+    let mut builder = StructureTreeBuilder {
+        pdf,
+        structure,
+        marked_contents,
+        link_annotations,
+        page_ids: &page_ids,
+        page_parents: BTreeMap::new(),
+        annotation_parents: Vec::new(),
+    };
+    let document_element_id = builder.add_element(structure.root(), "Document".to_owned(), root_id);
+    let page_parents = builder.page_parents;
+    let annotation_parents = builder.annotation_parents;
+
+    // The parent tree maps the marked content of each page and each annotation to its structure element
+    let mut parent_tree = Vec::new();
+    for (page_number, page_id) in &page_ids {
+        let key = (page_number - 1) as i64;
+        let parents: Vec<Object> = page_parents
+            .get(&(*page_number as usize))
+            .map(|parents| {
+                parents
+                    .iter()
+                    .map(|parent| parent.map_or(Object::Null, Object::Reference))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        parent_tree.push(key.into());
+        parent_tree.push(parents.into());
+
+        let page = pdf.get_object_mut(*page_id)?.as_dict_mut()?;
+        page.set("StructParents", key);
+        page.set("Tabs", "S");
+    }
+
+    let mut next_key = page_ids.len() as i64;
+    for (annotation_id, parent_id) in annotation_parents {
+        parent_tree.push(next_key.into());
+        parent_tree.push(parent_id.into());
+
+        pdf.get_object_mut(annotation_id)?
+            .as_dict_mut()?
+            .set("StructParent", next_key);
+        next_key += 1;
+    }
+
+    pdf.objects.insert(
+        root_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "StructTreeRoot",
+            "K" => document_element_id,
+            "ParentTree" => dictionary! {
+                "Nums" => parent_tree,
+            },
+            "ParentTreeNextKey" => next_key,
+        }),
+    );
+
+    let catalog_id = pdf.trailer.get(b"Root")?.as_reference()?;
+    let catalog = pdf.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("StructTreeRoot", root_id);
+    catalog.set("MarkInfo", dictionary! { "Marked" => true });
+    catalog.set(
+        "ViewerPreferences",
+        dictionary! { "DisplayDocTitle" => true },
+    );
+
+    Ok(())
+}
+
+struct StructureTreeBuilder<'a> {
+    pdf: &'a mut lopdf::Document,
+    structure: &'a DocumentStructure,
+    marked_contents: &'a MarkedContents,
+    link_annotations: &'a [LinkAnnotation],
+    page_ids: &'a BTreeMap<u32, ObjectId>,
+
+    /// Structure element of each marked content ID per page number.
+    page_parents: BTreeMap<usize, Vec<Option<ObjectId>>>,
+
+    /// Structure element of each link annotation.
+    annotation_parents: Vec<(ObjectId, ObjectId)>,
+}
+
+impl<'a> StructureTreeBuilder<'a> {
+    /// Add the structure element of the node and return its object ID.
+    fn add_element(
+        &mut self,
+        node: &DocumentNode,
+        structure_type: String,
+        parent_id: ObjectId,
+    ) -> ObjectId {
+        let element_id = self.pdf.new_object_id();
+
+        let kids = if let DocumentNodeValue::ListItem = node.value {
+            let body_id = self.pdf.new_object_id();
+            let mut body_kids = Vec::new();
+            self.collect_kids(node, body_id, &mut body_kids);
+            self.pdf.objects.insert(
+                body_id,
+                Object::Dictionary(to_element("LBody", element_id, body_kids)),
+            );
+
+            vec![Object::Reference(body_id)]
+        } else {
+            let mut kids = Vec::new();
+            self.collect_kids(node, element_id, &mut kids);
+            kids
+        };
+
+        let mut element = to_element(&structure_type, parent_id, kids);
+        if let DocumentNodeValue::Image { .. } = node.value {
+            if let Some(alt) = node.attributes.get(ALT_ATTRIBUTE) {
+                element.set("Alt", to_text_string(alt));
+            }
+        }
+        self.pdf
+            .objects
+            .insert(element_id, Object::Dictionary(element));
+
+        element_id
+    }
+
+    /// Collect the content of the node and its descendants belonging to the structure element
+    /// in document order, descendants with their own structure type get a child element.
+    fn collect_kids(&mut self, node: &DocumentNode, element_id: ObjectId, kids: &mut Vec<Object>) {
+        let structure = self.structure;
+        let sequences = self.marked_contents.sequences.get(&node.id);
+        for (page_number, id) in sequences.into_iter().flatten() {
+            kids.push(Object::Dictionary(dictionary! {
+                "Type" => "MCR",
+                "Pg" => self.page_ids[&(*page_number as u32)],
+                "MCID" => *id,
+            }));
+
+            let parents = self.page_parents.entry(*page_number).or_default();
+            let index = *id as usize;
+            if parents.len() <= index {
+                parents.resize(index + 1, None);
+            }
+            parents[index] = Some(element_id);
+        }
+
+        let link_annotations = self.link_annotations;
+        for annotation in link_annotations
+            .iter()
+            .filter(|annotation| annotation.node == node.id)
+        {
+            kids.push(Object::Dictionary(dictionary! {
+                "Type" => "OBJR",
+                "Pg" => self.page_ids[&(annotation.page_number as u32)],
+                "Obj" => annotation.annotation_id,
+            }));
+            self.annotation_parents
+                .push((annotation.annotation_id, element_id));
+        }
+
+        for child_id in node.children() {
+            let child = match structure.get_node(*child_id) {
+                Some(child) => child,
+                None => continue,
+            };
+
+            match structure_type(child, structure) {
+                Some(structure_type) => {
+                    let child_element_id = self.add_element(child, structure_type, element_id);
+                    kids.push(Object::Reference(child_element_id));
+                }
+                None => self.collect_kids(child, element_id, kids),
+            }
+        }
+    }
+}
+
+fn to_element(structure_type: &str, parent_id: ObjectId, kids: Vec<Object>) -> Dictionary {
+    dictionary! {
+        "Type" => "StructElem",
+        "S" => structure_type,
+        "P" => parent_id,
+        "K" => kids,
+    }
+}
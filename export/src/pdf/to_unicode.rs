@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+
+use lopdf::{dictionary, Stream};
+
+use typeset::glyph_shaping::GlyphDetails;
+
+use crate::result::ExportResult;
+
+/// Maximum number of mappings of a single `beginbfchar` block allowed by the CMap specification.
+const MAX_BLOCK_SIZE: usize = 100;
+
+/// Unicode text of the glyphs used with each embedded font, collected while drawing the text.
+/// PDF viewers use it to extract, copy and search the text of the document.
+#[derive(Default)]
+pub(crate) struct GlyphTextMaps {
+    fonts: HashMap<String, BTreeMap<u16, String>>,
+}
+
+impl GlyphTextMaps {
+    /// Remember the text of the glyphs for the font with the given name.
+    /// Glyphs without text (the trailing glyphs of a cluster) are skipped
+    /// and the first text seen for a glyph wins.
+    pub(crate) fn add_glyphs(&mut self, font_name: &str, glyphs: &[GlyphDetails]) {
+        let glyph_texts = self.fonts.entry(font_name.to_owned()).or_default();
+
+        for glyph in glyphs.iter().filter(|glyph| !glyph.text.is_empty()) {
+            glyph_texts
+                .entry(glyph.codepoint as u16)
+                .or_insert_with(|| glyph.text.clone());
+        }
+    }
+}
+
+/// Replace the ToUnicode CMaps of the embedded fonts written by printpdf,
+/// which are derived from the character map of the font and thus miss ligatures and alternate glyphs.
+pub(crate) fn add_to_unicode_maps(
+    pdf: &mut lopdf::Document,
+    glyph_text_maps: &GlyphTextMaps,
+) -> ExportResult<()> {
+    let mut font_ids = Vec::new();
+    for (object_id, object) in &pdf.objects {
+        let font = match object.as_dict() {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+
+        let is_composite_font = font.get(b"Type").and_then(|t| t.as_name()).ok() == Some(b"Font")
+            && font.get(b"Subtype").and_then(|t| t.as_name()).ok() == Some(b"Type0");
+        if !is_composite_font {
+            continue;
+        }
+
+        if let Ok(name) = font.get(b"BaseFont").and_then(|name| name.as_name_str()) {
+            if let Some(glyph_texts) = glyph_text_maps.fonts.get(name) {
+                font_ids.push((*object_id, to_cmap(glyph_texts)));
+            }
+        }
+    }
+
+    for (font_id, cmap) in font_ids {
+        let cmap_id = pdf.add_object(Stream::new(dictionary! {}, cmap.into_bytes()));
+        pdf.get_object_mut(font_id)?
+            .as_dict_mut()?
+            .set("ToUnicode", cmap_id);
+    }
+
+    Ok(())
+}
+
+/// Write the mapping of glyph IDs to UTF-16BE encoded text as a CMap.
+fn to_cmap(glyph_texts: &BTreeMap<u16, String>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n\
+        12 dict begin\n\
+        begincmap\n\
+        /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+        /CMapName /Adobe-Identity-UCS def\n\
+        /CMapType 2 def\n\
+        1 begincodespacerange\n\
+        <0000> <FFFF>\n\
+        endcodespacerange\n",
+    );
+
+    let mappings: Vec<(&u16, &String)> = glyph_texts.iter().collect();
+    for block in mappings.chunks(MAX_BLOCK_SIZE) {
+        cmap.push_str(&format!("{} beginbfchar\n", block.len()));
+        for (glyph_id, text) in block {
+            let unicode: String = text
+                .encode_utf16()
+                .map(|unit| format!("{:04X}", unit))
+                .collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", glyph_id, unicode));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+
+    cmap.push_str(
+        "endcmap\n\
+        CMapName currentdict /CMap defineresource pop\n\
+        end\n\
+        end",
+    );
+
+    cmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_mappings_as_utf16() {
+        // Given: Glyphs for a single letter, a ligature and a letter outside the basic multilingual plane
+        let glyph_texts = BTreeMap::from([
+            (3, "a".to_owned()),
+            (42, "fi".to_owned()),
+            (300, "𝔸".to_owned()),
+        ]);
+
+        // When: Writing the CMap
+        let cmap = to_cmap(&glyph_texts);
+
+        // Then: The texts are encoded in UTF-16BE including surrogate pairs
+        assert!(cmap.contains(
+            "3 beginbfchar\n<0003> <0061>\n<002A> <00660069>\n<012C> <D835DD38>\nendbfchar\n"
+        ));
+    }
+
+    #[test]
+    fn should_split_mappings_into_blocks_of_at_most_100_entries() {
+        // Given: Texts for 250 glyphs
+        let glyph_texts: BTreeMap<u16, String> = (0..250)
+            .map(|glyph_id| (glyph_id, "a".to_owned()))
+            .collect();
+
+        // When: Writing the CMap
+        let cmap = to_cmap(&glyph_texts);
+
+        // Then: The mappings are split into blocks of 100, 100 and 50 entries
+        let block_sizes: Vec<&str> = cmap
+            .lines()
+            .filter_map(|line| line.strip_suffix(" beginbfchar"))
+            .collect();
+        assert_eq!(block_sizes, vec!["100", "100", "50"]);
+        assert_eq!(cmap.matches("endbfchar").count(), 3);
+        assert_eq!(cmap.matches("> <0061>\n").count(), 250);
+    }
+
+    #[test]
+    fn should_write_single_block_for_exactly_100_entries() {
+        // Given: Texts for 100 glyphs
+        let glyph_texts: BTreeMap<u16, String> = (0..100)
+            .map(|glyph_id| (glyph_id, "a".to_owned()))
+            .collect();
+
+        // When: Writing the CMap
+        let cmap = to_cmap(&glyph_texts);
+
+        // Then: All mappings are written in a single block
+        assert_eq!(cmap.matches("beginbfchar").count(), 1);
+        assert!(cmap.contains("100 beginbfchar"));
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::png::raster::Canvas;
+use crate::result::ExportResult;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Encode the canvas as PNG with 8-bit RGB color and without interlacing.
+pub(crate) fn encode_png(canvas: &Canvas) -> ExportResult<Vec<u8>> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(canvas.width() as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height() as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // Bit depth, RGB, compression, filter and interlace method
+
+    // Each row is prefixed by its filter type, which is always none
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in canvas.rows() {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    let data = encoder.finish()?;
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &data);
+    write_chunk(&mut png, b"IEND", &[]);
+
+    Ok(png)
+}
+
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(chunk_type);
+    hasher.update(data);
+
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
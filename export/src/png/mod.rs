@@ -0,0 +1,332 @@
+//! Export of each page of the document layout as PNG image rasterised on the CPU,
+//! for example for page previews and thumbnails.
+
+use document::style::Color;
+use font::{GlyphOutlines, OutlineCommand};
+use layout::element::content::{LayoutElementContent, TextSliceContent};
+use layout::element::{DocumentLayout, LayoutElement, Size};
+use unit::{Distance, DistanceUnit};
+
+use crate::glyph_outlines::GlyphOutlinesCache;
+use crate::output::{numbered_file_name, ExportedFile};
+use crate::png::encode::encode_png;
+use crate::png::raster::{Canvas, Path};
+use crate::result::ExportResult;
+
+mod encode;
+mod raster;
+
+/// Maximum number of pixels of a single page, which limits the memory needed for rasterising
+/// to about 800 MB and is still enough for an A4 page at 1200 dpi.
+const MAX_CANVAS_PIXELS: usize = 1 << 28;
+
+/// Export each page as image named after the given file name and numbered by the page.
+pub(crate) fn export_as_png(
+    document_layout: DocumentLayout,
+    dpi: usize,
+    file_name: &str,
+) -> ExportResult<Vec<ExportedFile>> {
+    if dpi == 0 {
+        return Err("The resolution of PNG exports must be at least 1 dpi".into());
+    }
+
+    let unit = DistanceUnit::Pixel { dots_per_inch: dpi };
+    let mut glyph_outlines = GlyphOutlinesCache::default();
+
+    let mut files = Vec::new();
+    for page in document_layout.pages() {
+        let (width, height) = canvas_size(page.constraints().size(), unit)
+            .map_err(|e| format!("Page {} cannot be exported as PNG: {}", page.number(), e))?;
+        let mut canvas = Canvas::new(width, height);
+
+        for element_id in page.elements() {
+            if let Some(element) = document_layout.element(element_id) {
+                draw_element(
+                    element,
+                    &document_layout,
+                    unit,
+                    &mut glyph_outlines,
+                    &mut canvas,
+                )
+                .map_err(|e| format!("Page {} cannot be exported as PNG: {}", page.number(), e))?;
+            }
+        }
+
+        files.push(ExportedFile::new(
+            &numbered_file_name(file_name, page.number()),
+            encode_png(&canvas)?,
+        ));
+    }
+
+    Ok(files)
+}
+
+/// Size of the canvas in whole pixels, which must not be empty and not exceed the maximum number of pixels.
+fn canvas_size(size: Size, unit: DistanceUnit) -> ExportResult<(usize, usize)> {
+    let width = size.width.value(unit).round();
+    let height = size.height.value(unit).round();
+
+    if !(width >= 1.0 && height >= 1.0) {
+        return Err(format!("The page size of {}x{} pixels is empty", width, height).into());
+    }
+    if width * height > MAX_CANVAS_PIXELS as f64 {
+        return Err(format!(
+            "The page size of {}x{} pixels exceeds the maximum of {} pixels, use a lower resolution",
+            width, height, MAX_CANVAS_PIXELS
+        )
+        .into());
+    }
+
+    Ok((width as usize, height as usize))
+}
+
+fn draw_element(
+    element: &LayoutElement,
+    document_layout: &DocumentLayout,
+    unit: DistanceUnit,
+    glyph_outlines: &mut GlyphOutlinesCache,
+    canvas: &mut Canvas,
+) -> ExportResult<()> {
+    let bounds = element.bounds();
+    let position = bounds.position();
+
+    match element.content() {
+        LayoutElementContent::TextSlice(content) => {
+            let outlines = glyph_outlines.get(document_layout, content)?;
+
+            draw_text_slice(
+                content,
+                position.x(),
+                position.y(),
+                outlines,
+                document_layout,
+                unit,
+                canvas,
+            );
+        }
+        LayoutElementContent::Line(content) => {
+            let thickness = content.thickness.value(unit);
+            let center = (position.y() + bounds.size().height / 2.0).value(unit);
+            let path = Path::rectangle(
+                position.x().value(unit),
+                center - thickness / 2.0,
+                bounds.size().width.value(unit),
+                thickness,
+            );
+
+            canvas.fill(&path, to_rgb(content.color));
+        }
+        // Links are not interactive in an image
+        LayoutElementContent::Page | LayoutElementContent::Link(_) => {}
+        // Images do not carry their data in the layout yet, thus they cannot be drawn
+        LayoutElementContent::Image => {
+            return Err("Images are not supported yet".into());
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_text_slice(
+    content: &TextSliceContent,
+    x: Distance,
+    y: Distance,
+    outlines: &GlyphOutlines,
+    document_layout: &DocumentLayout,
+    unit: DistanceUnit,
+    canvas: &mut Canvas,
+) {
+    let units_per_em = document_layout
+        .get_font(&content.font)
+        .map(|font| font.units_per_em())
+        .unwrap_or(1000);
+    let scale = content.font_size.value(unit) / units_per_em as f64;
+    let baseline = (y + content.font_size - content.vertical_offset).value(unit);
+    let color = to_rgb(content.color);
+
+    let mut x = x;
+    for glyph in &content.glyphs {
+        if let Some(commands) = outlines.outline(glyph.codepoint) {
+            let origin_x = x.value(unit);
+
+            // Glyph outlines are in font units with the y-axis pointing upwards
+            let to_canvas =
+                |gx: &f32, gy: &f32| (origin_x + *gx as f64 * scale, baseline - *gy as f64 * scale);
+
+            let mut path = Path::default();
+            for command in &commands {
+                match command {
+                    OutlineCommand::MoveTo(x, y) => path.move_to(to_canvas(x, y)),
+                    OutlineCommand::LineTo(x, y) => path.line_to(to_canvas(x, y)),
+                    OutlineCommand::QuadTo(cx, cy, x, y) => {
+                        path.quad_to(to_canvas(cx, cy), to_canvas(x, y))
+                    }
+                    OutlineCommand::CubicTo(c1x, c1y, c2x, c2y, x, y) => {
+                        path.cubic_to(to_canvas(c1x, c1y), to_canvas(c2x, c2y), to_canvas(x, y))
+                    }
+                    OutlineCommand::Close => path.close(),
+                }
+            }
+            path.close();
+
+            canvas.fill(&path, color);
+        }
+
+        x += glyph.x_advance;
+    }
+}
+
+fn to_rgb(color: Color) -> [u8; 3] {
+    [color.red(), color.green(), color.blue()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_layout::{image_layout, sample_layout};
+
+    use super::*;
+
+    fn mm(value: f64) -> Distance {
+        Distance::new(value, DistanceUnit::Millimeter)
+    }
+
+    #[test]
+    fn should_determine_canvas_size_in_pixels() {
+        // Given: An A4 page
+        let size = Size::new(mm(210.0), mm(297.0));
+
+        // When: Determining the canvas size at 96 dpi
+        let result = canvas_size(size, DistanceUnit::Pixel { dots_per_inch: 96 });
+
+        // Then: The size is rounded to whole pixels
+        assert_eq!(result.unwrap(), (794, 1123));
+    }
+
+    #[test]
+    fn should_reject_empty_canvas() {
+        // Given: A page without height and a page of less than half a pixel width
+        let without_height = Size::new(mm(210.0), mm(0.0));
+        let too_narrow = Size::new(mm(0.1), mm(297.0));
+
+        // When: Determining the canvas sizes at 96 dpi
+        let unit = DistanceUnit::Pixel { dots_per_inch: 96 };
+        let without_height = canvas_size(without_height, unit);
+        let too_narrow = canvas_size(too_narrow, unit);
+
+        // Then: Both sizes are rejected
+        assert!(without_height.is_err());
+        assert!(too_narrow.is_err());
+    }
+
+    #[test]
+    fn should_reject_canvas_exceeding_maximum_number_of_pixels() {
+        // Given: An A4 page
+        let size = Size::new(mm(210.0), mm(297.0));
+
+        // When: Determining the canvas size at 1200 and 10000 dpi
+        let at_1200_dpi = canvas_size(
+            size,
+            DistanceUnit::Pixel {
+                dots_per_inch: 1200,
+            },
+        );
+        let at_10000_dpi = canvas_size(
+            size,
+            DistanceUnit::Pixel {
+                dots_per_inch: 10000,
+            },
+        );
+
+        // Then: Only the canvas at 10000 dpi is rejected
+        assert!(at_1200_dpi.is_ok());
+        assert!(at_10000_dpi.is_err());
+    }
+
+    #[test]
+    fn should_reject_zero_dpi() {
+        // Given: A layout with two pages
+        let layout = sample_layout();
+
+        // When: Exporting the layout at 0 dpi
+        let result = export_as_png(layout, 0, "out.png");
+
+        // Then: The export fails instead of producing empty images
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_export_each_page_as_image() {
+        // Given: A layout with two pages
+        let layout = sample_layout();
+
+        // When: Exporting the layout at 10 dpi
+        let files = export_as_png(layout, 10, "out.png").unwrap();
+
+        // Then: Each page is written as PNG file
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["out-1.png", "out-2.png"]);
+        assert!(files
+            .iter()
+            .all(|file| file.content.starts_with(b"\x89PNG\r\n\x1a\n")));
+    }
+
+    #[test]
+    fn should_draw_glyphs_of_text_within_bounds() {
+        // Given: The text element of a layout with the word "Letter" on the first page
+        let layout = sample_layout();
+        let text = layout.pages()[0]
+            .elements()
+            .iter()
+            .filter_map(|id| layout.element(id))
+            .find(|element| matches!(element.content(), LayoutElementContent::TextSlice(_)))
+            .unwrap();
+
+        // When: Drawing the element on a page canvas at 96 dpi
+        let unit = DistanceUnit::Pixel { dots_per_inch: 96 };
+        let mut canvas = Canvas::new(794, 1123);
+        let mut glyph_outlines = GlyphOutlinesCache::default();
+        draw_element(text, &layout, unit, &mut glyph_outlines, &mut canvas).unwrap();
+
+        // Then: Pixels are painted in the text color only within the bounds of the element
+        let bounds = text.bounds();
+        let left = bounds.position().x().value(unit).floor() as usize;
+        let top = bounds.position().y().value(unit).floor() as usize;
+        let right = (bounds.position().x() + bounds.size().width)
+            .value(unit)
+            .ceil() as usize;
+        let bottom = (bounds.position().y() + bounds.size().height)
+            .value(unit)
+            .ceil() as usize;
+        let mut painted = Vec::new();
+        for (y, row) in canvas.rows().enumerate() {
+            for (x, pixel) in row.chunks(3).enumerate() {
+                if pixel != [255, 255, 255] {
+                    painted.push((x, y, pixel.to_vec()));
+                }
+            }
+        }
+        assert!(painted.len() > 50, "{} pixels painted", painted.len());
+        assert!(painted
+            .iter()
+            .all(|(x, y, _)| (left..right).contains(x) && (top..bottom).contains(y)));
+        assert!(painted
+            .iter()
+            .any(|(_, _, pixel)| pixel == &[0x33, 0x33, 0x33]));
+    }
+
+    #[test]
+    fn should_fail_for_images() {
+        // Given: A layout with an image
+        let layout = image_layout();
+
+        // When: Exporting the layout as PNG
+        let result = export_as_png(layout, 10, "out.png");
+
+        // Then: The export fails naming the page instead of leaving out the image
+        let error = result.err().unwrap().to_string();
+        assert_eq!(
+            error,
+            "Page 1 cannot be exported as PNG: Images are not supported yet"
+        );
+    }
+}
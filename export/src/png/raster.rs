@@ -0,0 +1,207 @@
+/// Number of sub-scanlines sampled per pixel row for anti-aliasing.
+const SUB_SCANLINES: usize = 4;
+
+/// Maximum number of line segments a single curve is flattened into.
+const MAX_CURVE_SEGMENTS: usize = 64;
+
+/// RGB canvas with a white background that filled paths are composited onto.
+pub(crate) struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![255; width * height * 3],
+        }
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rows of RGB pixels from top to bottom.
+    pub(crate) fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.pixels.chunks(self.width * 3)
+    }
+
+    /// Fill the path using the non-zero winding rule.
+    pub(crate) fn fill(&mut self, path: &Path, color: [u8; 3]) {
+        let edges = &path.edges;
+        if edges.is_empty() {
+            return;
+        }
+
+        let min_y = edges
+            .iter()
+            .map(|e| e.0 .1.min(e.1 .1))
+            .fold(f64::MAX, f64::min);
+        let max_y = edges
+            .iter()
+            .map(|e| e.0 .1.max(e.1 .1))
+            .fold(f64::MIN, f64::max);
+        let first_row = (min_y.floor().max(0.0)) as usize;
+        let last_row = (max_y.ceil().max(0.0) as usize).min(self.height);
+
+        let mut coverage = vec![0.0; self.width];
+        let mut crossings: Vec<(f64, i32)> = Vec::new();
+        for row in first_row..last_row {
+            coverage.iter_mut().for_each(|c| *c = 0.0);
+
+            for sub_scanline in 0..SUB_SCANLINES {
+                let y = row as f64 + (sub_scanline as f64 + 0.5) / SUB_SCANLINES as f64;
+
+                crossings.clear();
+                for ((x0, y0), (x1, y1)) in edges {
+                    let (top, bottom, direction) = if y0 < y1 { (y0, y1, 1) } else { (y1, y0, -1) };
+                    if y < *top || y >= *bottom {
+                        continue;
+                    }
+
+                    let x = x0 + (y - y0) / (y1 - y0) * (x1 - x0);
+                    crossings.push((x, direction));
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                for window in crossings.windows(2) {
+                    winding += window[0].1;
+                    if winding != 0 {
+                        self.add_span(&mut coverage, window[0].0, window[1].0);
+                    }
+                }
+            }
+
+            let offset = row * self.width * 3;
+            for (column, coverage) in coverage.iter().enumerate() {
+                let alpha = (coverage / SUB_SCANLINES as f64).min(1.0);
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                let pixel = &mut self.pixels[offset + column * 3..offset + column * 3 + 3];
+                for (channel, value) in pixel.iter_mut().zip(color) {
+                    *channel =
+                        (*channel as f64 * (1.0 - alpha) + value as f64 * alpha).round() as u8;
+                }
+            }
+        }
+    }
+
+    /// Add the horizontal coverage of the span to the pixels it overlaps.
+    fn add_span(&self, coverage: &mut [f64], from: f64, to: f64) {
+        let from = from.max(0.0);
+        let to = to.min(self.width as f64);
+        if from >= to {
+            return;
+        }
+
+        let first_column = from.floor() as usize;
+        let last_column = (to.ceil() as usize).min(self.width);
+        for (column, coverage) in coverage
+            .iter_mut()
+            .enumerate()
+            .take(last_column)
+            .skip(first_column)
+        {
+            let left = from.max(column as f64);
+            let right = to.min(column as f64 + 1.0);
+            *coverage += right - left;
+        }
+    }
+}
+
+/// Closed polygon outlines in pixel coordinates made of flattened line segments.
+#[derive(Default)]
+pub(crate) struct Path {
+    edges: Vec<((f64, f64), (f64, f64))>,
+    start: (f64, f64),
+    current: (f64, f64),
+}
+
+impl Path {
+    pub(crate) fn rectangle(x: f64, y: f64, width: f64, height: f64) -> Self {
+        let mut path = Self::default();
+        path.move_to((x, y));
+        path.line_to((x + width, y));
+        path.line_to((x + width, y + height));
+        path.line_to((x, y + height));
+        path.close();
+
+        path
+    }
+
+    pub(crate) fn move_to(&mut self, point: (f64, f64)) {
+        self.close();
+        self.start = point;
+        self.current = point;
+    }
+
+    pub(crate) fn line_to(&mut self, point: (f64, f64)) {
+        if point.1 != self.current.1 {
+            self.edges.push((self.current, point));
+        }
+        self.current = point;
+    }
+
+    pub(crate) fn quad_to(&mut self, control: (f64, f64), point: (f64, f64)) {
+        let start = self.current;
+        let segments = curve_segments(&[start, control, point]);
+        for i in 1..=segments {
+            let t = i as f64 / segments as f64;
+            let mt = 1.0 - t;
+            self.line_to((
+                mt * mt * start.0 + 2.0 * mt * t * control.0 + t * t * point.0,
+                mt * mt * start.1 + 2.0 * mt * t * control.1 + t * t * point.1,
+            ));
+        }
+    }
+
+    pub(crate) fn cubic_to(
+        &mut self,
+        control1: (f64, f64),
+        control2: (f64, f64),
+        point: (f64, f64),
+    ) {
+        let start = self.current;
+        let segments = curve_segments(&[start, control1, control2, point]);
+        for i in 1..=segments {
+            let t = i as f64 / segments as f64;
+            let mt = 1.0 - t;
+            self.line_to((
+                mt * mt * mt * start.0
+                    + 3.0 * mt * mt * t * control1.0
+                    + 3.0 * mt * t * t * control2.0
+                    + t * t * t * point.0,
+                mt * mt * mt * start.1
+                    + 3.0 * mt * mt * t * control1.1
+                    + 3.0 * mt * t * t * control2.1
+                    + t * t * t * point.1,
+            ));
+        }
+    }
+
+    /// Close the current sub-path with a line back to its start.
+    pub(crate) fn close(&mut self) {
+        let start = self.start;
+        self.line_to(start);
+    }
+}
+
+/// Number of line segments a curve is flattened into, derived from the length of its control polygon.
+fn curve_segments(points: &[(f64, f64)]) -> usize {
+    let length: f64 = points
+        .windows(2)
+        .map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt())
+        .sum();
+
+    ((length / 2.0).ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+}
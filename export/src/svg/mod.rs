@@ -0,0 +1,328 @@
+//! Export of each page of the document layout as an SVG file with the glyphs drawn as outline paths.
+//! The output only depends on the layout, thus exporting the same layout again produces the same files.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use document::structure::LinkTarget;
+use font::{GlyphOutlines, OutlineCommand};
+use layout::element::content::{LayoutElementContent, TextSliceContent};
+use layout::element::{DocumentLayout, LayoutElement, Page};
+use unit::{Distance, DistanceUnit};
+
+use crate::format::{number, to_hex};
+use crate::glyph_outlines::GlyphOutlinesCache;
+use crate::output::{numbered_file_name, ExportedFile};
+use crate::result::ExportResult;
+use crate::xml::escape_xml;
+
+/// Export each page as file named after the given file name and numbered by the page.
+pub(crate) fn export_as_svg(
+    document_layout: DocumentLayout,
+    file_name: &str,
+) -> ExportResult<Vec<ExportedFile>> {
+    let mut glyph_outlines = GlyphOutlinesCache::default();
+
+    let mut files = Vec::new();
+    for page in document_layout.pages() {
+        let svg = page_to_svg(page, &document_layout, &mut glyph_outlines, file_name)
+            .map_err(|e| format!("Page {} cannot be exported as SVG: {}", page.number(), e))?;
+        files.push(ExportedFile::new(
+            &numbered_file_name(file_name, page.number()),
+            svg.into_bytes(),
+        ));
+    }
+
+    Ok(files)
+}
+
+fn page_to_svg(
+    page: &Page,
+    document_layout: &DocumentLayout,
+    glyph_outlines: &mut GlyphOutlinesCache,
+    file_name: &str,
+) -> ExportResult<String> {
+    let size = page.constraints().size();
+    let width = to_points(size.width);
+    let height = to_points(size.height);
+
+    let mut ctx = PageContext {
+        file_name,
+        defined_glyphs: HashSet::new(),
+        defs: String::new(),
+        body: String::new(),
+    };
+    for element_id in page.elements() {
+        if let Some(element) = document_layout.element(element_id) {
+            write_element(element, document_layout, glyph_outlines, &mut ctx)?;
+        }
+    }
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+        width=\"{0}pt\" height=\"{1}pt\" viewBox=\"0 0 {0} {1}\">",
+        number(width),
+        number(height)
+    )?;
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>"
+    )?;
+    if !ctx.defs.is_empty() {
+        write!(svg, "<defs>\n{}</defs>\n", ctx.defs)?;
+    }
+    svg.push_str(&ctx.body);
+    svg.push_str("</svg>\n");
+
+    Ok(svg)
+}
+
+struct PageContext<'a> {
+    /// Name of the exported file the pages are numbered after, links to other pages refer to their files.
+    file_name: &'a str,
+
+    /// IDs of the glyphs already defined on the page.
+    defined_glyphs: HashSet<String>,
+    defs: String,
+    body: String,
+}
+
+fn write_element(
+    element: &LayoutElement,
+    document_layout: &DocumentLayout,
+    glyph_outlines: &mut GlyphOutlinesCache,
+    ctx: &mut PageContext,
+) -> ExportResult<()> {
+    let bounds = element.bounds();
+    let position = bounds.position();
+
+    match element.content() {
+        LayoutElementContent::TextSlice(content) => {
+            let outlines = glyph_outlines.get(document_layout, content)?;
+
+            write_text_slice(
+                content,
+                position.x(),
+                position.y(),
+                outlines,
+                document_layout,
+                ctx,
+            )?;
+        }
+        LayoutElementContent::Line(content) => {
+            let y = to_points(position.y() + bounds.size().height / 2.0);
+            writeln!(
+                ctx.body,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                number(to_points(position.x())),
+                number(y),
+                number(to_points(position.x() + bounds.size().width)),
+                number(y),
+                to_hex(content.color),
+                number(to_points(content.thickness))
+            )?;
+        }
+        LayoutElementContent::Link(content) => {
+            let href = match &content.target {
+                LinkTarget::Uri(uri) => uri.clone(),
+                LinkTarget::Anchor(name) => match document_layout.anchor(name) {
+                    Some(anchor) => numbered_file_name(ctx.file_name, anchor.page_number()),
+                    None => return Err(format!("Link target '{}' does not exist", name).into()),
+                },
+            };
+
+            writeln!(
+                ctx.body,
+                "<a xlink:href=\"{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                fill=\"#ffffff\" fill-opacity=\"0\"/></a>",
+                escape_xml(&href),
+                number(to_points(position.x())),
+                number(to_points(position.y())),
+                number(to_points(bounds.size().width)),
+                number(to_points(bounds.size().height))
+            )?;
+        }
+        LayoutElementContent::Page => {}
+        // Images do not carry their data in the layout yet, thus they cannot be drawn
+        LayoutElementContent::Image => {
+            return Err("Images are not supported yet".into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Each glyph is defined once per page and placed on the baseline scaled to the font size.
+fn write_text_slice(
+    content: &TextSliceContent,
+    x: Distance,
+    y: Distance,
+    outlines: &GlyphOutlines,
+    document_layout: &DocumentLayout,
+    ctx: &mut PageContext,
+) -> ExportResult<()> {
+    let units_per_em = document_layout
+        .get_font(&content.font)
+        .map(|font| font.units_per_em())
+        .unwrap_or(1000);
+    let scale = to_points(content.font_size) / units_per_em as f64;
+    let baseline = to_points(y + content.font_size - content.vertical_offset);
+
+    writeln!(ctx.body, "<g fill=\"{}\">", to_hex(content.color))?;
+    let mut x = x;
+    for glyph in &content.glyphs {
+        let glyph_id = format!(
+            "g{}-{}-{}",
+            content.font, content.font_variation, glyph.codepoint
+        );
+        if !ctx.defined_glyphs.contains(&glyph_id) {
+            let path = outlines
+                .outline(glyph.codepoint)
+                .map(|commands| to_path_data(&commands))
+                .unwrap_or_default();
+            writeln!(ctx.defs, "<path id=\"{}\" d=\"{}\"/>", glyph_id, path)?;
+            ctx.defined_glyphs.insert(glyph_id.clone());
+        }
+
+        // Glyph outlines are in font units with the y-axis pointing upwards
+        writeln!(
+            ctx.body,
+            "<use xlink:href=\"#{}\" transform=\"translate({} {}) scale({} {})\"/>",
+            glyph_id,
+            number(to_points(x)),
+            number(baseline),
+            number(scale),
+            number(-scale)
+        )?;
+
+        x += glyph.x_advance;
+    }
+    writeln!(ctx.body, "</g>")?;
+
+    Ok(())
+}
+
+fn to_path_data(commands: &[OutlineCommand]) -> String {
+    let n = |value: &f32| number(*value as f64);
+
+    commands
+        .iter()
+        .map(|command| match command {
+            OutlineCommand::MoveTo(x, y) => format!("M{} {}", n(x), n(y)),
+            OutlineCommand::LineTo(x, y) => format!("L{} {}", n(x), n(y)),
+            OutlineCommand::QuadTo(cx, cy, x, y) => {
+                format!("Q{} {} {} {}", n(cx), n(cy), n(x), n(y))
+            }
+            OutlineCommand::CubicTo(c1x, c1y, c2x, c2y, x, y) => format!(
+                "C{} {} {} {} {} {}",
+                n(c1x),
+                n(c1y),
+                n(c2x),
+                n(c2y),
+                n(x),
+                n(y)
+            ),
+            OutlineCommand::Close => "Z".to_owned(),
+        })
+        .collect()
+}
+
+fn to_points(distance: Distance) -> f64 {
+    distance.value(DistanceUnit::Points)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_layout::{image_layout, sample_layout};
+
+    use super::*;
+
+    #[test]
+    fn should_export_same_layout_to_same_files() {
+        // Given: The same layout built twice
+        let first_layout = sample_layout();
+        let second_layout = sample_layout();
+
+        // When: Exporting both layouts as SVG
+        let first = export_as_svg(first_layout, "out.svg").unwrap();
+        let second = export_as_svg(second_layout, "out.svg").unwrap();
+
+        // Then: Both exports consist of the same files with the same bytes
+        let describe = |files: &[ExportedFile]| -> Vec<(String, Vec<u8>)> {
+            files
+                .iter()
+                .map(|file| (file.path.clone(), file.content.clone()))
+                .collect()
+        };
+        assert_eq!(first.len(), 2);
+        assert_eq!(describe(&first), describe(&second));
+    }
+
+    #[test]
+    fn should_write_elements_of_page() {
+        // Given: A layout with an underline and two links on the first page
+        let layout = sample_layout();
+
+        // When: Exporting the layout as SVG
+        let files = export_as_svg(layout, "out.svg").unwrap();
+
+        // Then: The elements are written in points with escaped link targets
+        let svg = String::from_utf8(files[0].content.clone()).unwrap();
+        assert_eq!(files[0].path, "out-1.svg");
+        assert!(svg.contains("width=\"595.276pt\" height=\"841.89pt\""));
+        assert!(svg.contains(
+            "<line x1=\"72\" y1=\"85.819\" x2=\"185.386\" y2=\"85.819\" \
+            stroke=\"#1234ab\" stroke-width=\"0.85\"/>"
+        ));
+        assert!(svg.contains("xlink:href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\""));
+        assert!(svg.contains("xlink:href=\"out-2.svg\""));
+    }
+
+    #[test]
+    fn should_write_glyphs_of_text() {
+        // Given: A layout with the word "Letter" on the first page
+        let layout = sample_layout();
+
+        // When: Exporting the layout as SVG
+        let files = export_as_svg(layout, "out.svg").unwrap();
+
+        // Then: Each distinct glyph is defined once by its outline
+        let svg = String::from_utf8(files[0].content.clone()).unwrap();
+        let definitions: Vec<&str> = svg
+            .lines()
+            .filter(|line| line.starts_with("<path id="))
+            .collect();
+        assert_eq!(definitions.len(), 4, "{}", svg);
+        assert!(definitions
+            .iter()
+            .all(|definition| definition.contains(" d=\"M")));
+
+        // And: The glyphs are placed on the baseline one after another
+        assert!(svg.contains("<g fill=\"#333333\">"));
+        assert_eq!(svg.matches("<use xlink:href=\"#g").count(), 6);
+        assert!(svg.contains(
+            "<use xlink:href=\"#g0-0-105\" transform=\"translate(72 182.079) scale(0.012 -0.012)\"/>"
+        ));
+        assert!(svg.contains(
+            "<use xlink:href=\"#g0-0-308\" transform=\"translate(79.188 182.079) scale(0.012 -0.012)\"/>"
+        ));
+    }
+
+    #[test]
+    fn should_fail_for_images() {
+        // Given: A layout with an image
+        let layout = image_layout();
+
+        // When: Exporting the layout as SVG
+        let result = export_as_svg(layout, "out.svg");
+
+        // Then: The export fails naming the page instead of leaving out the image
+        let error = result.err().unwrap().to_string();
+        assert_eq!(
+            error,
+            "Page 1 cannot be exported as SVG: Images are not supported yet"
+        );
+    }
+}
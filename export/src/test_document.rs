@@ -0,0 +1,55 @@
+//! Helpers building small documents for the tests of the exporters working from the document structure.
+
+use std::collections::HashMap;
+
+use document::meta_data::DocumentMetaData;
+use document::structure::{DocumentNode, DocumentNodeValue, DocumentStructure, NodeId};
+use document::Document;
+
+/// Add a node with the given name, value and attributes below the parent and return its ID.
+pub(crate) fn add_node_with_attributes(
+    structure: &mut DocumentStructure,
+    parent: NodeId,
+    name: &str,
+    value: DocumentNodeValue,
+    attributes: &[(&str, &str)],
+) -> NodeId {
+    let id = structure.unused_node_id();
+    let attributes = attributes
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<String, String>>();
+    let node = DocumentNode::new(id, Some(name.to_owned()), value, attributes, None);
+    structure.insert(parent, node);
+
+    id
+}
+
+/// Add a node with the given name and value below the parent and return its ID.
+pub(crate) fn add_node(
+    structure: &mut DocumentStructure,
+    parent: NodeId,
+    name: &str,
+    value: DocumentNodeValue,
+) -> NodeId {
+    add_node_with_attributes(structure, parent, name, value, &[])
+}
+
+/// Add a text node below the parent.
+pub(crate) fn add_text(structure: &mut DocumentStructure, parent: NodeId, text: &str) {
+    add_node(
+        structure,
+        parent,
+        "text",
+        DocumentNodeValue::Text(text.to_owned()),
+    );
+}
+
+/// Document of the structure with default meta data and the styles parsed from the source.
+pub(crate) fn document_of(structure: DocumentStructure, styles: &str) -> Document {
+    Document {
+        meta_data: DocumentMetaData::default(),
+        structure,
+        styles: style::parse(styles).unwrap(),
+    }
+}
@@ -0,0 +1,176 @@
+//! Small hand-built layout shared by the tests of the exporters.
+
+use std::collections::HashMap;
+
+use document::structure::LinkTarget;
+use document::style::Color;
+use font::FontManager;
+use layout::element::content::{LayoutElementContent, LineContent, LinkContent, TextSliceContent};
+use layout::element::{
+    Anchor, Bounds, DocumentLayout, ElementId, LayoutConstraints, LayoutElement, Page, Position,
+    Size,
+};
+use typeset::glyph_shaping::GlyphDetails;
+use unit::{Distance, DistanceUnit};
+
+/// Glyph IDs and advances (in font units) of the bundled Readex Pro font for the text `Letter`.
+const LETTER_GLYPHS: [(char, u32, f64); 6] = [
+    ('L', 105, 599.0),
+    ('e', 308, 586.0),
+    ('t', 452, 384.0),
+    ('t', 452, 384.0),
+    ('e', 308, 586.0),
+    ('r', 432, 403.0),
+];
+
+fn mm(value: f64) -> Distance {
+    Distance::new(value, DistanceUnit::Millimeter)
+}
+
+/// Layout of two A4 pages with an underline, links to an external resource and to an anchor
+/// on the second page, placed at fractional positions, and the word `Letter` set in the default font.
+pub(crate) fn sample_layout<'a>() -> DocumentLayout<'a> {
+    let mut font_manager = FontManager::new();
+    let text = letter_text_slice(&mut font_manager);
+
+    let elements = [
+        (
+            1,
+            Bounds::new(
+                Position::absolute(mm(25.4), mm(30.125)),
+                Size::new(mm(40.0), mm(0.3)),
+            ),
+            LayoutElementContent::Line(LineContent {
+                thickness: mm(0.3),
+                color: Color::new(0x12, 0x34, 0xab),
+            }),
+        ),
+        (
+            1,
+            Bounds::new(
+                Position::absolute(mm(25.4), mm(40.0)),
+                Size::new(mm(33.3333), mm(5.0)),
+            ),
+            LayoutElementContent::Link(LinkContent {
+                target: LinkTarget::Uri("https://example.com/?a=1&b=\"2\"".to_owned()),
+            }),
+        ),
+        (
+            1,
+            Bounds::new(
+                Position::absolute(mm(25.4), mm(50.0)),
+                Size::new(mm(20.0), mm(5.0)),
+            ),
+            LayoutElementContent::Link(LinkContent {
+                target: LinkTarget::Anchor("end".to_owned()),
+            }),
+        ),
+        (
+            2,
+            Bounds::new(
+                Position::absolute(mm(25.4), mm(20.0)),
+                Size::new(mm(159.2), mm(0.5)),
+            ),
+            LayoutElementContent::Line(LineContent {
+                thickness: mm(0.5),
+                color: Color::BLACK,
+            }),
+        ),
+        (
+            1,
+            Bounds::new(
+                Position::absolute(mm(25.4), mm(60.0)),
+                Size::new(text_width(&text), text.font_size),
+            ),
+            LayoutElementContent::TextSlice(text),
+        ),
+    ];
+
+    let anchors = HashMap::from([(
+        "end".to_owned(),
+        Anchor::new(2, Position::absolute(mm(25.4), mm(20.0))),
+    )]);
+
+    layout(2, elements.into(), anchors, font_manager)
+}
+
+/// Text slice of the word `Letter` at 12pt, the glyphs are marked as used and the fonts subset,
+/// so the exporters find their outlines like after a layout run.
+fn letter_text_slice(font_manager: &mut FontManager) -> TextSliceContent {
+    let font_id = font_manager.default_font_id();
+    let font = font_manager.get_font_mut(&font_id).unwrap();
+    let font_variation = font.set_variations(&[]);
+    let font_size = Distance::new(12.0, DistanceUnit::Points);
+    let units_per_em = font.units_per_em() as f64;
+
+    let glyphs: Vec<GlyphDetails> = LETTER_GLYPHS
+        .iter()
+        .enumerate()
+        .map(|(index, (c, codepoint, advance))| {
+            font.mark_codepoint_as_used(*codepoint);
+            let x_advance = font_size * (advance / units_per_em);
+            GlyphDetails {
+                codepoint: *codepoint,
+                cluster: index as u32,
+                text: c.to_string(),
+                x_advance,
+                font_x_advance: x_advance,
+            }
+        })
+        .collect();
+    font_manager.subset_fonts();
+
+    TextSliceContent {
+        font: font_id,
+        font_variation,
+        font_size,
+        vertical_offset: Distance::zero(),
+        color: Color::new(0x33, 0x33, 0x33),
+        text: "Letter".to_owned(),
+        glyphs,
+    }
+}
+
+fn text_width(content: &TextSliceContent) -> Distance {
+    content
+        .glyphs
+        .iter()
+        .fold(Distance::zero(), |width, glyph| width + glyph.x_advance)
+}
+
+/// Layout of a single A4 page with an image, which the layout cannot describe beyond its bounds yet.
+pub(crate) fn image_layout<'a>() -> DocumentLayout<'a> {
+    let elements = vec![(
+        1,
+        Bounds::new(
+            Position::absolute(mm(25.4), mm(20.0)),
+            Size::new(mm(50.0), mm(30.0)),
+        ),
+        LayoutElementContent::Image,
+    )];
+
+    layout(1, elements, HashMap::new(), FontManager::new())
+}
+
+/// Layout of A4 pages with the elements given by the number of their page, each of them the only element of its node.
+fn layout<'a>(
+    page_count: usize,
+    elements: Vec<(usize, Bounds, LayoutElementContent)>,
+    anchors: HashMap<String, Anchor>,
+    font_manager: FontManager<'a>,
+) -> DocumentLayout<'a> {
+    let size = Size::new(mm(210.0), mm(297.0));
+    let constraints = LayoutConstraints::new(size, mm(20.0), mm(20.0), mm(25.4), mm(25.4));
+
+    let mut pages: Vec<Page> = (1..=page_count)
+        .map(|number| Page::new(number, constraints))
+        .collect();
+    let mut element_lookup = HashMap::new();
+    for (index, (page_number, bounds, content)) in elements.into_iter().enumerate() {
+        let id = ElementId::new(index);
+        pages[page_number - 1].add_element(id);
+        element_lookup.insert(id, LayoutElement::of(id, index as u64, bounds, content));
+    }
+
+    DocumentLayout::new(pages, element_lookup, anchors, Vec::new(), font_manager)
+}
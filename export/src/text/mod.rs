@@ -0,0 +1,738 @@
+//! Export of the document as plain text or Markdown, for example to compare versions of a document
+//! in a version control system or as accessible fallback.
+//! Like the HTML export it works from the document structure rather than the paginated layout.
+
+use document::structure::{DocumentNode, DocumentNodeValue, DocumentStructure, LinkTarget};
+use document::Document;
+
+use crate::node::{is_block, ALT_ATTRIBUTE, MAX_HEADING_LEVEL};
+use crate::result::ExportResult;
+use crate::text::wrap::{escape_line_start, wrap};
+use crate::ExportOptions;
+
+mod wrap;
+
+/// Attribute of code blocks naming the language of the code.
+const LANGUAGE_ATTRIBUTE: &str = "lang";
+
+/// Character starting a reference to a document variable, the same as in style sheets.
+const VARIABLE_PREFIX: char = '$';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextFormat {
+    Plain,
+    Markdown,
+}
+
+pub(crate) fn export_as_text(
+    document: &Document,
+    options: &ExportOptions,
+    format: TextFormat,
+) -> ExportResult<Vec<u8>> {
+    Ok(render(document, options.text_width, format).into_bytes())
+}
+
+/// Render the document with paragraphs wrapped at the given width (in characters).
+fn render(document: &Document, width: usize, format: TextFormat) -> String {
+    let mut ctx = TextContext {
+        document,
+        format,
+        section_level: 0,
+    };
+
+    let blocks = ctx.blocks(document.structure.root(), width);
+
+    let mut text = join_blocks(blocks, true).join("\n");
+    text.push('\n');
+
+    text
+}
+
+/// Lines of a block, lists are kept apart since they follow other blocks within list items directly.
+struct Block {
+    lines: Vec<String>,
+    is_list: bool,
+}
+
+impl Block {
+    fn new(lines: Vec<String>) -> Self {
+        Self {
+            lines,
+            is_list: false,
+        }
+    }
+
+    fn list(lines: Vec<String>) -> Self {
+        Self {
+            lines,
+            is_list: true,
+        }
+    }
+}
+
+struct TextContext<'a> {
+    document: &'a Document,
+    format: TextFormat,
+
+    /// Number of sections the current node is nested in.
+    section_level: usize,
+}
+
+impl<'a> TextContext<'a> {
+    /// Blocks of lines the children of the node are rendered as.
+    /// Consecutive inline children are combined to a paragraph.
+    fn blocks(&mut self, node: &'a DocumentNode, width: usize) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+
+        for child in self.children(node) {
+            if is_block(&child.value) {
+                blocks.push(Block::new(
+                    self.paragraph(&std::mem::take(&mut inline), width),
+                ));
+                blocks.extend(self.block(child, width));
+            } else {
+                inline.push_str(&self.inline(child));
+            }
+        }
+        blocks.push(Block::new(self.paragraph(&inline, width)));
+
+        blocks.retain(|block| !block.lines.is_empty());
+        blocks
+    }
+
+    fn block(&mut self, node: &'a DocumentNode, width: usize) -> Vec<Block> {
+        match &node.value {
+            DocumentNodeValue::Section => {
+                self.section_level += 1;
+                let blocks = self.blocks(node, width);
+                self.section_level -= 1;
+
+                blocks
+            }
+            DocumentNodeValue::Heading => vec![Block::new(self.heading(node))],
+            DocumentNodeValue::Paragraph => {
+                let text = self.inline_children(node);
+                vec![Block::new(self.paragraph(&text, width))]
+            }
+            DocumentNodeValue::List => vec![Block::list(self.list(node, width))],
+            DocumentNodeValue::ListItem => vec![Block::list(self.list_item(node, width))],
+            DocumentNodeValue::Table => vec![Block::new(self.table(node))],
+            DocumentNodeValue::CodeBlock => vec![Block::new(self.code_block(node))],
+            _ => self.blocks(node, width),
+        }
+    }
+
+    /// Wrap the text, line breaks within the text are kept.
+    fn paragraph(&self, text: &str, width: usize) -> Vec<String> {
+        let mut segments: Vec<Vec<String>> = text
+            .split('\n')
+            .map(|segment| wrap(segment, width))
+            .collect();
+        while matches!(segments.last(), Some(lines) if lines.is_empty()) {
+            segments.pop();
+        }
+        if segments.iter().all(|lines| lines.is_empty()) {
+            return Vec::new();
+        }
+
+        let segment_count = segments.len();
+        let mut lines = Vec::new();
+        for (index, mut segment) in segments.into_iter().enumerate() {
+            if self.format == TextFormat::Markdown {
+                segment = segment.iter().map(|line| escape_line_start(line)).collect();
+
+                // Markdown joins lines unless they end with a backslash
+                if index + 1 < segment_count {
+                    match segment.last_mut() {
+                        Some(line) => line.push('\\'),
+                        None => segment.push("\\".to_owned()),
+                    }
+                }
+            }
+            lines.extend(segment);
+        }
+
+        lines
+    }
+
+    /// Headings are marked by their level in Markdown and underlined (up to the second level) in plain text.
+    fn heading(&mut self, node: &'a DocumentNode) -> Vec<String> {
+        let text = collapse_white_space(&self.inline_children(node));
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        let level = self.section_level.clamp(1, MAX_HEADING_LEVEL);
+        match self.format {
+            TextFormat::Markdown => vec![format!("{} {}", "#".repeat(level), text)],
+            TextFormat::Plain => {
+                let underline = match level {
+                    1 => "=",
+                    2 => "-",
+                    _ => return vec![text],
+                };
+                let underline = underline.repeat(text.chars().count());
+
+                vec![text, underline]
+            }
+        }
+    }
+
+    fn list(&mut self, node: &'a DocumentNode, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        for child in self.children(node) {
+            if let DocumentNodeValue::ListItem = child.value {
+                lines.extend(self.list_item(child, width));
+            } else if !is_block(&child.value) {
+                // Text between the items is only the white space of the script
+                continue;
+            } else {
+                lines.extend(join_blocks(self.block(child, width), false));
+            }
+        }
+
+        lines
+    }
+
+    /// Lines of the item with a marker in front of the first and the others indented to match.
+    fn list_item(&mut self, node: &'a DocumentNode, width: usize) -> Vec<String> {
+        const MARKER: &str = "- ";
+        const INDENT: &str = "  ";
+
+        let blocks = self.blocks(node, width.saturating_sub(MARKER.len()));
+        let lines = join_blocks(blocks, false);
+        if lines.is_empty() {
+            return vec![MARKER.trim_end().to_owned()];
+        }
+
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| match (index, line.is_empty()) {
+                (0, _) => format!("{}{}", MARKER, line),
+                (_, true) => line,
+                (_, false) => format!("{}{}", INDENT, line),
+            })
+            .collect()
+    }
+
+    /// Pipe table with the first row as header, since Markdown tables always have one.
+    /// The cells are padded to the width of their column, so the table is readable as plain text as well.
+    fn table(&mut self, node: &'a DocumentNode) -> Vec<String> {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for row in self.children(node) {
+            if !matches!(row.value, DocumentNodeValue::TableRow) {
+                continue;
+            }
+
+            let mut cells = Vec::new();
+            for cell in self.children(row) {
+                if matches!(cell.value, DocumentNodeValue::TableCell) {
+                    let text = collapse_white_space(&self.inline_children(cell));
+                    cells.push(text.replace('|', "\\|"));
+                }
+            }
+            rows.push(cells);
+        }
+
+        let column_count = rows.iter().map(|cells| cells.len()).max().unwrap_or(0);
+        if column_count == 0 {
+            return Vec::new();
+        }
+
+        // The delimiter row needs at least three dashes per column
+        let mut column_widths = vec![3; column_count];
+        for cells in &rows {
+            for (column, cell) in cells.iter().enumerate() {
+                column_widths[column] = column_widths[column].max(cell.chars().count());
+            }
+        }
+
+        let to_line = |cells: Vec<String>| {
+            let cells: Vec<String> = column_widths
+                .iter()
+                .enumerate()
+                .map(|(column, column_width)| {
+                    let cell = cells.get(column).map(|cell| cell.as_str()).unwrap_or("");
+                    format!(
+                        "{}{}",
+                        cell,
+                        " ".repeat(column_width - cell.chars().count())
+                    )
+                })
+                .collect();
+
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut rows = rows.into_iter();
+        let mut lines = vec![to_line(rows.next().unwrap_or_default())];
+        lines.push(to_line(
+            column_widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect(),
+        ));
+        lines.extend(rows.map(to_line));
+
+        lines
+    }
+
+    /// Code blocks keep their lines, they are fenced in Markdown and indented in plain text.
+    fn code_block(&self, node: &DocumentNode) -> Vec<String> {
+        let content = self.structure().text_content(node.id);
+        let content = content.trim_start_matches('\n').trim_end();
+
+        match self.format {
+            TextFormat::Markdown => {
+                // The fence must be longer than any run of backticks within the code
+                let fence = "`".repeat(3.max(longest_run(content, '`') + 1));
+                let language = node
+                    .attributes
+                    .get(LANGUAGE_ATTRIBUTE)
+                    .cloned()
+                    .unwrap_or_default();
+
+                let mut lines = vec![format!("{}{}", fence, language)];
+                lines.extend(content.lines().map(|line| line.to_owned()));
+                lines.push(fence);
+
+                lines
+            }
+            TextFormat::Plain => content
+                .lines()
+                .map(|line| match line.is_empty() {
+                    true => String::new(),
+                    false => format!("    {}", line),
+                })
+                .collect(),
+        }
+    }
+
+    /// Text of the node with inline formatting, line breaks are kept as `\n`.
+    fn inline(&mut self, node: &'a DocumentNode) -> String {
+        let markdown = self.format == TextFormat::Markdown;
+
+        match &node.value {
+            DocumentNodeValue::Text(text) => {
+                let text: String = self
+                    .resolve_variables(text)
+                    .chars()
+                    .map(|c| if c.is_whitespace() { ' ' } else { c })
+                    .collect();
+
+                if markdown {
+                    escape_markdown(&text)
+                } else {
+                    text
+                }
+            }
+            DocumentNodeValue::Bold => emphasize(&self.inline_children(node), "**"),
+            DocumentNodeValue::Italic => emphasize(&self.inline_children(node), "*"),
+            DocumentNodeValue::StrikeThrough if markdown => {
+                emphasize(&self.inline_children(node), "~~")
+            }
+            DocumentNodeValue::Code => code_span(&self.structure().text_content(node.id)),
+            DocumentNodeValue::Break => "\n".to_owned(),
+            DocumentNodeValue::Link(target) => {
+                let content = self.inline_children(node);
+                match (self.format, target) {
+                    (TextFormat::Markdown, LinkTarget::Uri(uri)) => {
+                        format!("[{}]({})", content, to_destination(uri))
+                    }
+                    (TextFormat::Markdown, LinkTarget::Anchor(name)) => {
+                        format!("[{}](#{})", content, to_destination(name))
+                    }
+                    (TextFormat::Plain, LinkTarget::Uri(uri)) if content.trim() != uri => {
+                        format!("{} <{}>", content, uri)
+                    }
+                    (TextFormat::Plain, _) => content,
+                }
+            }
+            DocumentNodeValue::Image { source, .. } => {
+                let alt = node
+                    .attributes
+                    .get(ALT_ATTRIBUTE)
+                    .map(|alt| collapse_white_space(alt))
+                    .unwrap_or_default();
+
+                match self.format {
+                    TextFormat::Markdown => {
+                        format!("![{}]({})", escape_markdown(&alt), to_destination(source))
+                    }
+                    TextFormat::Plain => {
+                        let mut reference = "[Image".to_owned();
+                        if !alt.is_empty() {
+                            reference.push_str(": ");
+                            reference.push_str(&alt);
+                        }
+                        if !source.is_empty() {
+                            reference.push_str(&format!(" <{}>", source));
+                        }
+                        reference.push(']');
+
+                        reference
+                    }
+                }
+            }
+            _ => self.inline_children(node),
+        }
+    }
+
+    fn inline_children(&mut self, node: &'a DocumentNode) -> String {
+        self.children(node)
+            .map(|child| self.inline(child))
+            .collect::<Vec<String>>()
+            .concat()
+    }
+
+    /// Replace references to document variables (`$name`) by their values.
+    /// Dollar signs not followed by the name of a defined variable are kept as they are, since they are common in text.
+    fn resolve_variables(&self, text: &str) -> String {
+        let variables = &self.document.meta_data.variables;
+
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(index) = rest.find(VARIABLE_PREFIX) {
+            result.push_str(&rest[..index]);
+
+            let after_prefix = &rest[index + VARIABLE_PREFIX.len_utf8()..];
+            let name_length = after_prefix
+                .find(|c: char| !is_name_character(c))
+                .unwrap_or(after_prefix.len());
+            let value = Some(&after_prefix[..name_length])
+                .filter(|name| !name.is_empty())
+                .and_then(|name| variables.get(name));
+
+            match value {
+                Some(value) => {
+                    result.push_str(&value);
+                    rest = &after_prefix[name_length..];
+                }
+                None => {
+                    result.push(VARIABLE_PREFIX);
+                    rest = after_prefix;
+                }
+            }
+        }
+        result.push_str(rest);
+
+        result
+    }
+
+    fn children(&self, node: &'a DocumentNode) -> impl Iterator<Item = &'a DocumentNode> + 'a {
+        let structure = self.structure();
+        node.children()
+            .iter()
+            .filter_map(move |child_id| structure.get_node(*child_id))
+    }
+
+    fn structure(&self) -> &'a DocumentStructure {
+        &self.document.structure
+    }
+}
+
+/// Join the blocks to lines separated by an empty line.
+/// Unless the blocks are always separated, lists directly follow the preceding block,
+/// so that nested lists stay part of their list item.
+fn join_blocks(blocks: Vec<Block>, always_separated: bool) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for block in blocks {
+        if !lines.is_empty() && (always_separated || !block.is_list) {
+            lines.push(String::new());
+        }
+        lines.extend(block.lines);
+    }
+
+    lines
+}
+
+/// Put the markers around the text, white space at the start and end stays outside,
+/// since Markdown does not recognize emphasis next to white space.
+fn emphasize(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_owned();
+    }
+
+    let start = text.len() - text.trim_start().len();
+    let end = start + trimmed.len();
+    format!(
+        "{}{}{}{}{}",
+        &text[..start],
+        marker,
+        trimmed,
+        marker,
+        &text[end..]
+    )
+}
+
+/// Inline code delimited by more backticks than any run of backticks within the code.
+fn code_span(code: &str) -> String {
+    let code = collapse_white_space(code);
+    let delimiter = "`".repeat(longest_run(&code, '`') + 1);
+
+    // Code starting or ending with a backtick is padded, so it is not taken for the delimiter
+    if code.starts_with('`') || code.ends_with('`') {
+        format!("{0} {1} {0}", delimiter, code)
+    } else {
+        format!("{0}{1}{0}", delimiter, code)
+    }
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+fn collapse_white_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Escape characters with a meaning in Markdown, those only meaningful at the start of a line
+/// are escaped after wrapping.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Link destinations must not contain white space or unbalanced brackets.
+fn to_destination(uri: &str) -> String {
+    uri.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+fn is_name_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use document::structure::{DocumentNodeValue, DocumentStructure, NodeId};
+
+    use crate::test_document::{add_node, add_node_with_attributes, add_text, document_of};
+
+    use super::*;
+
+    /// Document with a single section and the nodes added by the function to it.
+    fn document(add_content: impl FnOnce(&mut DocumentStructure, NodeId)) -> Document {
+        let mut structure = DocumentStructure::new();
+        let root = structure.root().id;
+        let section = add_node(&mut structure, root, "section", DocumentNodeValue::Section);
+        add_content(&mut structure, section);
+
+        document_of(structure, "")
+    }
+
+    fn add_paragraph(structure: &mut DocumentStructure, parent: NodeId, text: &str) -> NodeId {
+        let paragraph = add_node(structure, parent, "paragraph", DocumentNodeValue::Paragraph);
+        add_text(structure, paragraph, text);
+
+        paragraph
+    }
+
+    fn add_heading(structure: &mut DocumentStructure, parent: NodeId, text: &str) {
+        let heading = add_node(structure, parent, "heading", DocumentNodeValue::Heading);
+        add_text(structure, heading, text);
+    }
+
+    #[test]
+    fn should_mark_headings_by_section_level() {
+        // Given: headings of three nested sections
+        let document = document(|structure, section| {
+            add_heading(structure, section, "Title");
+            add_paragraph(structure, section, "Intro");
+            let part = add_node(structure, section, "section", DocumentNodeValue::Section);
+            add_heading(structure, part, "Part");
+            let detail = add_node(structure, part, "section", DocumentNodeValue::Section);
+            add_heading(structure, detail, "Detail");
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the first two levels are underlined in plain text and all levels are marked in Markdown
+        assert_eq!(plain, "Title\n=====\n\nIntro\n\nPart\n----\n\nDetail\n");
+        assert_eq!(markdown, "# Title\n\nIntro\n\n## Part\n\n### Detail\n");
+    }
+
+    #[test]
+    fn should_emphasize_bold_and_italic_text() {
+        // Given: a paragraph with bold and italic text
+        let document = document(|structure, section| {
+            let paragraph = add_paragraph(structure, section, "A ");
+            let bold = add_node(structure, paragraph, "b", DocumentNodeValue::Bold);
+            add_text(structure, bold, "strong ");
+            add_text(structure, paragraph, "and ");
+            let italic = add_node(structure, paragraph, "i", DocumentNodeValue::Italic);
+            add_text(structure, italic, "slanted");
+            add_text(structure, paragraph, " word.");
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the markers surround the text without its white space
+        assert_eq!(plain, "A **strong** and *slanted* word.\n");
+        assert_eq!(markdown, "A **strong** and *slanted* word.\n");
+    }
+
+    #[test]
+    fn should_indent_nested_lists() {
+        // Given: a list with a nested list in its first item
+        let document = document(|structure, section| {
+            let list = add_node(structure, section, "list", DocumentNodeValue::List);
+            let first = add_node(structure, list, "list-item", DocumentNodeValue::ListItem);
+            add_text(structure, first, "One");
+            let nested = add_node(structure, first, "list", DocumentNodeValue::List);
+            let nested_item = add_node(structure, nested, "list-item", DocumentNodeValue::ListItem);
+            add_text(structure, nested_item, "Nested");
+            let second = add_node(structure, list, "list-item", DocumentNodeValue::ListItem);
+            add_text(structure, second, "Two");
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the items are marked and the nested list is indented below its item
+        assert_eq!(plain, "- One\n  - Nested\n- Two\n");
+        assert_eq!(markdown, "- One\n  - Nested\n- Two\n");
+    }
+
+    #[test]
+    fn should_pad_table_cells_and_escape_pipes() {
+        // Given: a table with a pipe in one of its cells
+        let document = document(|structure, section| {
+            let table = add_node(structure, section, "table", DocumentNodeValue::Table);
+            for cells in [["Name", "Note"], ["Tom", "a|b c"]] {
+                let row = add_node(structure, table, "table-row", DocumentNodeValue::TableRow);
+                for text in cells {
+                    let cell = add_node(structure, row, "table-cell", DocumentNodeValue::TableCell);
+                    add_text(structure, cell, text);
+                }
+            }
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the cells are padded to the width of their column and the pipe is escaped
+        let expected = "| Name | Note   |\n| ---- | ------ |\n| Tom  | a\\|b c |\n";
+        assert_eq!(plain, expected);
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn should_reference_images() {
+        // Given: a paragraph with an image with an alternate description
+        let document = document(|structure, section| {
+            let paragraph = add_node(
+                structure,
+                section,
+                "paragraph",
+                DocumentNodeValue::Paragraph,
+            );
+            add_node_with_attributes(
+                structure,
+                paragraph,
+                "image",
+                DocumentNodeValue::Image {
+                    source: "images/my cat.png".to_owned(),
+                    width: None,
+                    height: None,
+                },
+                &[("alt", "A [cat]")],
+            );
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the image is referenced by its description and source
+        assert_eq!(plain, "[Image: A [cat] <images/my cat.png>]\n");
+        assert_eq!(markdown, "![A \\[cat\\]](images/my%20cat.png)\n");
+    }
+
+    #[test]
+    fn should_fence_code_blocks() {
+        // Given: a code block containing backticks
+        let document = document(|structure, section| {
+            let code_block = add_node_with_attributes(
+                structure,
+                section,
+                "code-block",
+                DocumentNodeValue::CodeBlock,
+                &[("lang", "rust")],
+            );
+            add_text(
+                structure,
+                code_block,
+                "\nfn main() {\n\n    let fence = \"```\";\n}\n",
+            );
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the code is indented in plain text and fenced by more backticks than it contains in Markdown
+        assert_eq!(
+            plain,
+            "    fn main() {\n\n        let fence = \"```\";\n    }\n"
+        );
+        assert_eq!(
+            markdown,
+            "````rust\nfn main() {\n\n    let fence = \"```\";\n}\n````\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_line_breaks() {
+        // Given: a paragraph with a line break
+        let document = document(|structure, section| {
+            let paragraph = add_paragraph(structure, section, "First line");
+            add_node(structure, paragraph, "br", DocumentNodeValue::Break);
+            add_text(structure, paragraph, "second line");
+        });
+
+        // When: rendering the document as plain text and Markdown
+        let plain = render(&document, 80, TextFormat::Plain);
+        let markdown = render(&document, 80, TextFormat::Markdown);
+
+        // Then: the line is broken, with a backslash as hard line break in Markdown
+        assert_eq!(plain, "First line\nsecond line\n");
+        assert_eq!(markdown, "First line\\\nsecond line\n");
+    }
+
+    #[test]
+    fn should_replace_defined_variables() {
+        // Given: a paragraph referencing a defined variable and one referencing an unknown name
+        let mut document = document(|structure, section| {
+            add_paragraph(structure, section, "Version $version-name.");
+            add_paragraph(structure, section, "Costs $5 or $unknown.");
+        });
+        document.meta_data.variables.set("version-name", "1.2");
+
+        // When: rendering the document as plain text
+        let plain = render(&document, 80, TextFormat::Plain);
+
+        // Then: the defined variable is replaced and the other dollar signs are kept
+        assert_eq!(plain, "Version 1.2.\n\nCosts $5 or $unknown.\n");
+    }
+}
@@ -0,0 +1,162 @@
+/// Break the text into lines of at most the given width at white space.
+/// Words longer than the width are not split but get a line of their own.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = word.chars().count();
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+    }
+
+    if line_width > 0 {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Escape characters at the start of a line that would otherwise begin a Markdown block
+/// (for example a heading, list item or block quote).
+pub(crate) fn escape_line_start(line: &str) -> String {
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        // Ordered list items start with a number followed by a dot or closing bracket and white space
+        let mut rest = line[digits..].chars();
+        if let Some(c @ ('.' | ')')) = rest.next() {
+            if rest.next().is_none_or(char::is_whitespace) {
+                return format!("{}\\{}{}", &line[..digits], c, &line[digits + 1..]);
+            }
+        }
+        return line.to_owned();
+    }
+
+    match line.chars().next() {
+        Some('#' | '-' | '+' | '=' | '>') => format!("\\{}", line),
+        _ => line.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_break_lines_at_white_space() {
+        // Given: a text longer than the width
+        let text = "The quick brown fox jumps over the lazy dog";
+
+        // When: wrapping the text
+        let lines = wrap(text, 15);
+
+        // Then: the lines are filled up to the width
+        assert_eq!(
+            lines,
+            vec!["The quick brown", "fox jumps over", "the lazy dog"]
+        );
+    }
+
+    #[test]
+    fn should_keep_long_words() {
+        // Given: a word longer than the width between short words
+        let text = "a incomprehensibilities b";
+
+        // When: wrapping the text
+        let lines = wrap(text, 10);
+
+        // Then: the word gets a line of its own without being split
+        assert_eq!(lines, vec!["a", "incomprehensibilities", "b"]);
+    }
+
+    #[test]
+    fn should_collapse_white_space() {
+        // Given: a text with runs of white space and line breaks
+        let text = "  one \t two\n\nthree  ";
+
+        // When: wrapping the text
+        let lines = wrap(text, 80);
+
+        // Then: words are separated by single spaces
+        assert_eq!(lines, vec!["one two three"]);
+    }
+
+    #[test]
+    fn should_count_characters_rather_than_bytes() {
+        // Given: words with multi-byte characters that exactly fill the width
+        let text = "größe über";
+
+        // When: wrapping the text
+        let lines = wrap(text, 10);
+
+        // Then: the words stay on the same line
+        assert_eq!(lines, vec!["größe über"]);
+    }
+
+    #[test]
+    fn should_return_no_lines_for_empty_text() {
+        // Given: a text consisting of white space only
+        let text = " \n ";
+
+        // When: wrapping the text
+        let lines = wrap(text, 80);
+
+        // Then: there are no lines
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn should_escape_block_markers_at_line_start() {
+        // Given: lines starting like Markdown blocks
+        let lines = ["# Heading", "- item", "+ item", "= underline", "> quote"];
+
+        // When: escaping the start of the lines
+        let escaped: Vec<String> = lines.iter().map(|line| escape_line_start(line)).collect();
+
+        // Then: the markers are escaped
+        assert_eq!(
+            escaped,
+            vec![
+                "\\# Heading",
+                "\\- item",
+                "\\+ item",
+                "\\= underline",
+                "\\> quote"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_escape_ordered_list_markers() {
+        // Given: lines starting like ordered list items
+        let lines = ["1. item", "42) item", "3."];
+
+        // When: escaping the start of the lines
+        let escaped: Vec<String> = lines.iter().map(|line| escape_line_start(line)).collect();
+
+        // Then: the character after the number is escaped
+        assert_eq!(escaped, vec!["1\\. item", "42\\) item", "3\\."]);
+    }
+
+    #[test]
+    fn should_keep_other_lines() {
+        // Given: lines that do not start a block
+        let lines = ["2023 was a year", "3.5 liters", "text # not a heading", ""];
+
+        // When: escaping the start of the lines
+        let escaped: Vec<String> = lines.iter().map(|line| escape_line_start(line)).collect();
+
+        // Then: the lines are kept as they are
+        assert_eq!(escaped, lines);
+    }
+}
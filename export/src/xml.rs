@@ -0,0 +1,40 @@
+/// Escape the characters with a special meaning in XML text and attribute values.
+pub(crate) fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_escape_markup_characters() {
+        // Given: a text containing characters with a special meaning in XML
+        let value = "<a href=\"x\">Tom & Jerry</a>";
+
+        // When: escaping the text
+        let escaped = escape_xml(value);
+
+        // Then: the characters are replaced by entities
+        assert_eq!(
+            escaped,
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn should_escape_ampersand_of_entities_only_once() {
+        // Given: a text that already looks like an entity
+        let value = "&lt;";
+
+        // When: escaping the text
+        let escaped = escape_xml(value);
+
+        // Then: the entity is written as text
+        assert_eq!(escaped, "&amp;lt;");
+    }
+}
@@ -3,6 +3,9 @@ use std::path::Path;
 
 use harfbuzz_rs::{subset, Blob, Face, Font, Owned, Shared, Tag, Variation};
 
+use crate::line_metrics::LineMetrics;
+use crate::outline::GlyphOutlines;
+use crate::script_metrics::ScriptMetrics;
 use crate::variation::{FontVariationId, FontVariationIdGenerator};
 use crate::LetterFontVariation;
 
@@ -52,6 +55,216 @@ impl<'a> LetterFont<'a> {
         &self.internal_font
     }
 
+    pub fn units_per_em(&self) -> usize {
+        self.internal_font.face().upem() as usize
+    }
+
+    /// Height of the lowercase letter "x" in font units.
+    pub fn x_height(&self) -> Option<i32> {
+        let glyph = self.internal_font.get_nominal_glyph('x')?;
+        let extents = self.internal_font.get_glyph_extents(glyph)?;
+
+        Some(extents.y_bearing)
+    }
+
+    /// Horizontal advance of the glyph for the given character in font units.
+    pub fn char_advance(&self, c: char) -> Option<i32> {
+        let glyph = self.internal_font.get_nominal_glyph(c)?;
+
+        Some(self.internal_font.get_glyph_h_advance(glyph))
+    }
+
+    /// Underline position and thickness as specified in the fonts `post` table.
+    /// The position in the table denotes the top of the underline.
+    pub fn underline_metrics(&self) -> LineMetrics {
+        let post = Tag::new('p', 'o', 's', 't');
+        let position = self.read_table_value(post, 8);
+        let thickness = self.read_table_value(post, 10).filter(|t| *t > 0);
+
+        match position.zip(thickness) {
+            Some((position, thickness)) => LineMetrics {
+                position: position - thickness / 2,
+                thickness,
+            },
+            None => {
+                let units_per_em = self.units_per_em() as i32;
+
+                LineMetrics {
+                    position: -units_per_em / 10,
+                    thickness: units_per_em / 20,
+                }
+            }
+        }
+    }
+
+    /// Strikeout position and thickness as specified in the fonts `OS/2` table,
+    /// whose position denotes the top of the strikeout stroke.
+    /// Falls back to a line at half the x-height.
+    pub fn strike_through_metrics(&self) -> LineMetrics {
+        let os2 = Tag::new('O', 'S', '/', '2');
+        let thickness = self.read_table_value(os2, 26).filter(|t| *t > 0);
+        let position = self.read_table_value(os2, 28);
+
+        match position.zip(thickness) {
+            Some((position, thickness)) => LineMetrics {
+                position: position - thickness / 2,
+                thickness,
+            },
+            None => {
+                let x_height = self.x_height().unwrap_or(self.units_per_em() as i32 / 2);
+
+                LineMetrics {
+                    position: x_height / 2,
+                    thickness: self.underline_metrics().thickness,
+                }
+            }
+        }
+    }
+
+    /// Overline at the ascender of the font with the thickness of the underline.
+    pub fn overline_metrics(&self) -> LineMetrics {
+        let ascender = self
+            .internal_font
+            .get_font_h_extents()
+            .map(|extents| extents.ascender)
+            .unwrap_or(self.units_per_em() as i32 * 4 / 5);
+        let thickness = self.underline_metrics().thickness;
+
+        LineMetrics {
+            position: ascender - thickness / 2,
+            thickness,
+        }
+    }
+
+    /// Superscript size and offset as specified in the fonts `OS/2` table.
+    pub fn superscript_metrics(&self) -> ScriptMetrics {
+        let units_per_em = self.units_per_em() as i32;
+
+        self.read_script_metrics(20, 24, 1)
+            .unwrap_or(ScriptMetrics {
+                size: units_per_em * 2 / 3,
+                offset: units_per_em / 3,
+            })
+    }
+
+    /// Subscript size and offset as specified in the fonts `OS/2` table.
+    pub fn subscript_metrics(&self) -> ScriptMetrics {
+        let units_per_em = self.units_per_em() as i32;
+
+        self.read_script_metrics(12, 16, -1)
+            .unwrap_or(ScriptMetrics {
+                size: units_per_em * 2 / 3,
+                offset: -units_per_em / 7,
+            })
+    }
+
+    /// The `OS/2` table stores subscript offsets as positive values pointing downwards,
+    /// thus the offset is multiplied with the given direction.
+    fn read_script_metrics(
+        &self,
+        size_offset: usize,
+        position_offset: usize,
+        direction: i32,
+    ) -> Option<ScriptMetrics> {
+        let os2 = Tag::new('O', 'S', '/', '2');
+        let size = self.read_table_value(os2, size_offset).filter(|s| *s > 0)?;
+        let offset = self.read_table_value(os2, position_offset)?;
+
+        Some(ScriptMetrics {
+            size,
+            offset: offset * direction,
+        })
+    }
+
+    /// Whether the license of the font permits embedding it into documents
+    /// as specified by the embedding permissions (`fsType`) of the fonts `OS/2` table.
+    /// Fonts restricted from embedding or permitting to embed bitmaps only may not be embedded.
+    pub fn is_embedding_permitted(&self) -> bool {
+        let os2 = Tag::new('O', 'S', '/', '2');
+        let permissions = match self.read_table_value(os2, 8) {
+            Some(permissions) => permissions as u16,
+            None => return true,
+        };
+
+        let is_restricted = permissions & 0x000F == 0x0002;
+        let is_bitmap_only = permissions & 0x0200 != 0;
+
+        !is_restricted && !is_bitmap_only
+    }
+
+    /// Family name of the font as specified in the fonts `name` table,
+    /// preferring the typographic family name over the legacy family name.
+    pub fn family_name(&self) -> Option<String> {
+        const TYPOGRAPHIC_FAMILY_NAME_ID: u16 = 16;
+        const FAMILY_NAME_ID: u16 = 1;
+
+        self.read_name(TYPOGRAPHIC_FAMILY_NAME_ID)
+            .or_else(|| self.read_name(FAMILY_NAME_ID))
+    }
+
+    /// Read the name record with the given ID from the fonts `name` table.
+    /// Names of the Unicode and Windows platforms are encoded in UTF-16BE,
+    /// names of the Macintosh platform are read as ASCII.
+    fn read_name(&self, name_id: u16) -> Option<String> {
+        let table = self
+            .internal_font
+            .face()
+            .table_with_tag(Tag::new('n', 'a', 'm', 'e'))?;
+        let read_u16 = |offset: usize| {
+            table
+                .get(offset..offset + 2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+
+        let count = read_u16(2)? as usize;
+        let strings_offset = read_u16(4)? as usize;
+        for record in (0..count).map(|index| 6 + index * 12) {
+            if read_u16(record + 6)? != name_id {
+                continue;
+            }
+
+            let platform_id = read_u16(record)?;
+            let length = read_u16(record + 8)? as usize;
+            let offset = strings_offset + read_u16(record + 10)? as usize;
+            let bytes = table.get(offset..offset + length)?;
+
+            let name = match platform_id {
+                0 | 3 => {
+                    let units: Vec<u16> = bytes
+                        .chunks_exact(2)
+                        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                        .collect();
+                    String::from_utf16(&units).ok()
+                }
+                1 => Some(bytes.iter().map(|byte| *byte as char).collect()),
+                _ => None,
+            };
+
+            if name.is_some() {
+                return name;
+            }
+        }
+
+        None
+    }
+
+    /// Whether the fonts `GSUB` table lists the OpenType substitution feature with the given tag
+    /// for any script, which allows checking for a feature without shaping any text.
+    pub fn has_substitution_feature(&self, tag: Tag) -> bool {
+        self.internal_font
+            .face()
+            .table_with_tag(Tag::new('G', 'S', 'U', 'B'))
+            .is_some_and(|table| lists_feature(&table, tag))
+    }
+
+    /// Read a big-endian 16-bit signed value at the given byte offset of a font table.
+    fn read_table_value(&self, tag: Tag, offset: usize) -> Option<i32> {
+        let table = self.internal_font.face().table_with_tag(tag)?;
+        let bytes = table.get(offset..offset + 2)?;
+
+        Some(i16::from_be_bytes([bytes[0], bytes[1]]) as i32)
+    }
+
     pub fn mark_codepoint_as_used(&mut self, codepoint: u32) {
         self.used_codepoints.insert(codepoint);
     }
@@ -83,6 +296,11 @@ impl<'a> LetterFont<'a> {
             .map(|font| font.face().face_data().to_vec())
     }
 
+    /// Outlines of the glyphs of the subsetted font of the given variation.
+    pub fn glyph_outlines(&self, variation_id: &FontVariationId) -> Option<GlyphOutlines> {
+        GlyphOutlines::from_vec(self.get_subsetted_font_data(variation_id)?)
+    }
+
     pub(crate) fn subset(&mut self) {
         let used_codepoints: Vec<u32> = self.used_codepoints.iter().copied().collect();
 
@@ -113,3 +331,65 @@ impl<'a> LetterFont<'a> {
             .collect()
     }
 }
+
+/// Whether the feature list of the given `GSUB` or `GPOS` table contains a record with the tag.
+fn lists_feature(table: &[u8], tag: Tag) -> bool {
+    let read_u16 = |offset: usize| {
+        table
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let feature_list = match read_u16(6) {
+        Some(offset) => offset,
+        None => return false,
+    };
+    let count = read_u16(feature_list).unwrap_or(0);
+
+    (0..count)
+        .map(|index| feature_list + 2 + index * 6)
+        .filter_map(|record| table.get(record..record + 4))
+        .any(|bytes| Tag(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])) == tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_with_features(tags: &[&[u8; 4]]) -> Vec<u8> {
+        // Header with the feature list directly following it
+        let mut table = vec![0, 1, 0, 0, 0, 0, 0, 10, 0, 0];
+        table.extend_from_slice(&(tags.len() as u16).to_be_bytes());
+        for tag in tags {
+            table.extend_from_slice(&tag[..]);
+            table.extend_from_slice(&[0, 0]);
+        }
+        table
+    }
+
+    #[test]
+    fn should_find_listed_feature() {
+        // Given: A table listing the `liga` and `smcp` features
+        let table = table_with_features(&[b"liga", b"smcp"]);
+
+        // When: Looking for the features
+        let has_small_caps = lists_feature(&table, Tag::new('s', 'm', 'c', 'p'));
+        let has_superscript = lists_feature(&table, Tag::new('s', 'u', 'p', 's'));
+
+        // Then: Only the listed feature is found
+        assert!(has_small_caps);
+        assert!(!has_superscript);
+    }
+
+    #[test]
+    fn should_not_find_feature_in_truncated_table() {
+        // Given: A table whose feature list is cut off in the middle of a record
+        let mut table = table_with_features(&[b"smcp"]);
+        table.truncate(table.len() - 4);
+
+        // When: Looking for the feature
+        let has_small_caps = lists_feature(&table, Tag::new('s', 'm', 'c', 'p'));
+
+        // Then: The feature is not found
+        assert!(!has_small_caps);
+    }
+}
@@ -5,9 +5,12 @@ use font_kit::handle::Handle;
 use font_kit::properties::{Properties, Stretch, Style, Weight};
 use font_kit::source::SystemSource;
 
-use document::style::{FontFamilyType, FontStyle};
+use document::style::{FontFace, FontFamilyType, FontStyle};
 pub use font::LetterFont;
 pub use id::FontId;
+pub use line_metrics::LineMetrics;
+pub use outline::{GlyphOutlines, OutlineCommand};
+pub use script_metrics::ScriptMetrics;
 pub use style::FontStyleSettings;
 pub use variation::{FontVariationId, LetterFontVariation};
 
@@ -15,6 +18,9 @@ use crate::id::FontIdGenerator;
 
 mod font;
 mod id;
+mod line_metrics;
+mod outline;
+mod script_metrics;
 mod style;
 mod variation;
 
@@ -33,13 +39,17 @@ enum FontKey {
         font_family_type: FontFamilyType,
         style: FontStyleSettings,
     },
-    Path(String),
+    Path {
+        path: String,
+        index: u32,
+    },
 }
 
 pub struct FontManager<'a> {
     font_id_generator: FontIdGenerator,
     registered_fonts: HashMap<FontId, LetterFont<'a>>,
     key_to_id: HashMap<FontKey, FontId>,
+    font_faces: Vec<FontFace>,
 }
 
 impl<'a> FontManager<'a> {
@@ -48,6 +58,7 @@ impl<'a> FontManager<'a> {
             font_id_generator: FontIdGenerator::new(),
             registered_fonts: HashMap::new(),
             key_to_id: HashMap::new(),
+            font_faces: Vec::new(),
         };
 
         let default_font = LetterFont::from_bytes(DEFAULT_FONT_BYTES, 0);
@@ -64,6 +75,12 @@ impl<'a> FontManager<'a> {
         return font_id;
     }
 
+    /// Register a font file for a font family name.
+    /// Font faces take precedence over system fonts when looking up fonts by name.
+    pub fn register_font_face(&mut self, font_face: FontFace) {
+        self.font_faces.push(font_face);
+    }
+
     pub fn get_font(&self, font_id: &FontId) -> Option<&LetterFont> {
         self.registered_fonts.get(font_id)
     }
@@ -120,8 +137,14 @@ impl<'a> FontManager<'a> {
             return Some(font_id.clone());
         }
 
-        let family_name = FamilyName::Title(name.to_owned());
-        let font_id = self.find_by_family_name(family_name, style);
+        let font_id = self
+            .find_font_face(name, &style)
+            .map(|font_face| (font_face.path.clone(), font_face.index))
+            .and_then(|(path, index)| self.find_by_path(&path, index))
+            .or_else(|| {
+                let family_name = FamilyName::Title(name.to_owned());
+                self.find_by_family_name(family_name, style)
+            });
 
         if let Some(font_id) = font_id {
             self.key_to_id.insert(key, font_id);
@@ -162,14 +185,17 @@ impl<'a> FontManager<'a> {
             })
     }
 
-    pub fn find_by_path(&mut self, path: &str) -> Option<FontId> {
-        let key = FontKey::Path(path.to_owned());
+    pub fn find_by_path(&mut self, path: &str, index: u32) -> Option<FontId> {
+        let key = FontKey::Path {
+            path: path.to_owned(),
+            index,
+        };
 
         if let Some(font_id) = self.key_to_id.get(&key) {
             return Some(font_id.clone());
         }
 
-        let font_id = LetterFont::from_path(path, 0)
+        let font_id = LetterFont::from_path(path, index)
             .ok()
             .map(|font| self.register_font(font));
 
@@ -180,6 +206,36 @@ impl<'a> FontManager<'a> {
         font_id
     }
 
+    /// Find the registered font face of the given family best matching the given style.
+    /// Similar to CSS font matching the font style is matched first, then the closest weight.
+    fn find_font_face(&self, family: &str, style: &FontStyleSettings) -> Option<&FontFace> {
+        let style_preference = match style.style() {
+            FontStyle::Normal => [FontStyle::Normal, FontStyle::Oblique, FontStyle::Italic],
+            FontStyle::Italic => [FontStyle::Italic, FontStyle::Oblique, FontStyle::Normal],
+            FontStyle::Oblique => [FontStyle::Oblique, FontStyle::Italic, FontStyle::Normal],
+        };
+        let weight = style.weight();
+
+        self.font_faces
+            .iter()
+            .filter(|font_face| font_face.is_of_family(family))
+            .min_by(|a, b| {
+                let style_rank = |font_face: &FontFace| {
+                    style_preference
+                        .iter()
+                        .position(|style| *style == font_face.style)
+                };
+                let weight_distance = |font_face: &FontFace| {
+                    let (min, max) = font_face.weight;
+                    (min - weight).max(weight - max).max(0.0)
+                };
+
+                style_rank(a)
+                    .cmp(&style_rank(b))
+                    .then(weight_distance(a).total_cmp(&weight_distance(b)))
+            })
+    }
+
     pub fn subset_fonts(&mut self) {
         for font in self.registered_fonts.values_mut() {
             font.subset();
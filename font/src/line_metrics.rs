@@ -0,0 +1,7 @@
+/// Vertical position and thickness of a line drawn for a text decoration in font units.
+/// The position is the center of the line relative to the baseline with positive values pointing upwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineMetrics {
+    pub position: i32,
+    pub thickness: i32,
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use font_kit::hinting::HintingOptions;
+use font_kit::loaders::default::Font;
+use font_kit::outline::OutlineSink;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+
+/// Drawing command of a glyph outline in font units with the y-axis pointing upwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineCommand {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// Outlines of the glyphs of a single variation of a font.
+pub struct GlyphOutlines {
+    font: Font,
+}
+
+impl GlyphOutlines {
+    pub(crate) fn from_vec(font_data: Vec<u8>) -> Option<Self> {
+        Font::from_bytes(Arc::new(font_data), 0)
+            .ok()
+            .map(|font| Self { font })
+    }
+
+    /// Outline of the glyph with the given ID, which is empty for glyphs without an outline (like spaces).
+    pub fn outline(&self, glyph_id: u32) -> Option<Vec<OutlineCommand>> {
+        let mut sink = CommandSink {
+            commands: Vec::new(),
+        };
+        self.font
+            .outline(glyph_id, HintingOptions::None, &mut sink)
+            .ok()?;
+
+        Some(sink.commands)
+    }
+}
+
+struct CommandSink {
+    commands: Vec<OutlineCommand>,
+}
+
+impl OutlineSink for CommandSink {
+    fn move_to(&mut self, to: Vector2F) {
+        self.commands.push(OutlineCommand::MoveTo(to.x(), to.y()));
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.commands.push(OutlineCommand::LineTo(to.x(), to.y()));
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.commands
+            .push(OutlineCommand::QuadTo(ctrl.x(), ctrl.y(), to.x(), to.y()));
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.commands.push(OutlineCommand::CubicTo(
+            ctrl.from_x(),
+            ctrl.from_y(),
+            ctrl.to_x(),
+            ctrl.to_y(),
+            to.x(),
+            to.y(),
+        ));
+    }
+
+    fn close(&mut self) {
+        self.commands.push(OutlineCommand::Close);
+    }
+}
@@ -0,0 +1,7 @@
+/// Size and baseline offset of superscript or subscript glyphs in font units.
+/// The offset is relative to the baseline with positive values pointing upwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptMetrics {
+    pub size: i32,
+    pub offset: i32,
+}
@@ -0,0 +1,61 @@
+use document::style::{DistanceExpressionValue, StyleDistance};
+use unit::Distance;
+
+use crate::element::Size;
+use crate::result::LayoutResult;
+
+/// Values that relative distances given in styles are resolved against.
+pub(crate) struct DistanceReference {
+    pub(crate) font_size: Distance,
+    pub(crate) root_font_size: Distance,
+    pub(crate) x_height: Distance,
+    pub(crate) zero_width: Distance,
+
+    /// Size of the containing bounds that percentages refer to.
+    pub(crate) container: Size,
+}
+
+/// The value a percentage refers to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PercentageBase {
+    Width,
+    Height,
+    FontSize,
+}
+
+impl DistanceReference {
+    pub(crate) fn resolve(
+        &self,
+        distance: &StyleDistance,
+        base: PercentageBase,
+    ) -> LayoutResult<Distance> {
+        let distance = match distance {
+            StyleDistance::Absolute(distance) => *distance,
+            StyleDistance::Em(factor) => self.font_size * *factor,
+            StyleDistance::Rem(factor) => self.root_font_size * *factor,
+            StyleDistance::Ex(factor) => self.x_height * *factor,
+            StyleDistance::Ch(factor) => self.zero_width * *factor,
+            StyleDistance::Percent(percentage) => {
+                let base = match base {
+                    PercentageBase::Width => self.container.width,
+                    PercentageBase::Height => self.container.height,
+                    PercentageBase::FontSize => self.font_size,
+                };
+
+                base * (*percentage / 100.0)
+            }
+            StyleDistance::Calc(expression) => {
+                match expression
+                    .evaluate(&|distance: &StyleDistance| self.resolve(distance, base))?
+                {
+                    DistanceExpressionValue::Distance(distance) => distance,
+                    DistanceExpressionValue::Number(_) => {
+                        return Err("calc() must evaluate to a distance".into())
+                    }
+                }
+            }
+        };
+
+        Ok(distance)
+    }
+}
@@ -1,18 +1,24 @@
 use std::collections::HashMap;
 
-use document::structure::DocumentNode;
-use document::style::{FontFamilySource, NodeName, Style, StyleResolvingContext};
+use ::style::resolve_variables;
+use document::structure::{DocumentNode, DocumentNodeValue, NodeId};
+use document::style::{FontFace, FontFamilySource, NodeName, Style, StyleResolvingContext};
 use document::Document;
 use font::{FontId, FontManager, FontStyleSettings, LetterFont};
+use unit::{Distance, DistanceUnit};
 
+use crate::context::distance::{DistanceReference, PercentageBase};
 pub(crate) use crate::context::insets::Insets;
 pub(crate) use crate::context::page_sizing::{OneSizeFitsAllPageSizing, PageSizing};
 pub(crate) use crate::context::style::LayoutStyle;
+use crate::element::content::LayoutElementContent;
 use crate::element::{
-    Bounds, DocumentLayout, ElementId, LayoutConstraints, LayoutElement, Page, Position, Size,
+    Anchor, Bounds, DocumentLayout, ElementId, Heading, LayoutConstraints, LayoutElement, Page,
+    Position, Size,
 };
 use crate::result::LayoutResult;
 
+mod distance;
 mod insets;
 mod page_sizing;
 mod style;
@@ -41,10 +47,25 @@ pub(crate) struct LayoutContext<'a> {
     /// All elements that have been laid out.
     element_lookup: HashMap<ElementId, LayoutElement>,
 
+    /// Locations of named nodes that links may refer to.
+    anchors: HashMap<String, Anchor>,
+
+    /// Names of anchors waiting for the location of the next content to be laid out.
+    pending_anchors: Vec<String>,
+
+    /// Headings laid out so far in document order.
+    headings: Vec<Heading>,
+
+    /// Levels and texts of headings waiting for the location of their first line.
+    pending_headings: Vec<(usize, String)>,
+
     font_manager: FontManager<'a>,
 
     /// Current section level. When not in a section currently this is 0.
     section_level: usize,
+
+    /// Font size of the document root that `rem` distances refer to.
+    root_font_size: Option<Distance>,
 }
 
 impl<'a> LayoutContext<'a> {
@@ -59,8 +80,13 @@ impl<'a> LayoutContext<'a> {
             bounds: Bounds::empty(),
             pages: Vec::new(),
             element_lookup: HashMap::new(),
+            anchors: HashMap::new(),
+            pending_anchors: Vec::new(),
+            headings: Vec::new(),
+            pending_headings: Vec::new(),
             font_manager: FontManager::new(),
             section_level: 0,
+            root_font_size: None,
         };
 
         result.push_page();
@@ -68,8 +94,23 @@ impl<'a> LayoutContext<'a> {
         result
     }
 
-    pub(crate) fn to_layout(self) -> DocumentLayout<'a> {
-        DocumentLayout::new(self.pages, self.element_lookup, self.font_manager)
+    pub(crate) fn register_font_faces(&mut self, font_faces: &[FontFace]) {
+        for font_face in font_faces {
+            self.font_manager.register_font_face(font_face.clone());
+        }
+    }
+
+    pub(crate) fn to_layout(mut self) -> DocumentLayout<'a> {
+        // Anchors without any content following them point to the end of the document
+        self.locate_pending_anchors_and_headings();
+
+        DocumentLayout::new(
+            self.pages,
+            self.element_lookup,
+            self.anchors,
+            self.headings,
+            self.font_manager,
+        )
     }
 
     pub(crate) fn is_stable(&self) -> bool {
@@ -114,9 +155,45 @@ impl<'a> LayoutContext<'a> {
         self.reset_bounds();
     }
 
-    pub(crate) fn register_element(&mut self, element: LayoutElement) {
-        self.current_page().add_element(element.id());
-        self.element_lookup.insert(element.id(), element);
+    /// Add an element to the current page.
+    /// Elements are never removed, so the number of elements registered so far is an unused ID.
+    pub(crate) fn register_element(
+        &mut self,
+        node: NodeId,
+        bounds: Bounds,
+        content: LayoutElementContent,
+    ) {
+        let id = ElementId::new(self.element_lookup.len());
+        self.current_page().add_element(id);
+        self.element_lookup
+            .insert(id, LayoutElement::of(id, node, bounds, content));
+    }
+
+    /// Register an anchor with the given name located at the start of the next content to be laid out.
+    /// The location is not known before the content has been laid out, since the content
+    /// may not fit into the current bounds and thus be moved to the next page.
+    pub(crate) fn register_anchor(&mut self, name: &str) {
+        self.pending_anchors.push(name.to_owned());
+    }
+
+    /// Register a heading with the given text located at the start of its first line.
+    /// Like anchors, the location is not known before the first line has been laid out.
+    pub(crate) fn register_heading(&mut self, text: String) {
+        self.pending_headings.push((self.section_level, text));
+    }
+
+    /// Locate all pending anchors and headings at the start of the current bounds.
+    /// To be called once the bounds of the content following them are final.
+    pub(crate) fn locate_pending_anchors_and_headings(&mut self) {
+        let page_number = self.current_page().number();
+        let location = Anchor::new(page_number, *self.bounds.position());
+
+        for name in self.pending_anchors.drain(..) {
+            self.anchors.insert(name, location);
+        }
+        for (level, text) in self.pending_headings.drain(..) {
+            self.headings.push(Heading::new(level, text, location));
+        }
     }
 
     pub(crate) fn bounds(&self) -> Bounds {
@@ -151,7 +228,7 @@ impl<'a> LayoutContext<'a> {
             FontFamilySource::Type(font_family_type) => {
                 self.font_manager.find_by_type(*font_family_type, style)
             }
-            FontFamilySource::Path(path) => self.font_manager.find_by_path(path.as_str()),
+            FontFamilySource::Path(path) => self.font_manager.find_by_path(path.as_str(), 0),
         }
     }
 
@@ -174,7 +251,17 @@ impl<'a> LayoutContext<'a> {
             let styles = document
                 .styles
                 .resolve(&node_name, class_name, resolving_context);
-            let layout_style = self.apply_to_layout_style(current_style, &styles);
+            let mut layout_style = self.apply_to_layout_style(current_style, &styles, document)?;
+
+            // The link target is passed down to the content of the link like a style
+            if let DocumentNodeValue::Link(target) = node.value() {
+                layout_style.set_link(target.clone());
+            }
+
+            let is_root_node = node.parent.is_none();
+            if is_root_node {
+                self.root_font_size = Some(*layout_style.font_size());
+            }
 
             self.push_style(layout_style);
         }
@@ -191,62 +278,171 @@ impl<'a> LayoutContext<'a> {
     }
 
     fn apply_to_layout_style(
-        &self,
+        &mut self,
         mut layout_style: LayoutStyle,
-        styles: &Vec<&Style>,
-    ) -> LayoutStyle {
+        styles: &[&Style],
+        document: &Document,
+    ) -> LayoutResult<LayoutStyle> {
         // Size, margin and padding are not inherited
         layout_style.set_size(Size::max());
         layout_style.set_margin(Insets::zero());
         layout_style.set_padding(Insets::zero());
 
-        for style in styles {
+        let mut custom_properties = layout_style.custom_properties().clone();
+        let styles = resolve_variables(
+            styles,
+            &mut custom_properties,
+            &document.meta_data.variables,
+        )?;
+        layout_style.set_custom_properties(custom_properties);
+
+        let requires_font_metrics = styles
+            .iter()
+            .filter_map(|style| style.distance())
+            .any(|distance| distance.is_font_metric_relative());
+
+        // Font styles are applied first, since relative distances of all other styles refer to them.
+        // A relative font size itself refers to the font of the parent node.
+        let parent_reference = self.distance_reference(&layout_style, requires_font_metrics);
+        for style in &styles {
+            match style {
+                Style::FontSize(distance) => layout_style
+                    .set_font_size(parent_reference.resolve(distance, PercentageBase::FontSize)?),
+                Style::FontFamily(font_family) => layout_style.set_font_family(font_family.clone()),
+                Style::FontVariationSettings(settings) => {
+                    layout_style.set_font_variation_settings(settings.clone())
+                }
+                Style::FontWeight(weight) => layout_style.set_font_weight(*weight),
+                Style::FontStretch(stretch) => layout_style.set_font_stretch(*stretch),
+                Style::FontStyle(style) => layout_style.set_font_style(*style),
+                _ => {}
+            }
+        }
+
+        // As in CSS percentages of all margins and paddings refer to the width of the container
+        let reference = self.distance_reference(&layout_style, requires_font_metrics);
+        for style in &styles {
             match style {
                 Style::Width(distance) => {
-                    layout_style.set_size(layout_style.size().with_width(*distance))
+                    let width = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_size(layout_style.size().with_width(width))
                 }
                 Style::Height(distance) => {
-                    layout_style.set_size(layout_style.size().with_height(*distance))
+                    let height = reference.resolve(distance, PercentageBase::Height)?;
+                    layout_style.set_size(layout_style.size().with_height(height))
                 }
                 Style::MarginTop(distance) => {
-                    layout_style.set_margin(layout_style.margin().with_top(*distance))
+                    let top = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_margin(layout_style.margin().with_top(top))
                 }
                 Style::MarginRight(distance) => {
-                    layout_style.set_margin(layout_style.margin().with_right(*distance))
+                    let right = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_margin(layout_style.margin().with_right(right))
                 }
                 Style::MarginBottom(distance) => {
-                    layout_style.set_margin(layout_style.margin().with_bottom(*distance))
+                    let bottom = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_margin(layout_style.margin().with_bottom(bottom))
                 }
                 Style::MarginLeft(distance) => {
-                    layout_style.set_margin(layout_style.margin().with_left(*distance))
+                    let left = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_margin(layout_style.margin().with_left(left))
                 }
                 Style::PaddingTop(distance) => {
-                    layout_style.set_padding(layout_style.padding().with_top(*distance))
+                    let top = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_padding(layout_style.padding().with_top(top))
                 }
                 Style::PaddingRight(distance) => {
-                    layout_style.set_padding(layout_style.padding().with_right(*distance))
+                    let right = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_padding(layout_style.padding().with_right(right))
                 }
                 Style::PaddingBottom(distance) => {
-                    layout_style.set_padding(layout_style.padding().with_bottom(*distance))
+                    let bottom = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_padding(layout_style.padding().with_bottom(bottom))
                 }
                 Style::PaddingLeft(distance) => {
-                    layout_style.set_padding(layout_style.padding().with_left(*distance))
+                    let left = reference.resolve(distance, PercentageBase::Width)?;
+                    layout_style.set_padding(layout_style.padding().with_left(left))
                 }
-                Style::FontSize(distance) => layout_style.set_font_size(*distance),
-                Style::FontFamily(font_family) => layout_style.set_font_family(font_family.clone()),
-                Style::FontVariationSettings(settings) => {
-                    layout_style.set_font_variation_settings(settings.clone())
-                }
-                Style::FontWeight(weight) => layout_style.set_font_weight(*weight),
-                Style::FontStretch(stretch) => layout_style.set_font_stretch(*stretch),
-                Style::FontStyle(style) => layout_style.set_font_style(*style),
                 Style::LineHeight(line_height) => layout_style.set_line_height(*line_height),
                 Style::TextAlignment(alignment) => layout_style.set_text_alignment(*alignment),
-                Style::FirstLineIndent(distance) => layout_style.set_first_line_indent(*distance),
+                Style::FirstLineIndent(distance) => layout_style
+                    .set_first_line_indent(reference.resolve(distance, PercentageBase::Width)?),
+                Style::LetterSpacing(distance) => layout_style
+                    .set_letter_spacing(reference.resolve(distance, PercentageBase::FontSize)?),
+                Style::WordSpacing(word_spacing) => layout_style.set_word_spacing(*word_spacing),
+                Style::TextTransform(transform) => layout_style.set_text_transform(*transform),
+                Style::TextPosition(position) => layout_style.set_text_position(*position),
+                Style::WhiteSpace(white_space) => layout_style.set_white_space(*white_space),
+                Style::Color(color) => layout_style.set_color(*color),
+                Style::TextDecoration(decoration) => layout_style.set_text_decoration(*decoration),
+                Style::TextDecorationColor(color) => layout_style.set_text_decoration_color(*color),
+                Style::FontSize(_)
+                | Style::FontFamily(_)
+                | Style::FontVariationSettings(_)
+                | Style::FontWeight(_)
+                | Style::FontStretch(_)
+                | Style::FontStyle(_) => {
+                    // Already applied beforehand
+                }
+                Style::CustomProperty { .. } | Style::Deferred { .. } => {
+                    // Already resolved beforehand
+                }
             };
         }
 
-        layout_style
+        Ok(layout_style)
+    }
+
+    fn distance_reference(
+        &mut self,
+        style: &LayoutStyle,
+        with_font_metrics: bool,
+    ) -> DistanceReference {
+        let font_size = *style.font_size();
+        let (x_height, zero_width) = if with_font_metrics {
+            self.font_metrics(style)
+        } else {
+            (font_size * 0.5, font_size * 0.5)
+        };
+
+        DistanceReference {
+            font_size,
+            root_font_size: self.root_font_size.unwrap_or(font_size),
+            x_height,
+            zero_width,
+            container: *self.bounds.size(),
+        }
+    }
+
+    /// Find the x-height and width of the "0" character of the font used by the given style.
+    /// Both fall back to half of the font size when the font does not provide them.
+    fn font_metrics(&mut self, style: &LayoutStyle) -> (Distance, Distance) {
+        let font_size = *style.font_size();
+        let fallback = font_size * 0.5;
+
+        let font = self
+            .find_font(style.font_family(), Some(style))
+            .and_then(|font_id| self.font_manager.get_font(&font_id));
+
+        match font {
+            Some(font) => {
+                let unit = DistanceUnit::FontUnits {
+                    units_per_em: font.units_per_em(),
+                    font_size: font_size.value(DistanceUnit::Millimeter),
+                };
+                let x_height = font
+                    .x_height()
+                    .map(|value| Distance::new(value as f64, unit))
+                    .unwrap_or(fallback);
+                let zero_width = font
+                    .char_advance('0')
+                    .map(|value| Distance::new(value as f64, unit))
+                    .unwrap_or(fallback);
+
+                (x_height, zero_width)
+            }
+            None => (fallback, fallback),
+        }
     }
 
     fn current_page(&mut self) -> &mut Page {
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
+use document::structure::LinkTarget;
 use document::style::{
-    FontFamilySource, FontStretch, FontStyle, FontVariationSettings, FontWeight, TextAlignment,
+    Color, FontFamilySource, FontStretch, FontStyle, FontVariationSettings, FontWeight,
+    TextAlignment, TextDecoration, TextPosition, TextTransform, WhiteSpace,
 };
 use unit::{Distance, DistanceUnit};
 
@@ -17,9 +21,21 @@ pub(crate) struct LayoutStyle {
     font_weight: FontWeight,
     font_stretch: FontStretch,
     font_style: FontStyle,
+    color: Color,
+    text_position: TextPosition,
     line_height: f64,
     text_alignment: TextAlignment,
     first_line_indent: Distance,
+    letter_spacing: Distance,
+    word_spacing: f64,
+    text_transform: TextTransform,
+    white_space: WhiteSpace,
+    text_decoration: TextDecoration,
+    text_decoration_color: Option<Color>,
+
+    /// Target of the link the styled content belongs to, if any.
+    link: Option<LinkTarget>,
+    custom_properties: HashMap<String, String>,
 }
 
 impl LayoutStyle {
@@ -34,9 +50,19 @@ impl LayoutStyle {
             font_weight: 400.0,
             font_stretch: 1.0,
             font_style: FontStyle::Normal,
+            color: Color::BLACK,
+            text_position: TextPosition::Normal,
             line_height: 1.25,
             text_alignment: TextAlignment::Justify,
             first_line_indent: Distance::zero(),
+            letter_spacing: Distance::zero(),
+            word_spacing: 1.0,
+            text_transform: TextTransform::None,
+            white_space: WhiteSpace::Normal,
+            text_decoration: TextDecoration::None,
+            text_decoration_color: None,
+            link: None,
+            custom_properties: HashMap::new(),
         }
     }
 
@@ -76,6 +102,14 @@ impl LayoutStyle {
         self.font_style
     }
 
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn text_position(&self) -> TextPosition {
+        self.text_position
+    }
+
     pub fn line_height(&self) -> f64 {
         self.line_height
     }
@@ -88,6 +122,39 @@ impl LayoutStyle {
         &self.first_line_indent
     }
 
+    pub fn letter_spacing(&self) -> &Distance {
+        &self.letter_spacing
+    }
+
+    pub fn word_spacing(&self) -> f64 {
+        self.word_spacing
+    }
+
+    pub fn text_transform(&self) -> TextTransform {
+        self.text_transform
+    }
+
+    pub fn white_space(&self) -> WhiteSpace {
+        self.white_space
+    }
+
+    pub fn text_decoration(&self) -> TextDecoration {
+        self.text_decoration
+    }
+
+    /// Color of the text decoration, which defaults to the color of the text.
+    pub fn text_decoration_color(&self) -> Color {
+        self.text_decoration_color.unwrap_or(self.color)
+    }
+
+    pub fn link(&self) -> Option<&LinkTarget> {
+        self.link.as_ref()
+    }
+
+    pub fn custom_properties(&self) -> &HashMap<String, String> {
+        &self.custom_properties
+    }
+
     pub fn set_size(&mut self, size: Size) {
         self.size = size;
     }
@@ -134,6 +201,14 @@ impl LayoutStyle {
         self.font_style = style;
     }
 
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn set_text_position(&mut self, text_position: TextPosition) {
+        self.text_position = text_position;
+    }
+
     pub fn set_line_height(&mut self, line_height: f64) {
         self.line_height = line_height;
     }
@@ -145,4 +220,36 @@ impl LayoutStyle {
     pub fn set_first_line_indent(&mut self, indent: Distance) {
         self.first_line_indent = indent;
     }
+
+    pub fn set_letter_spacing(&mut self, letter_spacing: Distance) {
+        self.letter_spacing = letter_spacing;
+    }
+
+    pub fn set_word_spacing(&mut self, word_spacing: f64) {
+        self.word_spacing = word_spacing;
+    }
+
+    pub fn set_text_transform(&mut self, text_transform: TextTransform) {
+        self.text_transform = text_transform;
+    }
+
+    pub fn set_white_space(&mut self, white_space: WhiteSpace) {
+        self.white_space = white_space;
+    }
+
+    pub fn set_text_decoration(&mut self, text_decoration: TextDecoration) {
+        self.text_decoration = text_decoration;
+    }
+
+    pub fn set_text_decoration_color(&mut self, text_decoration_color: Color) {
+        self.text_decoration_color = Some(text_decoration_color);
+    }
+
+    pub fn set_link(&mut self, link: LinkTarget) {
+        self.link = Some(link);
+    }
+
+    pub fn set_custom_properties(&mut self, custom_properties: HashMap<String, String>) {
+        self.custom_properties = custom_properties;
+    }
 }
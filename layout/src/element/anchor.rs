@@ -0,0 +1,25 @@
+use crate::element::Position;
+
+/// Location of a named node within the document layout that links may refer to.
+#[derive(Debug, Copy, Clone)]
+pub struct Anchor {
+    page_number: usize,
+    position: Position,
+}
+
+impl Anchor {
+    pub fn new(page_number: usize, position: Position) -> Self {
+        Self {
+            page_number,
+            position,
+        }
+    }
+
+    pub fn page_number(&self) -> usize {
+        self.page_number
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+}
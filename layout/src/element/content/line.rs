@@ -0,0 +1,9 @@
+use document::style::Color;
+use unit::Distance;
+
+/// A straight horizontal line (for example an underline) drawn along the vertical center of the elements bounds.
+#[derive(Debug)]
+pub struct LineContent {
+    pub thickness: Distance,
+    pub color: Color,
+}
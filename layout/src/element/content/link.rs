@@ -0,0 +1,8 @@
+use document::structure::LinkTarget;
+
+/// An area of a link (for example all glyphs of the link on a line) that can be clicked to follow the link.
+/// Links broken across lines or pages consist of multiple areas.
+#[derive(Debug)]
+pub struct LinkContent {
+    pub target: LinkTarget,
+}
@@ -1,10 +1,16 @@
+pub use line::LineContent;
+pub use link::LinkContent;
 pub use text_slice::TextSliceContent;
 
+mod line;
+mod link;
 mod text_slice;
 
 #[derive(Debug)]
 pub enum LayoutElementContent {
     Page,
     TextSlice(TextSliceContent),
+    Line(LineContent),
+    Link(LinkContent),
     Image,
 }
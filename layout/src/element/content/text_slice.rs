@@ -1,3 +1,4 @@
+use document::style::Color;
 use font::{FontId, FontVariationId};
 use typeset::glyph_shaping::GlyphDetails;
 use unit::Distance;
@@ -7,5 +8,13 @@ pub struct TextSliceContent {
     pub font: FontId,
     pub font_variation: FontVariationId,
     pub font_size: Distance,
+
+    /// Shift of the baseline with positive values pointing upwards, for example for superscripts.
+    pub vertical_offset: Distance,
+    pub color: Color,
+
+    /// Text of the slice as written in the document, which may differ from the text of the glyphs,
+    /// for example it is empty for hyphens inserted at line breaks.
+    pub text: String,
     pub glyphs: Vec<GlyphDetails>,
 }
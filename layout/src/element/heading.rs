@@ -0,0 +1,34 @@
+use crate::element::Anchor;
+
+/// A heading of the document along with its location in the layout.
+/// Used for navigation aids like the outline of a PDF.
+#[derive(Debug, Clone)]
+pub struct Heading {
+    level: usize,
+    text: String,
+    location: Anchor,
+}
+
+impl Heading {
+    pub fn new(level: usize, text: String, location: Anchor) -> Self {
+        Self {
+            level,
+            text,
+            location,
+        }
+    }
+
+    /// Level of the section the heading belongs to, starting with 1 for top-level sections.
+    /// Headings outside of any section have level 0.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn location(&self) -> &Anchor {
+        &self.location
+    }
+}
@@ -1,14 +1,16 @@
-use uuid::Uuid;
-
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+/// ID of a layout element, which is the number of elements laid out before it.
+/// Thus laying out the same document again results in the same IDs.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct ElementId {
-    value: Uuid,
+    value: usize,
 }
 
 impl ElementId {
-    pub fn new() -> Self {
-        Self {
-            value: Uuid::new_v4(),
-        }
+    pub fn new(value: usize) -> Self {
+        Self { value }
+    }
+
+    pub fn value(&self) -> usize {
+        self.value
     }
 }
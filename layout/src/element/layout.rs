@@ -2,11 +2,13 @@ use std::collections::HashMap;
 
 use font::{FontId, FontManager, LetterFont};
 
-use super::{ElementId, LayoutElement, Page};
+use super::{Anchor, ElementId, Heading, LayoutElement, Page};
 
 pub struct DocumentLayout<'a> {
     pages: Vec<Page>,
     element_lookup: HashMap<ElementId, LayoutElement>,
+    anchors: HashMap<String, Anchor>,
+    headings: Vec<Heading>,
     pub font_manager: FontManager<'a>,
 }
 
@@ -14,11 +16,15 @@ impl<'a> DocumentLayout<'a> {
     pub fn new(
         pages: Vec<Page>,
         element_lookup: HashMap<ElementId, LayoutElement>,
+        anchors: HashMap<String, Anchor>,
+        headings: Vec<Heading>,
         font_manager: FontManager<'a>,
     ) -> Self {
         Self {
             pages,
             element_lookup,
+            anchors,
+            headings,
             font_manager,
         }
     }
@@ -27,6 +33,16 @@ impl<'a> DocumentLayout<'a> {
         self.element_lookup.get(&id)
     }
 
+    /// Find the location of the node with the given anchor name (the value of its `id` attribute).
+    pub fn anchor(&self, name: &str) -> Option<&Anchor> {
+        self.anchors.get(name)
+    }
+
+    /// All headings of the document in document order.
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
     pub fn pages(&self) -> &[Page] {
         &self.pages
     }
@@ -1,16 +1,22 @@
+pub use anchor::Anchor;
 pub use bounds::Bounds;
 pub use constraints::LayoutConstraints;
+pub use heading::Heading;
 pub use id::ElementId;
 pub use layout::DocumentLayout;
 pub use page::Page;
 pub use position::Position;
 pub use size::Size;
 
+use document::structure::NodeId;
+
 use crate::element::content::LayoutElementContent;
 
+mod anchor;
 mod bounds;
 mod constraints;
 pub mod content;
+mod heading;
 mod id;
 mod layout;
 mod page;
@@ -20,22 +26,18 @@ mod size;
 #[derive(Debug)]
 pub struct LayoutElement {
     id: ElementId,
+
+    /// Node of the document structure the element has been laid out for.
+    node: NodeId,
     bounds: Bounds,
     content: LayoutElementContent,
 }
 
 impl LayoutElement {
-    pub fn of(id: ElementId, bounds: Bounds, content: LayoutElementContent) -> Self {
+    pub fn of(id: ElementId, node: NodeId, bounds: Bounds, content: LayoutElementContent) -> Self {
         Self {
             id,
-            bounds,
-            content,
-        }
-    }
-
-    pub fn new(bounds: Bounds, content: LayoutElementContent) -> Self {
-        Self {
-            id: ElementId::new(),
+            node,
             bounds,
             content,
         }
@@ -45,6 +47,10 @@ impl LayoutElement {
         self.id
     }
 
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
     pub fn bounds(&self) -> &Bounds {
         &self.bounds
     }
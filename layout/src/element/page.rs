@@ -8,6 +8,8 @@ use super::ElementId;
 pub struct Page {
     number: usize,
     constraints: LayoutConstraints,
+
+    /// Elements on the page in the order they have been laid out.
     elements: Vec<ElementId>,
 }
 
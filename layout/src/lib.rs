@@ -1,11 +1,14 @@
 extern crate core;
 
+use std::collections::HashMap;
+
 use document::structure::DocumentNodeValue::Section;
 use document::structure::{DocumentNode, DocumentNodeValue};
-use document::style::Style;
+use document::style::{Style, StyleDistance};
 use document::Document;
+use style::resolve_variables;
 use unit::{Distance, DistanceUnit};
-use DocumentNodeValue::{Heading, ListItem, Paragraph, Text};
+use DocumentNodeValue::{CodeBlock, Heading, ListItem, Paragraph, Text};
 
 use crate::context::{LayoutContext, OneSizeFitsAllPageSizing, PageSizing};
 use crate::element::{DocumentLayout, LayoutConstraints, Size};
@@ -49,8 +52,9 @@ fn layout_pass<'a>(
     last_pass_layout: Option<DocumentLayout<'a>>,
     _options: &LayoutOptions,
 ) -> LayoutResult<LayoutPassResult<'a>> {
-    let page_sizing = create_page_sizing_behavior(document);
+    let page_sizing = create_page_sizing_behavior(document)?;
     let mut ctx = LayoutContext::new(last_pass_layout, page_sizing);
+    ctx.register_font_faces(document.styles.font_faces());
 
     process_node(&document.structure.root(), document, &mut ctx)?;
 
@@ -68,6 +72,13 @@ fn process_node(
     let structure = &document.structure;
 
     ctx.push_node_styles(node, document)?;
+    if let Some(anchor_name) = node.anchor_name() {
+        ctx.register_anchor(anchor_name);
+    }
+    if let Heading = node.value {
+        let text = structure.text_content(node.id);
+        ctx.register_heading(text.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
     {
         let rule = map_node_to_rule(node);
         let is_consumed = if let Some(rule) = rule.as_ref() {
@@ -97,23 +108,29 @@ fn process_node(
 
 fn map_node_to_rule(node: &DocumentNode) -> Option<Box<dyn LayoutRule>> {
     match node.value {
-        Text(_) | Paragraph | Heading | ListItem => Some(Box::new(InlineLayoutRule::new())),
+        Text(_) | Paragraph | Heading | ListItem | CodeBlock => {
+            Some(Box::new(InlineLayoutRule::new()))
+        }
         Section => Some(Box::new(SectionLayoutRule::new())),
         _ => None,
     }
 }
 
-fn create_page_sizing_behavior(document: &Document) -> Box<dyn PageSizing> {
+fn create_page_sizing_behavior(document: &Document) -> LayoutResult<Box<dyn PageSizing>> {
     // TODO Replace `get_root_layout_constraints` by really checking for page sizing rules in the style sheet
     // TODO for now we just check the size defined on the root (document) style
-    let layout_constraints = get_root_layout_constraints(document);
+    let layout_constraints = get_root_layout_constraints(document)?;
 
-    Box::new(OneSizeFitsAllPageSizing::new(layout_constraints))
+    Ok(Box::new(OneSizeFitsAllPageSizing::new(layout_constraints)))
 }
 
-fn get_root_layout_constraints(document: &Document) -> LayoutConstraints {
+fn get_root_layout_constraints(document: &Document) -> LayoutResult<LayoutConstraints> {
     let document_styles = &document.styles;
-    let styles = document_styles.root_style();
+    let styles = resolve_variables(
+        &document_styles.root_style(),
+        &mut HashMap::new(),
+        &document.meta_data.variables,
+    )?;
     let mut width = Distance::new(210.0, DistanceUnit::Millimeter);
     let mut height = Distance::new(297.0, DistanceUnit::Millimeter);
 
@@ -124,14 +141,30 @@ fn get_root_layout_constraints(document: &Document) -> LayoutConstraints {
 
     for style in styles {
         match style {
-            Style::Width(distance) => width = *distance,
-            Style::Height(distance) => height = *distance,
+            Style::Width(distance) => width = to_absolute_page_distance(&distance)?,
+            Style::Height(distance) => height = to_absolute_page_distance(&distance)?,
             _ => {}
         }
     }
 
     let size = Size::new(width, height);
-    LayoutConstraints::new(size, margin_top, margin_right, margin_bottom, margin_left)
+    Ok(LayoutConstraints::new(
+        size,
+        margin_top,
+        margin_right,
+        margin_bottom,
+        margin_left,
+    ))
+}
+
+fn to_absolute_page_distance(distance: &StyleDistance) -> LayoutResult<Distance> {
+    distance.as_absolute().ok_or(
+        format!(
+            "The page size must be given in absolute units, got {:?}",
+            distance
+        )
+        .into(),
+    )
 }
 
 fn finalize_layout(mut layout: DocumentLayout) -> DocumentLayout {
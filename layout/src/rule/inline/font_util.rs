@@ -1,5 +1,6 @@
-use document::style::FontVariationSettings;
+use document::style::{FontVariationSettings, TextPosition, TextTransform};
 use font::{FontId, FontVariationId, LetterFont, LetterFontVariation};
+use typeset::glyph_shaping::{ScriptPosition, ShapingOptions};
 use unit::Distance;
 
 use crate::context::{LayoutContext, LayoutStyle};
@@ -9,14 +10,21 @@ pub(crate) struct FontContext {
     pub(crate) font_id: FontId,
     pub(crate) _font_variation_id: FontVariationId,
     pub(crate) font_size: Distance,
+    pub(crate) shaping_options: ShapingOptions,
 }
 
 impl FontContext {
-    pub fn new(font_id: FontId, font_variation_id: FontVariationId, font_size: Distance) -> Self {
+    pub fn new(
+        font_id: FontId,
+        font_variation_id: FontVariationId,
+        font_size: Distance,
+        shaping_options: ShapingOptions,
+    ) -> Self {
         Self {
             font_id,
             _font_variation_id: font_variation_id,
             font_size,
+            shaping_options,
         }
     }
 }
@@ -36,7 +44,22 @@ pub(crate) fn setup_font(
     let font = ctx.get_font_mut(&font_id);
     let font_variation_id = initialize_font_variations(font, &font_variation_settings);
 
-    Ok(FontContext::new(font_id, font_variation_id, font_size))
+    let shaping_options = ShapingOptions {
+        letter_spacing: *style.letter_spacing(),
+        small_caps: style.text_transform() == TextTransform::SmallCaps,
+        script_position: match style.text_position() {
+            TextPosition::Normal => None,
+            TextPosition::Superscript => Some(ScriptPosition::Superscript),
+            TextPosition::Subscript => Some(ScriptPosition::Subscript),
+        },
+    };
+
+    Ok(FontContext::new(
+        font_id,
+        font_variation_id,
+        font_size,
+        shaping_options,
+    ))
 }
 
 fn initialize_font_variations(
@@ -7,15 +7,25 @@ use crate::rule::inline::item::box_content::BoxContent;
 #[derive(Debug)]
 pub(crate) struct BoxItem {
     width: Distance,
+
+    /// Shift of the baseline of the content with positive values pointing upwards.
+    vertical_offset: Distance,
     content: BoxContent,
     node: NodeId,
     style: LayoutStyle,
 }
 
 impl BoxItem {
-    pub fn new(width: Distance, content: BoxContent, node: NodeId, style: LayoutStyle) -> Self {
+    pub fn new(
+        width: Distance,
+        vertical_offset: Distance,
+        content: BoxContent,
+        node: NodeId,
+        style: LayoutStyle,
+    ) -> Self {
         Self {
             width,
+            vertical_offset,
             content,
             node,
             style,
@@ -26,6 +36,10 @@ impl BoxItem {
         self.width
     }
 
+    pub fn vertical_offset(&self) -> Distance {
+        self.vertical_offset
+    }
+
     pub fn content(&self) -> &BoxContent {
         &self.content
     }
@@ -23,6 +23,7 @@ impl LineItem {
 pub(crate) struct LineItemContent {
     pub(crate) kind: LineItemContentKind,
     pub(crate) width: Distance,
+    pub(crate) vertical_offset: Distance,
     pub(crate) node: NodeId,
     pub(crate) style: LayoutStyle,
 }
@@ -30,6 +31,9 @@ pub(crate) struct LineItemContent {
 #[derive(Debug)]
 pub(crate) enum LineItemContentKind {
     Text(String),
+
+    /// Hyphen inserted where a word is broken at the end of a line.
+    Hyphen,
 }
 
 pub(crate) struct Line {
@@ -124,6 +128,7 @@ pub(crate) fn break_into_lines(
                         LineItemContent {
                             kind: content,
                             width: box_item.width(),
+                            vertical_offset: box_item.vertical_offset(),
                             node: box_item.node(),
                             style: box_item.style().clone(),
                         }
@@ -149,8 +154,9 @@ pub(crate) fn break_into_lines(
                         let last_item = line_items.last_mut().unwrap();
                         let last_part = last_item.parts.last().unwrap();
                         last_item.parts.push(LineItemContent {
-                            kind: LineItemContentKind::Text("-".to_owned()),
+                            kind: LineItemContentKind::Hyphen,
                             width: item.width(),
+                            vertical_offset: last_part.vertical_offset,
                             node: last_part.node,
                             style: last_part.style.clone(),
                         });
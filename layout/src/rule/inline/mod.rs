@@ -1,14 +1,14 @@
-use document::structure::DocumentNode;
-use document::style::TextAlignment;
+use document::structure::{DocumentNode, LinkTarget, NodeId};
+use document::style::{TextAlignment, TextDecoration, WhiteSpace};
 use document::Document;
-use font::LetterFont;
+use font::{LetterFont, LineMetrics};
 use typeset::glyph_shaping::{shape_text, GlyphDetails};
 use unit::{Distance, DistanceUnit, UnitValue};
 use DistanceUnit::Millimeter;
 
 use crate::context::{LayoutContext, LayoutStyle};
-use crate::element::content::{LayoutElementContent, TextSliceContent};
-use crate::element::{Bounds, LayoutElement, Position, Size};
+use crate::element::content::{LayoutElementContent, LineContent, LinkContent, TextSliceContent};
+use crate::element::{Bounds, Position, Size};
 use crate::result::LayoutResult;
 use crate::rule::inline::line_breaker::{Line, LineItem, LineItemContentKind, Lines};
 use crate::rule::LayoutRule;
@@ -64,6 +64,7 @@ fn layout_lines(lines: Lines, ctx: &mut LayoutContext) -> LayoutResult<()> {
     if line_height > bounds.size().height {
         bounds = ctx.choose_next_bounds();
     }
+    ctx.locate_pending_anchors_and_headings();
 
     let mut position_ctx = PositionContext {
         line_height,
@@ -119,6 +120,16 @@ fn layout_line(
     ctx: &mut LayoutContext,
 ) -> LayoutResult<()> {
     let item_count_in_line = line.items.len();
+    let continued_decorations: Vec<bool> = line
+        .items
+        .windows(2)
+        .map(|items| is_decoration_continued(&items[0], &items[1]))
+        .collect();
+    let continued_links: Vec<bool> = line
+        .items
+        .windows(2)
+        .map(|items| is_link_continued(&items[0], &items[1]))
+        .collect();
 
     position_ctx.x_offset += alignment.indent;
 
@@ -126,6 +137,11 @@ fn layout_line(
         let is_last_item_on_line = item_index == item_count_in_line - 1;
         let flags = ItemLayoutFlags {
             is_last_item_on_line,
+            is_decoration_continued: continued_decorations
+                .get(item_index)
+                .copied()
+                .unwrap_or(false),
+            is_link_continued: continued_links.get(item_index).copied().unwrap_or(false),
         };
 
         layout_item_on_line(item, flags, position_ctx, &alignment, ctx)?;
@@ -134,8 +150,33 @@ fn layout_line(
     Ok(())
 }
 
+/// Whether the decoration line (for example an underline) of the given item
+/// continues across the white space to the next item on the same line.
+fn is_decoration_continued(item: &LineItem, next_item: &LineItem) -> bool {
+    match (item.parts.last(), next_item.parts.first()) {
+        (Some(last), Some(first)) => {
+            last.style.text_decoration() != TextDecoration::None
+                && last.style.text_decoration() == first.style.text_decoration()
+                && last.style.text_decoration_color() == first.style.text_decoration_color()
+        }
+        _ => false,
+    }
+}
+
+/// Whether the link of the given item continues across the white space to the next item on the same line.
+fn is_link_continued(item: &LineItem, next_item: &LineItem) -> bool {
+    match (item.parts.last(), next_item.parts.first()) {
+        (Some(last), Some(first)) => {
+            last.style.link().is_some() && last.style.link() == first.style.link()
+        }
+        _ => false,
+    }
+}
+
 struct ItemLayoutFlags {
     is_last_item_on_line: bool,
+    is_decoration_continued: bool,
+    is_link_continued: bool,
 }
 
 fn layout_item_on_line(
@@ -146,43 +187,122 @@ fn layout_item_on_line(
     ctx: &mut LayoutContext,
 ) -> LayoutResult<()> {
     let mut elements = Vec::new();
+    let mut decoration_lines = Vec::new();
+    let mut link_areas: Vec<(Position, Size, LinkTarget, NodeId)> = Vec::new();
+    let mut is_previous_part_linked = false;
     for part in item.parts {
         let font_ctx = font_util::setup_font(ctx, Some(&part.style))?;
         let font = ctx.get_font_mut(&font_ctx.font_id);
 
-        match part.kind {
-            LineItemContentKind::Text(text) => {
-                let result = shape_text(&text, font_ctx.font_size, font)?;
-
-                mark_codepoints_as_used(font, &result.glyphs);
-
-                let element = {
-                    let position = Position::relative_to(
-                        &position_ctx.bounds.position(),
-                        position_ctx.x_offset,
-                        position_ctx.y_offset,
-                    );
-                    let size = Size::new(result.width, position_ctx.line_height);
-                    let bounds = Bounds::new(position, size);
-
-                    let content = LayoutElementContent::TextSlice(TextSliceContent {
-                        font: font_ctx.font_id,
-                        font_variation: font_ctx._font_variation_id,
-                        font_size: font_ctx.font_size,
-                        glyphs: result.glyphs,
-                    });
-
-                    LayoutElement::new(bounds, content)
-                };
-                elements.push(element);
-
-                position_ctx.x_offset += result.width;
+        let (text, is_hyphen) = match part.kind {
+            LineItemContentKind::Text(text) => (text, false),
+            LineItemContentKind::Hyphen => ("-".to_owned(), true),
+        };
+
+        let result = shape_text(&text, font_ctx.font_size, font, &font_ctx.shaping_options)?;
+
+        let mut run_x_offset = position_ctx.x_offset;
+        for run in result.runs {
+            mark_codepoints_as_used(font, &run.glyphs);
+
+            // Glyphs rendered with a smaller font size (for example synthesized superscripts)
+            // are moved down to keep sharing the baseline with the rest of the line
+            let position = Position::relative_to(
+                &position_ctx.bounds.position(),
+                run_x_offset,
+                position_ctx.y_offset + font_ctx.font_size - run.font_size,
+            );
+            let size = Size::new(run.width, position_ctx.line_height);
+            let bounds = Bounds::new(position, size);
+            run_x_offset += run.width;
+
+            let content = LayoutElementContent::TextSlice(TextSliceContent {
+                font: font_ctx.font_id,
+                font_variation: font_ctx._font_variation_id,
+                font_size: run.font_size,
+                vertical_offset: part.vertical_offset,
+                color: part.style.color(),
+                text: if is_hyphen { String::new() } else { run.text },
+                glyphs: run.glyphs,
+            });
+
+            elements.push((part.node, bounds, content));
+        }
+
+        if let Some(metrics) = decoration_metrics(font, part.style.text_decoration()) {
+            let unit = DistanceUnit::FontUnits {
+                units_per_em: font.units_per_em(),
+                font_size: font_ctx.font_size.value(Millimeter),
+            };
+            let thickness = Distance::new(metrics.thickness as f64, unit);
+            let baseline = position_ctx.y_offset + font_ctx.font_size;
+            let position = Position::relative_to(
+                position_ctx.bounds.position(),
+                position_ctx.x_offset,
+                baseline - Distance::new(metrics.position as f64, unit) - thickness / 2.0,
+            );
+            let content = LineContent {
+                thickness,
+                color: part.style.text_decoration_color(),
+            };
+
+            decoration_lines.push((
+                position,
+                Size::new(result.width, thickness),
+                content,
+                part.node,
+            ));
+        }
+
+        // Consecutive parts of the same link share a single area
+        match (part.style.link(), link_areas.last_mut()) {
+            (Some(target), Some((_, size, area_target, _)))
+                if is_previous_part_linked && target == area_target =>
+            {
+                *size = size.with_width(size.width + result.width);
+            }
+            (Some(target), _) => {
+                let position = Position::relative_to(
+                    position_ctx.bounds.position(),
+                    position_ctx.x_offset,
+                    position_ctx.y_offset,
+                );
+                let size = Size::new(result.width, position_ctx.line_height);
+                link_areas.push((position, size, target.clone(), part.node));
             }
+            (None, _) => {}
+        }
+        is_previous_part_linked = part.style.link().is_some();
+
+        position_ctx.x_offset += result.width;
+    }
+
+    if flags.is_decoration_continued {
+        if let Some((_, size, _, _)) = decoration_lines.last_mut() {
+            *size = size.with_width(size.width + alignment.white_space_width);
+        }
+    }
+
+    if flags.is_link_continued {
+        if let Some((_, size, _, _)) = link_areas.last_mut() {
+            *size = size.with_width(size.width + alignment.white_space_width);
         }
     }
 
-    for element in elements {
-        ctx.register_element(element);
+    for (node, bounds, content) in elements {
+        ctx.register_element(node, bounds, content);
+    }
+    for (position, size, content, node) in decoration_lines {
+        let bounds = Bounds::new(position, size);
+        ctx.register_element(node, bounds, LayoutElementContent::Line(content));
+    }
+    for (position, size, target, node) in link_areas {
+        let bounds = Bounds::new(position, size);
+        ctx.register_element(
+            node,
+            bounds,
+            LayoutElementContent::Link(LinkContent { target }),
+        );
     }
 
     if !flags.is_last_item_on_line {
@@ -205,7 +325,11 @@ struct Alignment {
 }
 
 fn align_line(line: &Line, is_last_line: bool, style: &LayoutStyle) -> Alignment {
-    let text_alignment = style.text_alignment();
+    // Preformatted text is never justified, since its white space must be kept as is
+    let text_alignment = match (style.white_space(), style.text_alignment()) {
+        (WhiteSpace::Pre | WhiteSpace::PreWrap, TextAlignment::Justify) => TextAlignment::Left,
+        (_, text_alignment) => text_alignment,
+    };
 
     let white_space_count_in_line = line.white_spaces();
     let min_width = line.min_width();
@@ -259,9 +383,157 @@ fn align_line(line: &Line, is_last_line: bool, style: &LayoutStyle) -> Alignment
     };
 }
 
+fn decoration_metrics(font: &LetterFont, decoration: TextDecoration) -> Option<LineMetrics> {
+    match decoration {
+        TextDecoration::None => None,
+        TextDecoration::Underline => Some(font.underline_metrics()),
+        TextDecoration::StrikeThrough => Some(font.strike_through_metrics()),
+        TextDecoration::Overline => Some(font.overline_metrics()),
+    }
+}
+
 fn mark_codepoints_as_used(font: &mut LetterFont, glyphs: &Vec<GlyphDetails>) {
     for glyph in glyphs {
         let codepoint = glyph.codepoint;
         font.mark_codepoint_as_used(codepoint);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::context::OneSizeFitsAllPageSizing;
+    use crate::element::LayoutConstraints;
+    use crate::rule::inline::line_breaker::LineItemContent;
+
+    use super::*;
+
+    fn context<'a>() -> LayoutContext<'a> {
+        let a4 = Size::new(
+            Distance::new(210.0, Millimeter),
+            Distance::new(297.0, Millimeter),
+        );
+        let margin = Distance::new(20.0, Millimeter);
+        let constraints = LayoutConstraints::new(a4, margin, margin, margin, margin);
+
+        LayoutContext::new(None, Box::new(OneSizeFitsAllPageSizing::new(constraints)))
+    }
+
+    fn line(text: &str) -> Line {
+        let part = LineItemContent {
+            kind: LineItemContentKind::Text(text.to_owned()),
+            width: Distance::zero(),
+            vertical_offset: Distance::zero(),
+            node: 1,
+            style: LayoutStyle::new(),
+        };
+
+        Line {
+            items: vec![LineItem { parts: vec![part] }],
+            indent: Distance::zero(),
+            width: Distance::zero(),
+            white_space_width: Distance::zero(),
+        }
+    }
+
+    /// Shrink the current bounds to the given height, as if the page was filled up to there.
+    fn leave_height(ctx: &mut LayoutContext, height: Distance) {
+        let bounds = ctx.bounds();
+        let remaining = Bounds::new(*bounds.position(), bounds.size().with_height(height));
+        ctx.set_bounds(remaining);
+    }
+
+    #[test]
+    fn should_locate_anchor_at_first_line() {
+        // Given: An anchor registered before a paragraph fitting on the current page
+        let mut ctx = context();
+        let start = *ctx.bounds().position();
+        ctx.register_anchor("intro");
+
+        // When: Laying out the lines of the paragraph
+        layout_lines(vec![line("Intro")], &mut ctx).unwrap();
+
+        // Then: The anchor is located at the start of the first line
+        let layout = ctx.to_layout();
+        let anchor = layout.anchor("intro").unwrap();
+        assert_eq!(anchor.page_number(), 1);
+        assert_eq!(anchor.position().y(), start.y());
+    }
+
+    #[test]
+    fn should_locate_anchor_on_next_page_when_first_line_does_not_fit() {
+        // Given: An anchor registered before a paragraph at the very bottom of the page
+        let mut ctx = context();
+        let page_start = *ctx.bounds().position();
+        leave_height(&mut ctx, Distance::new(1.0, Millimeter));
+        ctx.register_anchor("intro");
+
+        // When: Laying out the lines of the paragraph
+        layout_lines(vec![line("Intro")], &mut ctx).unwrap();
+
+        // Then: The anchor is located on the next page the paragraph has been moved to
+        let layout = ctx.to_layout();
+        let anchor = layout.anchor("intro").unwrap();
+        assert_eq!(anchor.page_number(), 2);
+        assert_eq!(anchor.position().y(), page_start.y());
+    }
+
+    #[test]
+    fn should_locate_heading_on_next_page_when_first_line_does_not_fit() {
+        // Given: A heading registered at the very bottom of the page
+        let mut ctx = context();
+        let page_start = *ctx.bounds().position();
+        leave_height(&mut ctx, Distance::new(1.0, Millimeter));
+        ctx.register_heading("Introduction".to_owned());
+
+        // When: Laying out the lines of the heading
+        layout_lines(vec![line("Introduction")], &mut ctx).unwrap();
+
+        // Then: The heading is located on the next page the heading has been moved to
+        let layout = ctx.to_layout();
+        let heading = &layout.headings()[0];
+        assert_eq!(heading.text(), "Introduction");
+        assert_eq!(heading.location().page_number(), 2);
+        assert_eq!(heading.location().position().y(), page_start.y());
+    }
+
+    /// Lay out the lines on a fresh context and describe each page by the IDs, nodes and positions of its elements.
+    fn layout_and_describe(texts: &[&str]) -> Vec<Vec<(usize, NodeId, f64)>> {
+        let mut ctx = context();
+        let lines = texts.iter().map(|text| line(text)).collect();
+        layout_lines(lines, &mut ctx).unwrap();
+
+        let layout = ctx.to_layout();
+        layout
+            .pages()
+            .iter()
+            .map(|page| {
+                page.elements()
+                    .iter()
+                    .map(|id| {
+                        let element = layout.element(id).unwrap();
+                        let y = element.bounds().position().y().value(Millimeter);
+                        (id.value(), element.node(), y)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_assign_same_element_ids_in_each_layout_run() {
+        // Given: Enough lines to fill more than one page
+        let texts = vec!["Line"; 200];
+
+        // When: Laying out the same lines twice
+        let first = layout_and_describe(&texts);
+        let second = layout_and_describe(&texts);
+
+        // Then: Both layouts have the same elements with the same IDs in the same order
+        assert!(first.len() > 1);
+        assert_eq!(first, second);
+
+        // And: The IDs are numbered in the order the elements were laid out
+        let ids: Vec<usize> = first.iter().flatten().map(|(id, _, _)| *id).collect();
+        assert_eq!(ids, (0..texts.len()).collect::<Vec<usize>>());
+    }
+}
@@ -4,10 +4,13 @@
 use hypher::{hyphenate, Lang};
 
 use document::structure::{DocumentNode, DocumentNodeValue, NodeId};
+use document::style::{TextTransform, WhiteSpace};
 use document::Document;
-use typeset::glyph_shaping::shape_text;
+use typeset::glyph_shaping::{shape_text, TextShaperResult};
 use unit::{Distance, DistanceUnit};
-use DocumentNodeValue::{Bold, Italic, Text};
+use DocumentNodeValue::{
+    Bold, Code, CodeToken, Italic, Link, StrikeThrough, Subscript, Superscript, Text, Underline,
+};
 
 use crate::context::LayoutContext;
 use crate::result::LayoutResult;
@@ -17,6 +20,7 @@ use crate::rule::inline::item::{BoxContent, BoxItem, GlueItem, Item, PenaltyItem
 
 const HYPHEN_PENALTY: i32 = 50;
 const INFINITE_PENALTY: i32 = 10000;
+const TAB_WIDTH: usize = 4;
 
 pub(crate) fn to_box_glue_model(
     node: &DocumentNode,
@@ -24,19 +28,40 @@ pub(crate) fn to_box_glue_model(
     ctx: &mut LayoutContext,
 ) -> LayoutResult<Vec<Item>> {
     let mut items = Vec::new();
+    let mut is_word_start = true;
 
     for child in node.children() {
         if let Some(child_node) = document.structure.get_node(*child) {
-            process_node(child_node, document, ctx, &mut items)?;
+            process_node(child_node, document, ctx, &mut is_word_start, &mut items)?;
         }
     }
 
+    if ctx.current_style().white_space() != WhiteSpace::Normal {
+        trim_trailing_white_space(&mut items);
+    }
     finalize_paragraph(&mut items);
 
     Ok(items)
 }
 
+/// Remove the boxes containing only white space following the last forced line break,
+/// which usually stem from the indentation of the closing tag of a preformatted block.
+fn trim_trailing_white_space(result: &mut Vec<Item>) {
+    while let Some(Item::Box(box_item)) = result.last() {
+        let BoxContent::Text(text) = box_item.content();
+        if !text.trim().is_empty() {
+            break;
+        }
+
+        result.pop();
+    }
+}
+
 fn finalize_paragraph(result: &mut Vec<Item>) {
+    push_forced_line_break(result);
+}
+
+fn push_forced_line_break(result: &mut Vec<Item>) {
     result.push(Item::Glue(GlueItem::new(
         Distance::zero(),
         Distance::new(INFINITE_PENALTY as f64, DistanceUnit::Meter),
@@ -49,19 +74,26 @@ fn finalize_paragraph(result: &mut Vec<Item>) {
     )));
 }
 
+/// Whether the next text starts a new word is carried across the text nodes of the paragraph,
+/// as words may continue in a sibling node (for example `foo<b>bar</b>`).
 fn process_node(
     node: &DocumentNode,
     document: &Document,
     ctx: &mut LayoutContext,
+    is_word_start: &mut bool,
     result: &mut Vec<Item>,
 ) -> LayoutResult<()> {
     ctx.push_node_styles(node, document)?;
+    if let Some(anchor_name) = node.anchor_name() {
+        // Inline nodes are not positioned yet, thus links refer to the start of the paragraph instead
+        ctx.register_anchor(anchor_name);
+    }
     {
-        let is_consumed = map_node_to_item(node, document, ctx, result)?;
+        let is_consumed = map_node_to_item(node, document, ctx, is_word_start, result)?;
         if !is_consumed {
             for child in node.children() {
                 if let Some(child_node) = document.structure.get_node(*child) {
-                    process_node(child_node, document, ctx, result)?;
+                    process_node(child_node, document, ctx, is_word_start, result)?;
                 }
             }
         }
@@ -75,12 +107,14 @@ fn map_node_to_item(
     node: &DocumentNode,
     document: &Document,
     ctx: &mut LayoutContext,
+    is_word_start: &mut bool,
     result: &mut Vec<Item>,
 ) -> LayoutResult<bool> {
     match node.value() {
-        Text(content) => map_text_node_to_item(content, node, document, ctx, result),
-        Bold | Italic => Ok(false),
-        // TODO Image, math, link, etc.
+        Text(content) => map_text_node_to_item(content, node, document, ctx, is_word_start, result),
+        Bold | Italic | Underline | StrikeThrough | Superscript | Subscript | Code | CodeToken
+        | Link(_) => Ok(false),
+        // TODO Image, math, etc.
         _ => {
             return Err(format!(
                 "Node '{}' is not a supported inline node",
@@ -96,27 +130,70 @@ fn map_text_node_to_item(
     node: &DocumentNode,
     document: &Document,
     ctx: &mut LayoutContext,
+    is_word_start: &mut bool,
     result: &mut Vec<Item>,
 ) -> LayoutResult<bool> {
     let font_ctx = font_util::setup_font(ctx, None)?;
+    let text = transform_text(text, ctx.current_style().text_transform(), is_word_start);
 
-    split_text_into_parts_and_map_to_items(text, node.id, document, ctx, font_ctx, result)?;
+    match ctx.current_style().white_space() {
+        WhiteSpace::Normal => {
+            split_text_into_parts_and_map_to_items(&text, node.id, document, ctx, font_ctx, result)?
+        }
+        WhiteSpace::Pre => {
+            split_preformatted_text_into_items(&text, node.id, false, ctx, font_ctx, result)?
+        }
+        WhiteSpace::PreWrap => {
+            split_preformatted_text_into_items(&text, node.id, true, ctx, font_ctx, result)?
+        }
+    }
 
     Ok(true)
 }
 
+/// Apply the text transform before shaping.
+/// Small caps are applied during shaping instead.
+/// Whether the text starts a new word is given by the preceding text and updated for the following text.
+fn transform_text(text: &str, transform: TextTransform, is_word_start: &mut bool) -> String {
+    let result = match transform {
+        TextTransform::None | TextTransform::SmallCaps => text.to_owned(),
+        TextTransform::Uppercase => text.to_uppercase(),
+        TextTransform::Lowercase => text.to_lowercase(),
+        TextTransform::Capitalize => {
+            let mut result = String::with_capacity(text.len());
+            let mut is_word_start = *is_word_start;
+            for c in text.chars() {
+                if is_word_start {
+                    result.extend(c.to_uppercase());
+                } else {
+                    result.push(c);
+                }
+                is_word_start = c.is_whitespace();
+            }
+            result
+        }
+    };
+
+    if let Some(c) = text.chars().last() {
+        *is_word_start = c.is_whitespace();
+    }
+
+    result
+}
+
 fn glue_after(
     _node_id: NodeId,
-    _ctx: &mut LayoutContext,
+    ctx: &mut LayoutContext,
     font_ctx: &FontContext,
     _last_char: char,
     white_space_width: Distance,
 ) -> Item {
     let font_size = font_ctx.font_size;
+    let word_spacing = ctx.current_style().word_spacing();
 
     Item::Glue(GlueItem::new(
-        white_space_width,
-        font_size / 6.0,
+        white_space_width * word_spacing,
+        font_size / 6.0 * word_spacing,
         font_size / 9.0,
     ))
 }
@@ -131,8 +208,8 @@ fn split_text_into_parts_and_map_to_items(
 ) -> LayoutResult<()> {
     let mut buf = String::new();
     let mut last_char = '*';
-    let white_space_width = calculate_text_width(" ", &font_ctx, ctx)?;
-    let minus_char_width = calculate_text_width("-", &font_ctx, ctx)?;
+    let white_space_width = shape_text_part(" ", &font_ctx, ctx)?.width;
+    let minus_char = shape_text_part("-", &font_ctx, ctx)?;
     let style = ctx.current_style().clone();
 
     for c in text.chars() {
@@ -144,7 +221,7 @@ fn split_text_into_parts_and_map_to_items(
                     document,
                     ctx,
                     &font_ctx,
-                    minus_char_width,
+                    minus_char.width,
                     result,
                 )?;
                 buf.clear();
@@ -174,7 +251,8 @@ fn split_text_into_parts_and_map_to_items(
             }
             '-' => {
                 result.push(Item::Box(BoxItem::new(
-                    minus_char_width,
+                    minus_char.width,
+                    minus_char.baseline_shift,
                     BoxContent::Text(c.to_string()),
                     node_id,
                     style.clone(),
@@ -200,7 +278,7 @@ fn split_text_into_parts_and_map_to_items(
             document,
             ctx,
             &font_ctx,
-            minus_char_width,
+            minus_char.width,
             result,
         )?;
     }
@@ -208,6 +286,88 @@ fn split_text_into_parts_and_map_to_items(
     Ok(())
 }
 
+/// Map text whose white space and line breaks are to be kept to items.
+/// Each line of the text is ended by a forced line break and words are never hyphenated.
+/// Wrappable lines may additionally be broken after each sequence of white space.
+/// Like in HTML, a line break directly at the start of the block is ignored.
+fn split_preformatted_text_into_items(
+    text: &str,
+    node_id: NodeId,
+    wrap: bool,
+    ctx: &mut LayoutContext,
+    font_ctx: FontContext,
+    result: &mut Vec<Item>,
+) -> LayoutResult<()> {
+    let style = ctx.current_style().clone();
+    let text = text
+        .replace("\r\n", "\n")
+        .replace('\t', &" ".repeat(TAB_WIDTH));
+    let text = match text.strip_prefix('\n') {
+        Some(rest) if result.is_empty() => rest,
+        _ => text.as_str(),
+    };
+
+    for (line_index, line) in text.split('\n').enumerate() {
+        if line_index > 0 {
+            push_forced_line_break(result);
+        }
+
+        let chunks = if wrap {
+            split_after_white_space(line)
+        } else {
+            vec![line]
+        };
+        for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+            if chunk_index > 0 {
+                // Ragged right break opportunity that does not take up any space itself,
+                // since the white space is part of the preceding chunk
+                result.push(Item::Glue(GlueItem::new(
+                    Distance::zero(),
+                    font_ctx.font_size * 3.0,
+                    Distance::zero(),
+                )));
+            }
+
+            // Empty lines are kept by an empty box
+            let shaped = shape_text_part(chunk, &font_ctx, ctx)?;
+            result.push(Item::Box(BoxItem::new(
+                shaped.width,
+                shaped.baseline_shift,
+                BoxContent::Text(chunk.to_owned()),
+                node_id,
+                style.clone(),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Split the line into chunks of a word and its following white space.
+/// White space at the start of the line belongs to the first chunk.
+fn split_after_white_space(line: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut is_after_word = false;
+    let mut is_after_white_space = false;
+
+    for (index, c) in line.char_indices() {
+        if c.is_whitespace() {
+            is_after_white_space = is_after_word;
+        } else {
+            if is_after_white_space {
+                result.push(&line[start..index]);
+                start = index;
+                is_after_white_space = false;
+            }
+            is_after_word = true;
+        }
+    }
+    result.push(&line[start..]);
+
+    result
+}
+
 fn split_word_into_syllables_and_map_to_items(
     word: &str,
     node_id: NodeId,
@@ -225,9 +385,10 @@ fn split_word_into_syllables_and_map_to_items(
     let syllables: Vec<&str> = hyphenate(word, lang).collect();
     let syllable_count = syllables.len();
     for (idx, syllable) in syllables.into_iter().enumerate() {
-        let width = calculate_text_width(&syllable, font_ctx, ctx)?;
+        let shaped = shape_text_part(&syllable, font_ctx, ctx)?;
         result.push(Item::Box(BoxItem::new(
-            width,
+            shaped.width,
+            shaped.baseline_shift,
             BoxContent::Text(syllable.to_owned()),
             node_id,
             style.clone(),
@@ -245,13 +406,195 @@ fn split_word_into_syllables_and_map_to_items(
     Ok(())
 }
 
-fn calculate_text_width(
+fn shape_text_part(
     text: &str,
     font_ctx: &FontContext,
     ctx: &mut LayoutContext,
-) -> LayoutResult<Distance> {
+) -> LayoutResult<TextShaperResult> {
     let font = ctx.get_font_mut(&font_ctx.font_id);
-    let result = shape_text(text, font_ctx.font_size, font)?;
+    let result = shape_text(text, font_ctx.font_size, font, &font_ctx.shaping_options)?;
+
+    Ok(result)
+}
 
-    Ok(result.width)
+#[cfg(test)]
+mod tests {
+    use crate::context::OneSizeFitsAllPageSizing;
+    use crate::element::{LayoutConstraints, Size};
+
+    use super::*;
+
+    /// Split the preformatted text into items and describe them as text,
+    /// where `|` marks a wrap opportunity and each forced line break is written as a line break.
+    fn split_preformatted(text: &str, wrap: bool) -> String {
+        let a4 = Size::new(
+            Distance::new(210.0, DistanceUnit::Millimeter),
+            Distance::new(297.0, DistanceUnit::Millimeter),
+        );
+        let margin = Distance::new(20.0, DistanceUnit::Millimeter);
+        let constraints = LayoutConstraints::new(a4, margin, margin, margin, margin);
+        let mut ctx =
+            LayoutContext::new(None, Box::new(OneSizeFitsAllPageSizing::new(constraints)));
+        let font_ctx = font_util::setup_font(&mut ctx, None).unwrap();
+
+        let mut items = Vec::new();
+        split_preformatted_text_into_items(text, 0, wrap, &mut ctx, font_ctx, &mut items).unwrap();
+
+        let mut result = String::new();
+        for item in &items {
+            match item {
+                Item::Box(box_item) => {
+                    let BoxContent::Text(text) = box_item.content();
+                    result.push_str(&format!("[{}]", text));
+                }
+                Item::Glue(glue) if glue.stretch() > Distance::new(1.0, DistanceUnit::Meter) => {}
+                Item::Glue(_) => result.push('|'),
+                Item::Penalty(_) => result.push('\n'),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn should_split_preformatted_text_into_lines() {
+        // Given: A preformatted text with several lines
+        let text = "fn main() {\n    run();\n}";
+
+        // When: Splitting the text into items without wrapping
+        let result = split_preformatted(text, false);
+
+        // Then: Each line is a single box ended by a forced line break
+        assert_eq!(result, "[fn main() {]\n[    run();]\n[}]");
+    }
+
+    #[test]
+    fn should_expand_tabs_in_preformatted_text() {
+        // Given: A preformatted text indented by tabs
+        let text = "a\n\tb\n\t\tc";
+
+        // When: Splitting the text into items
+        let result = split_preformatted(text, false);
+
+        // Then: Each tab is replaced by four spaces
+        assert_eq!(result, "[a]\n[    b]\n[        c]");
+    }
+
+    #[test]
+    fn should_keep_blank_lines_in_preformatted_text() {
+        // Given: A preformatted text with blank lines between and after its lines
+        let text = "a\r\n\n\nb\n";
+
+        // When: Splitting the text into items
+        let result = split_preformatted(text, false);
+
+        // Then: Blank lines are kept as empty boxes
+        assert_eq!(result, "[a]\n[]\n[]\n[b]\n[]");
+    }
+
+    #[test]
+    fn should_ignore_line_break_at_start_of_preformatted_block() {
+        // Given: A preformatted text starting with a line break like after the opening tag
+        let text = "\n\na";
+
+        // When: Splitting the text into items at the start of the block
+        let result = split_preformatted(text, false);
+
+        // Then: Only the first line break is ignored
+        assert_eq!(result, "[]\n[a]");
+    }
+
+    #[test]
+    fn should_allow_wrapping_after_white_space_in_preformatted_text() {
+        // Given: A wrappable preformatted line with leading and inner white space
+        let text = "  let  x = 1;";
+
+        // When: Splitting the text into items with wrapping
+        let result = split_preformatted(text, true);
+
+        // Then: The line may be broken after each sequence of white space
+        assert_eq!(result, "[  let  ]|[x ]|[= ]|[1;]");
+    }
+
+    #[test]
+    fn should_capitalize_first_letter_of_each_word() {
+        // Given: A text with several words
+        let text = "hello  wide\nworld";
+
+        // When: Capitalizing the text
+        let mut is_word_start = true;
+        let result = transform_text(text, TextTransform::Capitalize, &mut is_word_start);
+
+        // Then: The first letter of each word is uppercase
+        assert_eq!(result, "Hello  Wide\nWorld");
+        assert!(!is_word_start);
+    }
+
+    #[test]
+    fn should_continue_word_across_text_nodes() {
+        // Given: A word split into two sibling text nodes like in `foo<b>bar</b>`
+        let mut is_word_start = true;
+
+        // When: Capitalizing both texts in order
+        let first = transform_text("foo", TextTransform::Capitalize, &mut is_word_start);
+        let second = transform_text("bar", TextTransform::Capitalize, &mut is_word_start);
+
+        // Then: Only the start of the word is capitalized
+        assert_eq!(first, "Foo");
+        assert_eq!(second, "bar");
+    }
+
+    #[test]
+    fn should_start_new_word_after_white_space_in_previous_text_node() {
+        // Given: A text node ending with white space followed by another text node
+        let mut is_word_start = true;
+
+        // When: Capitalizing both texts in order
+        transform_text("foo ", TextTransform::Capitalize, &mut is_word_start);
+        let result = transform_text("bar", TextTransform::Capitalize, &mut is_word_start);
+
+        // Then: The second text starts a new word
+        assert_eq!(result, "Bar");
+    }
+
+    #[test]
+    fn should_track_word_start_in_untransformed_text() {
+        // Given: An untransformed text node followed by a capitalized one like in `foo<span>bar</span>`
+        let mut is_word_start = true;
+
+        // When: Transforming both texts in order
+        let first = transform_text("foo", TextTransform::None, &mut is_word_start);
+        let second = transform_text("bar", TextTransform::Capitalize, &mut is_word_start);
+
+        // Then: The capitalized text continues the word of the untransformed text
+        assert_eq!(first, "foo");
+        assert_eq!(second, "bar");
+    }
+
+    #[test]
+    fn should_keep_word_start_for_empty_text() {
+        // Given: An empty text node between two words
+        let mut is_word_start = true;
+        transform_text("foo ", TextTransform::None, &mut is_word_start);
+
+        // When: Transforming an empty text before the next word
+        transform_text("", TextTransform::Capitalize, &mut is_word_start);
+        let result = transform_text("bar", TextTransform::Capitalize, &mut is_word_start);
+
+        // Then: The word start is kept
+        assert_eq!(result, "Bar");
+    }
+
+    #[test]
+    fn should_transform_case() {
+        // Given: A text with mixed case
+        let text = "Straße ist Lang";
+
+        // When: Transforming the text to upper and lower case
+        let uppercase = transform_text(text, TextTransform::Uppercase, &mut true);
+        let lowercase = transform_text(text, TextTransform::Lowercase, &mut true);
+
+        // Then: All letters are transformed
+        assert_eq!(uppercase, "STRASSE IST LANG");
+        assert_eq!(lowercase, "straße ist lang");
+    }
 }
@@ -14,6 +14,35 @@ pub fn read_meta_data(src: &str) -> Result<DocumentMetaData, Box<dyn Error>> {
 
     for (key, value) in table.into_iter() {
         match key.as_str() {
+            "title" => {
+                result.title = Some(
+                    value
+                        .as_str()
+                        .expect("expected value for title")
+                        .to_string(),
+                )
+            }
+            "subject" => {
+                result.subject = Some(
+                    value
+                        .as_str()
+                        .expect("expected value for subject")
+                        .to_string(),
+                )
+            }
+            "keywords" => {
+                result.keywords = value
+                    .as_array()
+                    .expect("expected array for keywords")
+                    .iter()
+                    .map(|keyword| {
+                        keyword
+                            .as_str()
+                            .expect("expected string for keyword")
+                            .to_string()
+                    })
+                    .collect();
+            }
             "encoding" => {
                 result.encoding =
                     DocumentEncoding::new(value.as_str().expect("expected value for encoding"))
@@ -91,7 +120,14 @@ pub fn read_meta_data(src: &str) -> Result<DocumentMetaData, Box<dyn Error>> {
                     .as_table()
                     .expect("expected table for variables")
                     .into_iter()
-                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .map(|(key, value)| {
+                        let value = match value.as_str() {
+                            Some(value) => value.to_string(),
+                            None => value.to_string(),
+                        };
+
+                        (key.to_string(), value)
+                    })
                     .for_each(|(key, value)| variables.set(&key, &value));
 
                 result.variables = variables;
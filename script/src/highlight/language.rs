@@ -0,0 +1,114 @@
+use crate::highlight::TokenClass;
+
+/// Lexical rules of a language supported by the highlighter.
+pub(crate) struct Language {
+    pub(crate) names: &'static [&'static str],
+    pub(crate) keywords: &'static [&'static str],
+    pub(crate) types: &'static [&'static str],
+    pub(crate) line_comments: &'static [&'static str],
+    pub(crate) block_comments: &'static [(&'static str, &'static str)],
+    pub(crate) string_quotes: &'static [char],
+
+    /// Characters allowed in identifiers besides alphanumeric characters and underscores.
+    pub(crate) identifier_chars: &'static str,
+
+    /// Characters that start an identifier of the given class (for example `$HOME` in shell scripts).
+    pub(crate) prefixes: &'static [(char, TokenClass)],
+
+    /// Whether identifiers starting with an uppercase letter are types.
+    pub(crate) capitalized_types: bool,
+
+    /// Whether lines starting with `[` are section headers (like `[dependencies]` in TOML).
+    pub(crate) section_headers: bool,
+
+    /// Whether the language is XML-like, where tag names are keywords and attributes are types.
+    pub(crate) markup: bool,
+}
+
+impl Language {
+    pub(crate) fn find(name: &str) -> Option<&'static Language> {
+        let name = name.trim().to_lowercase();
+
+        LANGUAGES
+            .iter()
+            .find(|language| language.names.contains(&name.as_str()))
+    }
+}
+
+const PLAIN: Language = Language {
+    names: &[],
+    keywords: &[],
+    types: &[],
+    line_comments: &[],
+    block_comments: &[],
+    string_quotes: &[],
+    identifier_chars: "",
+    prefixes: &[],
+    capitalized_types: false,
+    section_headers: false,
+    markup: false,
+};
+
+const LANGUAGES: [Language; 6] = [
+    Language {
+        names: &["rust", "rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+            "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+            "true", "type", "unsafe", "use", "where", "while",
+        ],
+        types: &[
+            "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+            "u16", "u32", "u64", "u128", "usize",
+        ],
+        line_comments: &["//"],
+        block_comments: &[("/*", "*/")],
+        string_quotes: &['"'],
+        capitalized_types: true,
+        ..PLAIN
+    },
+    Language {
+        names: &["toml"],
+        keywords: &["true", "false"],
+        line_comments: &["#"],
+        string_quotes: &['"', '\''],
+        identifier_chars: "-",
+        section_headers: true,
+        ..PLAIN
+    },
+    Language {
+        names: &["json"],
+        keywords: &["true", "false", "null"],
+        string_quotes: &['"'],
+        ..PLAIN
+    },
+    Language {
+        names: &["shell", "sh", "bash", "zsh"],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "until", "while",
+        ],
+        line_comments: &["#"],
+        string_quotes: &['"', '\''],
+        identifier_chars: "-",
+        prefixes: &[('$', TokenClass::Type)],
+        ..PLAIN
+    },
+    Language {
+        names: &["lsc", "letter-script"],
+        block_comments: &[("<!--", "-->")],
+        string_quotes: &['"', '\''],
+        identifier_chars: "-",
+        markup: true,
+        ..PLAIN
+    },
+    Language {
+        names: &["lst", "letter-style"],
+        block_comments: &[("/*", "*/")],
+        string_quotes: &['"', '\''],
+        identifier_chars: "-",
+        prefixes: &[('@', TokenClass::Keyword)],
+        ..PLAIN
+    },
+];
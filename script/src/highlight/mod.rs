@@ -0,0 +1,279 @@
+//! Built-in syntax highlighting for code blocks like `<code-block lang="rust">`.
+//! The code is split into tokens that are classified by a simple lexer configured per language,
+//! which is good enough to color keywords, strings, comments, numbers and types.
+
+use crate::highlight::language::Language;
+
+mod language;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TokenClass {
+    Keyword,
+    String,
+    Comment,
+    Number,
+    Type,
+}
+
+impl TokenClass {
+    /// Name of the class used to select the token in style sheets, for example `code-token.keyword`.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            TokenClass::Keyword => "keyword",
+            TokenClass::String => "string",
+            TokenClass::Comment => "comment",
+            TokenClass::Number => "number",
+            TokenClass::Type => "type",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Token<'a> {
+    pub(crate) class: Option<TokenClass>,
+    pub(crate) text: &'a str,
+}
+
+/// Split the code into tokens, returns nothing for unsupported languages.
+/// Joining the text of all tokens results in the original code.
+pub(crate) fn highlight<'a>(language: &str, code: &'a str) -> Option<Vec<Token<'a>>> {
+    let language = Language::find(language)?;
+
+    Some(Lexer::new(language, code).tokenize())
+}
+
+struct Lexer<'a> {
+    language: &'static Language,
+    code: &'a str,
+    position: usize,
+    is_in_tag: bool,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(language: &'static Language, code: &'a str) -> Self {
+        Self {
+            language,
+            code,
+            position: 0,
+            is_in_tag: false,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn tokenize(mut self) -> Vec<Token<'a>> {
+        while self.position < self.code.len() {
+            let rest = &self.code[self.position..];
+            let c = rest.chars().next().unwrap();
+
+            let token_length = if let Some(length) = self.comment_length(rest) {
+                self.push(length, Some(TokenClass::Comment))
+            } else if self.is_string_start(c) {
+                self.push(string_length(rest, c), Some(TokenClass::String))
+            } else if c.is_ascii_digit() {
+                self.push(word_length(rest, "."), Some(TokenClass::Number))
+            } else if self.language.section_headers && c == '[' && self.is_at_line_start() {
+                let line_length = rest.find('\n').unwrap_or(rest.len());
+                let length = rest[..line_length]
+                    .rfind(']')
+                    .map_or(line_length, |index| index + 1);
+                self.push(length, Some(TokenClass::Type))
+            } else if c.is_alphabetic() || c == '_' || self.prefix_class(c).is_some() {
+                let length = word_length(rest, self.language.identifier_chars);
+                let class = self.classify_word(&rest[..length]);
+                self.push(length, class)
+            } else {
+                if self.language.markup {
+                    match c {
+                        '<' => self.is_in_tag = true,
+                        '>' => self.is_in_tag = false,
+                        _ => {}
+                    }
+                }
+
+                self.push(c.len_utf8(), None)
+            };
+
+            self.position += token_length;
+        }
+
+        self.tokens
+    }
+
+    fn comment_length(&self, rest: &str) -> Option<usize> {
+        if let Some((start, end)) = self
+            .language
+            .block_comments
+            .iter()
+            .find(|(start, _)| rest.starts_with(start))
+        {
+            return Some(
+                rest[start.len()..]
+                    .find(end)
+                    .map_or(rest.len(), |index| start.len() + index + end.len()),
+            );
+        }
+
+        self.language
+            .line_comments
+            .iter()
+            .find(|start| rest.starts_with(*start))
+            .map(|_| rest.find('\n').unwrap_or(rest.len()))
+    }
+
+    /// Quotes only start strings inside of tags in markup languages,
+    /// since the text between tags may contain apostrophes.
+    fn is_string_start(&self, c: char) -> bool {
+        self.language.string_quotes.contains(&c) && (!self.language.markup || self.is_in_tag)
+    }
+
+    fn prefix_class(&self, c: char) -> Option<TokenClass> {
+        self.language
+            .prefixes
+            .iter()
+            .find(|(prefix, _)| *prefix == c)
+            .map(|(_, class)| *class)
+    }
+
+    fn classify_word(&self, word: &str) -> Option<TokenClass> {
+        let first_char = word.chars().next()?;
+        if let Some(class) = self.prefix_class(first_char) {
+            return Some(class);
+        }
+
+        if self.language.markup {
+            let before = self.code[..self.position].trim_end_matches('/');
+            return if before.ends_with('<') {
+                Some(TokenClass::Keyword)
+            } else if self.is_in_tag {
+                Some(TokenClass::Type)
+            } else {
+                None
+            };
+        }
+
+        if self.language.keywords.contains(&word) {
+            Some(TokenClass::Keyword)
+        } else if self.language.types.contains(&word)
+            || (self.language.capitalized_types && first_char.is_uppercase())
+        {
+            Some(TokenClass::Type)
+        } else {
+            None
+        }
+    }
+
+    fn is_at_line_start(&self) -> bool {
+        let line_start = self.code[..self.position]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+
+        self.code[line_start..self.position].trim().is_empty()
+    }
+
+    /// Add the next token of the given length, plain text is merged with the previous plain token.
+    fn push(&mut self, length: usize, class: Option<TokenClass>) -> usize {
+        let start = self.position;
+        let end = start + length;
+
+        match self.tokens.last_mut() {
+            Some(last) if class.is_none() && last.class.is_none() => {
+                let last_start = end - length - last.text.len();
+                last.text = &self.code[last_start..end];
+            }
+            _ => self.tokens.push(Token {
+                class,
+                text: &self.code[start..end],
+            }),
+        }
+
+        length
+    }
+}
+
+/// Length of the string starting with the given quote up to and including the closing quote.
+/// Escaped quotes are skipped, unterminated strings span the rest of the code.
+fn string_length(rest: &str, quote: char) -> usize {
+    let mut is_escaped = false;
+    for (index, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if is_escaped => is_escaped = false,
+            '\\' => is_escaped = true,
+            c if c == quote => return index + c.len_utf8(),
+            _ => {}
+        }
+    }
+
+    rest.len()
+}
+
+/// Length of the word at the start of the given text.
+/// Words may contain alphanumeric characters, underscores and the given additional characters.
+fn word_length(rest: &str, additional_chars: &str) -> usize {
+    rest.char_indices()
+        .skip(1)
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || additional_chars.contains(*c)))
+        .map_or(rest.len(), |(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_highlight_rust_code() {
+        // Given: a snippet of Rust code
+        let code = "fn main() { let x: u32 = 42; // answer\n    println!(\"{}\", x); }";
+
+        // When: the code is highlighted
+        let tokens = highlight("rust", code).unwrap();
+
+        // Then: the tokens are classified and cover the whole code
+        let classified: Vec<(TokenClass, &str)> = tokens
+            .iter()
+            .filter_map(|token| token.class.map(|class| (class, token.text)))
+            .collect();
+        assert_eq!(
+            classified,
+            vec![
+                (TokenClass::Keyword, "fn"),
+                (TokenClass::Keyword, "let"),
+                (TokenClass::Type, "u32"),
+                (TokenClass::Number, "42"),
+                (TokenClass::Comment, "// answer"),
+                (TokenClass::String, "\"{}\""),
+            ]
+        );
+        assert_eq!(
+            tokens.iter().map(|token| token.text).collect::<String>(),
+            code
+        );
+    }
+
+    #[test]
+    fn should_highlight_letter_script_tags() {
+        // Given: a snippet of a Letter script
+        let code = "<p class=\"intro\">Some <b>text</b></p> <!-- note -->";
+
+        // When: the code is highlighted
+        let tokens = highlight("lsc", code).unwrap();
+
+        // Then: tag names, attributes, attribute values and comments are classified
+        let classified: Vec<(TokenClass, &str)> = tokens
+            .iter()
+            .filter_map(|token| token.class.map(|class| (class, token.text)))
+            .collect();
+        assert_eq!(
+            classified,
+            vec![
+                (TokenClass::Keyword, "p"),
+                (TokenClass::Type, "class"),
+                (TokenClass::String, "\"intro\""),
+                (TokenClass::Keyword, "b"),
+                (TokenClass::Keyword, "b"),
+                (TokenClass::Keyword, "p"),
+                (TokenClass::Comment, "<!-- note -->"),
+            ]
+        );
+    }
+}
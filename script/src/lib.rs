@@ -6,12 +6,19 @@ use quick_xml::events::Event;
 use quick_xml::Reader;
 
 use document::structure::{
-    DocumentNode, DocumentNodeValue, DocumentStructure, NodeId, SourcePosition,
+    DocumentNode, DocumentNodeValue, DocumentStructure, LinkTarget, NodeId, SourcePosition,
 };
 use source_position_resolver::SourcePositionResolver;
 
+mod highlight;
 mod source_position_resolver;
 
+const LANGUAGE_ATTRIBUTE: &str = "lang";
+const CODE_TOKEN_NODE_NAME: &str = "code-token";
+const CLASS_ATTRIBUTE: &str = "class";
+const LINK_URI_ATTRIBUTE: &str = "href";
+const LINK_ANCHOR_ATTRIBUTE: &str = "to";
+
 pub type ParseResult<T> = Result<T, Box<dyn Error>>;
 
 struct ParseContext {
@@ -94,7 +101,15 @@ fn handle_event(
 }
 
 fn push_text_node(text: String, offset: usize, context: &mut ParseContext) -> ParseResult<()> {
-    if text.trim().is_empty() {
+    if let Some(language) = find_code_block_language(context) {
+        if let Some(tokens) = highlight::highlight(&language, &text) {
+            push_code_token_nodes(tokens, offset, context);
+            return Ok(());
+        }
+    }
+
+    // White space between tags is insignificant, except within code blocks
+    if text.trim().is_empty() && !is_within_code_block(context) {
         return Ok(());
     }
 
@@ -104,6 +119,56 @@ fn push_text_node(text: String, offset: usize, context: &mut ParseContext) -> Pa
     Ok(())
 }
 
+/// Language of the code block the text is directly placed in, if any.
+fn find_code_block_language(context: &ParseContext) -> Option<String> {
+    let parent_id = context.node_stack.last()?;
+    let parent = context.document_structure.get_node(*parent_id)?;
+
+    match parent.value() {
+        DocumentNodeValue::CodeBlock => parent.attributes.get(LANGUAGE_ATTRIBUTE).cloned(),
+        _ => None,
+    }
+}
+
+/// Whether any of the currently open nodes is a code block.
+fn is_within_code_block(context: &ParseContext) -> bool {
+    context.node_stack.iter().any(|node_id| {
+        context
+            .document_structure
+            .get_node(*node_id)
+            .is_some_and(|node| matches!(node.value(), DocumentNodeValue::CodeBlock))
+    })
+}
+
+/// Insert the tokens of highlighted code.
+/// Plain tokens become text nodes (including pure white space, since it is significant in code),
+/// while classified tokens are wrapped in a `code-token` node with the token class as class name.
+fn push_code_token_nodes(tokens: Vec<highlight::Token>, offset: usize, context: &mut ParseContext) {
+    for token in tokens {
+        match token.class {
+            None => {
+                let text_node = to_text_node(token.text.to_owned(), offset, context);
+                insert_node(text_node, context);
+            }
+            Some(class) => {
+                let source_position = find_source_position(offset, context);
+                let node_id = context.document_structure.unused_node_id();
+                let token_node = DocumentNode::new(
+                    node_id,
+                    Some(CODE_TOKEN_NODE_NAME.to_owned()),
+                    DocumentNodeValue::CodeToken,
+                    HashMap::from([(CLASS_ATTRIBUTE.to_owned(), class.name().to_owned())]),
+                    Some(source_position),
+                );
+                insert_node(token_node, context);
+
+                let text_node = to_text_node(token.text.to_owned(), offset, context);
+                context.document_structure.insert(node_id, text_node);
+            }
+        }
+    }
+}
+
 fn push_child_node(
     name: &str,
     attributes: Attributes,
@@ -220,16 +285,18 @@ fn to_text_node(text: String, offset: usize, context: &mut ParseContext) -> Docu
 
 fn to_node_value(
     name: &str,
-    _attributes: &HashMap<String, String>,
+    attributes: &HashMap<String, String>,
     source_position: SourcePosition,
 ) -> ParseResult<DocumentNodeValue> {
     Ok(match name {
-        "section" | "s" => DocumentNodeValue::Section,
+        "section" => DocumentNodeValue::Section,
         "paragraph" | "p" => DocumentNodeValue::Paragraph,
         "heading" | "h" => DocumentNodeValue::Heading,
         "list" | "l" => DocumentNodeValue::List,
         "list-item" | "li" => DocumentNodeValue::ListItem,
         "table" | "t" => DocumentNodeValue::Table,
+        "table-row" | "tr" => DocumentNodeValue::TableRow,
+        "table-cell" | "td" => DocumentNodeValue::TableCell,
         "break" | "br" => DocumentNodeValue::Break,
         "image" | "img" => DocumentNodeValue::Image {
             source: "".to_string(), // TODO Parse from attributes
@@ -238,6 +305,14 @@ fn to_node_value(
         },
         "bold" | "b" => DocumentNodeValue::Bold,
         "italic" | "i" => DocumentNodeValue::Italic,
+        "underline" | "u" => DocumentNodeValue::Underline,
+        "strike-through" | "s" => DocumentNodeValue::StrikeThrough,
+        "superscript" | "sup" => DocumentNodeValue::Superscript,
+        "subscript" | "sub" => DocumentNodeValue::Subscript,
+        "code" => DocumentNodeValue::Code,
+        "code-block" | "pre" => DocumentNodeValue::CodeBlock,
+        "code-token" => DocumentNodeValue::CodeToken,
+        "link" => DocumentNodeValue::Link(to_link_target(attributes, source_position)?),
         _ => Err(format!(
             "Node with name '{}' at '{}:{}' is currently not supported.",
             name, source_position.line, source_position.column
@@ -245,3 +320,70 @@ fn to_node_value(
         .to_owned())?,
     })
 }
+
+/// Links either point to an external resource (`href`) or to a node within the document (`to`).
+fn to_link_target(
+    attributes: &HashMap<String, String>,
+    source_position: SourcePosition,
+) -> ParseResult<LinkTarget> {
+    match (
+        attributes.get(LINK_URI_ATTRIBUTE),
+        attributes.get(LINK_ANCHOR_ATTRIBUTE),
+    ) {
+        (Some(uri), None) => Ok(LinkTarget::Uri(uri.to_owned())),
+        (None, Some(anchor)) => Ok(LinkTarget::Anchor(anchor.to_owned())),
+        _ => Err(format!(
+            "Link at '{}:{}' must have either a '{}' or a '{}' attribute.",
+            source_position.line, source_position.column, LINK_URI_ATTRIBUTE, LINK_ANCHOR_ATTRIBUTE
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_child_id(structure: &DocumentStructure) -> NodeId {
+        structure.root().children()[0]
+    }
+
+    #[test]
+    fn should_keep_white_space_between_nodes_within_code_block() {
+        // Given: A code block whose words are separated by white space between inline nodes
+        let src = "<code-block>\n<b>fn</b> <i>main</i>\n    <b>x</b>\n</code-block>";
+
+        // When: Parsing the document structure
+        let structure = parse_document_structure(src).unwrap();
+
+        // Then: The white space between the nodes is kept
+        let code_block_id = first_child_id(&structure);
+        assert_eq!(structure.text_content(code_block_id), "\nfn main\n    x\n");
+    }
+
+    #[test]
+    fn should_keep_white_space_in_nodes_nested_in_code_block() {
+        // Given: A code block with white space between nodes nested in another inline node
+        let src = "<pre><b><i>a</i> <i>b</i></b></pre>";
+
+        // When: Parsing the document structure
+        let structure = parse_document_structure(src).unwrap();
+
+        // Then: The white space within the nested node is kept
+        let code_block_id = first_child_id(&structure);
+        assert_eq!(structure.text_content(code_block_id), "a b");
+    }
+
+    #[test]
+    fn should_drop_white_space_between_nodes_outside_of_code_block() {
+        // Given: A paragraph with white space between its inline nodes
+        let src = "<p>\n    <b>a</b> <i>b</i>\n</p>";
+
+        // When: Parsing the document structure
+        let structure = parse_document_structure(src).unwrap();
+
+        // Then: The white space between the nodes is dropped
+        let paragraph_id = first_child_id(&structure);
+        assert_eq!(structure.text_content(paragraph_id), "ab");
+    }
+}
@@ -0,0 +1,161 @@
+//! Parsing of `calc(...)` distances like `calc(100% - 2cm)` into an expression tree
+//! that is evaluated during layout.
+
+use pest::iterators::Pair;
+use pest::Parser;
+
+use document::style::{DistanceExpression, StyleDistance};
+use unit::{Distance, DistanceUnit};
+
+use crate::result::StyleParseResult;
+use crate::{parse_distance, LetterStyleParser, Rule};
+
+pub(crate) const CALC_FUNCTION_PREFIX: &str = "calc(";
+
+pub(crate) fn parse_calc_distance(value: &str) -> StyleParseResult<StyleDistance> {
+    let mut expression = None;
+
+    let pairs = LetterStyleParser::parse(Rule::CalcExpression, value)?;
+    for pair in pairs {
+        for pair in pair.into_inner() {
+            if let Rule::Calc = pair.as_rule() {
+                expression = Some(parse_calc(pair)?);
+            }
+        }
+    }
+
+    let expression = expression.ok_or(format!("Invalid calc() expression '{}'", value))?;
+    validate(&expression, value)?;
+
+    Ok(StyleDistance::Calc(Box::new(expression)))
+}
+
+/// Check that the expression does not mix incompatible units
+/// by evaluating it with placeholder distances.
+fn validate(expression: &DistanceExpression, value: &str) -> StyleParseResult<()> {
+    let result = expression.evaluate(&|_: &StyleDistance| {
+        Ok::<Distance, String>(Distance::new(1.0, DistanceUnit::Millimeter))
+    });
+
+    match result {
+        Ok(document::style::DistanceExpressionValue::Distance(_)) => Ok(()),
+        Ok(document::style::DistanceExpressionValue::Number(_)) => Err(format!(
+            "Expression '{}' must result in a distance but results in a number",
+            value
+        )
+        .into()),
+        Err(message) => Err(format!("{} (in '{}')", message, value).into()),
+    }
+}
+
+fn parse_calc(pair: Pair<Rule>) -> StyleParseResult<DistanceExpression> {
+    let sum = pair
+        .into_inner()
+        .next()
+        .ok_or("Expected an expression in calc()")?;
+
+    parse_sum(sum)
+}
+
+fn parse_sum(pair: Pair<Rule>) -> StyleParseResult<DistanceExpression> {
+    let mut pairs = pair.into_inner();
+    let mut result = parse_product(pairs.next().ok_or("Expected an operand")?)?;
+
+    while let (Some(operator), Some(operand)) = (pairs.next(), pairs.next()) {
+        let operand = Box::new(parse_product(operand)?);
+
+        result = match operator.as_str() {
+            "+" => DistanceExpression::Add(Box::new(result), operand),
+            "-" => DistanceExpression::Subtract(Box::new(result), operand),
+            _ => unreachable!(),
+        };
+    }
+
+    Ok(result)
+}
+
+fn parse_product(pair: Pair<Rule>) -> StyleParseResult<DistanceExpression> {
+    let mut pairs = pair.into_inner();
+    let mut result = parse_factor(pairs.next().ok_or("Expected an operand")?)?;
+
+    while let (Some(operator), Some(operand)) = (pairs.next(), pairs.next()) {
+        let operand = Box::new(parse_factor(operand)?);
+
+        result = match operator.as_str() {
+            "*" => DistanceExpression::Multiply(Box::new(result), operand),
+            "/" => DistanceExpression::Divide(Box::new(result), operand),
+            _ => unreachable!(),
+        };
+    }
+
+    Ok(result)
+}
+
+fn parse_factor(pair: Pair<Rule>) -> StyleParseResult<DistanceExpression> {
+    let pair = pair.into_inner().next().ok_or("Expected an operand")?;
+
+    match pair.as_rule() {
+        Rule::Calc => parse_calc(pair),
+        Rule::CalcSum => parse_sum(pair),
+        Rule::CalcDimension => Ok(DistanceExpression::Distance(parse_distance(pair.as_str())?)),
+        Rule::CalcNumber => Ok(DistanceExpression::Number(pair.as_str().parse::<f64>()?)),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_respect_operator_precedence() {
+        // Given: an expression with mixed operators
+        let value = "calc(100% - 2cm * 2)";
+
+        // When: the expression is parsed
+        let distance = parse_calc_distance(value).unwrap();
+
+        // Then: the multiplication binds stronger than the subtraction
+        let expected = StyleDistance::Calc(Box::new(DistanceExpression::Subtract(
+            Box::new(DistanceExpression::Distance(StyleDistance::Percent(100.0))),
+            Box::new(DistanceExpression::Multiply(
+                Box::new(DistanceExpression::Distance(StyleDistance::Absolute(
+                    Distance::new(2.0, DistanceUnit::Centimeter),
+                ))),
+                Box::new(DistanceExpression::Number(2.0)),
+            )),
+        )));
+        assert_eq!(distance, expected);
+    }
+
+    #[test]
+    fn should_evaluate_absolute_expressions() {
+        // Given: an expression with nested brackets and calc() functions
+        let value = "calc((2cm + 10mm) / 2 - calc(-5mm))";
+
+        // When: the expression is parsed and evaluated
+        let distance = parse_calc_distance(value).unwrap().as_absolute().unwrap();
+
+        // Then: the result is correct
+        assert_eq!(distance.value(DistanceUnit::Millimeter), 20.0);
+    }
+
+    #[test]
+    fn should_fail_for_incompatible_units() {
+        // Given: expressions mixing numbers and distances incompatibly
+        let values = [
+            "calc(2cm + 3)",
+            "calc(2cm * 3mm)",
+            "calc(3 / 2cm)",
+            "calc(3 * 2)",
+        ];
+
+        for value in values {
+            // When: the expression is parsed
+            let result = parse_calc_distance(value);
+
+            // Then: an error is returned
+            assert!(result.is_err(), "Expected '{}' to be rejected", value);
+        }
+    }
+}
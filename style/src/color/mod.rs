@@ -2,11 +2,13 @@ use core::fmt::{Debug, Display};
 
 pub use cmyk::CMYKColor;
 pub use hex::HexColor;
+pub(crate) use parse::parse_color;
 pub use rgb::RGBColor;
 pub use rgba::RGBAColor;
 
 mod cmyk;
 mod hex;
+mod parse;
 mod rgb;
 mod rgba;
 
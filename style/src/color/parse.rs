@@ -0,0 +1,107 @@
+//! Parsing of color values like `#e4002b`, `#fff` or `rgb(228, 0, 43)` into document colors.
+
+use document::style::Color as DocumentColor;
+
+use crate::result::StyleParseResult;
+
+use super::{Color, HexColor, RGBColor};
+
+const NAMED_COLORS: [(&str, usize); 6] = [
+    ("black", 0x000000FF),
+    ("white", 0xFFFFFFFF),
+    ("gray", 0x808080FF),
+    ("red", 0xFF0000FF),
+    ("green", 0x008000FF),
+    ("blue", 0x0000FFFF),
+];
+
+pub(crate) fn parse_color(value: &str) -> StyleParseResult<DocumentColor> {
+    let value = value.trim();
+
+    let rgb = if let Some(hex) = value.strip_prefix('#') {
+        HexColor::new(parse_hex_value(hex).ok_or(format!(
+            "Invalid hex color '{}', expected the format '#rgb', '#rrggbb' or '#rrggbbaa'",
+            value
+        ))?)
+        .as_rgb()
+    } else if let Some(arguments) = value
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        parse_rgb_arguments(arguments).ok_or(format!(
+            "Invalid color '{}', expected three values between 0 and 255 like 'rgb(228, 0, 43)'",
+            value
+        ))?
+    } else {
+        let (_, hex) = NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == value)
+            .ok_or(format!(
+                "Unknown color '{}', expected a hex color like '#e4002b', 'rgb(...)' or one of: {}",
+                value,
+                NAMED_COLORS.map(|(name, _)| name).join(", ")
+            ))?;
+
+        HexColor::new(*hex).as_rgb()
+    };
+
+    Ok(DocumentColor::new(
+        rgb.red() as u8,
+        rgb.green() as u8,
+        rgb.blue() as u8,
+    ))
+}
+
+/// Parse the digits of a hex color into a value including the alpha channel.
+fn parse_hex_value(hex: &str) -> Option<usize> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>() + "FF",
+        6 => format!("{}FF", hex),
+        8 => hex.to_owned(),
+        _ => return None,
+    };
+
+    usize::from_str_radix(&hex, 16).ok()
+}
+
+fn parse_rgb_arguments(arguments: &str) -> Option<RGBColor> {
+    let values = arguments
+        .split(',')
+        .map(|value| value.trim().parse::<usize>().ok())
+        .collect::<Option<Vec<usize>>>()?;
+
+    match values[..] {
+        [red, green, blue] => RGBColor::new(red, green, blue).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_colors() {
+        // Given: the same color in different notations
+        let values = ["#e4002b", "#E4002BFF", "rgb(228, 0, 43)"];
+
+        for value in values {
+            // When: the color is parsed
+            let color = parse_color(value).unwrap();
+
+            // Then: all notations result in the same color
+            assert_eq!(color, DocumentColor::new(228, 0, 43), "{}", value);
+        }
+
+        assert_eq!(
+            parse_color("#fff").unwrap(),
+            DocumentColor::new(255, 255, 255)
+        );
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("rgb(256, 0, 0)").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Parsing of `@font-face` rules registering font files for a font family, for example
+//! `@font-face { family: "Brand"; src: url(fonts/Brand-Regular.otf); weight: 400; style: normal; }`.
+
+use std::collections::HashMap;
+
+use pest::iterators::Pairs;
+
+use document::style::{DocumentStyles, FontFace, FontStyle};
+
+use crate::import::ImportContext;
+use crate::result::StyleParseResult;
+use crate::{parse_font_style, parse_font_weight, parse_unnamed_block_to_map, Rule};
+
+pub(crate) const PROPERTY_NAMES: [&str; 5] = ["family", "src", "weight", "style", "index"];
+const DEFAULT_FONT_WEIGHT: f32 = 400.0;
+
+pub(crate) fn parse_font_face(
+    pairs: Pairs<Rule>,
+    styles: &mut DocumentStyles,
+    context: &ImportContext,
+) -> StyleParseResult<()> {
+    let properties = parse_unnamed_block_to_map(pairs)?;
+
+    if let Some(key) = properties
+        .keys()
+        .find(|key| !PROPERTY_NAMES.contains(&key.as_str()))
+    {
+        return Err(format!(
+            "Unknown property '{}' in @font-face, expected one of: {}",
+            key,
+            PROPERTY_NAMES.join(", ")
+        )
+        .into());
+    }
+
+    let family = required_property(&properties, "family")?
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_owned();
+
+    let src = required_property(&properties, "src")?;
+    let path = parse_url(src)?;
+    let path = context
+        .resolve_relative(path)
+        .to_string_lossy()
+        .into_owned();
+
+    let weight = match properties.get("weight") {
+        Some(weight) => parse_weight_range(weight)?,
+        None => (DEFAULT_FONT_WEIGHT, DEFAULT_FONT_WEIGHT),
+    };
+
+    let style = match properties.get("style") {
+        Some(style) => parse_font_style(style)?,
+        None => FontStyle::Normal,
+    };
+
+    let index = match properties.get("index") {
+        Some(index) => index.trim().parse::<u32>()?,
+        None => 0,
+    };
+
+    styles.register_font_face(FontFace {
+        family,
+        path,
+        index,
+        weight,
+        style,
+    });
+
+    Ok(())
+}
+
+fn required_property<'a>(
+    properties: &'a HashMap<String, String>,
+    key: &str,
+) -> StyleParseResult<&'a str> {
+    properties
+        .get(key)
+        .map(|value| value.trim())
+        .ok_or(format!("@font-face requires the property '{}'", key).into())
+}
+
+/// Parse `url(fonts/Brand.otf)`, where the path may optionally be quoted.
+fn parse_url(src: &str) -> StyleParseResult<&str> {
+    let path = src
+        .strip_prefix("url(")
+        .and_then(|src| src.strip_suffix(')'))
+        .ok_or(format!(
+            "Expected 'src' in @font-face to be given as 'url(<path>)', got '{}'",
+            src
+        ))?;
+
+    Ok(path.trim().trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// Parse a single weight like `bold` or a range like `100 900` for variable fonts.
+fn parse_weight_range(value: &str) -> StyleParseResult<(f32, f32)> {
+    let weights = value
+        .split_whitespace()
+        .map(parse_font_weight)
+        .collect::<StyleParseResult<Vec<f32>>>()?;
+
+    match weights[..] {
+        [weight] => Ok((weight, weight)),
+        [min, max] if min <= max => Ok((min, max)),
+        _ => Err(format!(
+            "Expected 'weight' in @font-face to be a single weight or a range like '100 900', got '{}'",
+            value.trim()
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::parse_into;
+
+    use super::*;
+
+    #[test]
+    fn should_register_font_faces_relative_to_style_sheet() {
+        // Given: a style sheet at a known location declaring a variable font face
+        let src = "@font-face { family: \"Brand\"; src: url(\"fonts/Brand.ttc\"); \
+            weight: 100 900; style: italic; index: 1; }";
+        let path = std::env::temp_dir().join("letter-font-face.lst");
+        std::fs::write(&path, src).unwrap();
+
+        // When: the style sheet is parsed
+        let mut styles = DocumentStyles::new();
+        parse_into(&mut styles, src, Some(&path)).unwrap();
+
+        // Then: the font face is registered with its path resolved relative to the style sheet
+        let directory = path.canonicalize().unwrap().parent().unwrap().to_owned();
+        let expected = FontFace {
+            family: "Brand".to_owned(),
+            path: directory
+                .join(Path::new("fonts/Brand.ttc"))
+                .to_string_lossy()
+                .into_owned(),
+            index: 1,
+            weight: (100.0, 900.0),
+            style: FontStyle::Italic,
+        };
+        assert_eq!(styles.font_faces(), &[expected]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_for_invalid_font_faces() {
+        // Given: font faces missing required or using invalid properties
+        let sources = [
+            "@font-face { src: url(Brand.otf); }",
+            "@font-face { family: Brand; src: Brand.otf; }",
+            "@font-face { family: Brand; src: url(Brand.otf); weight: 900 100; }",
+            "@font-face { family: Brand; src: url(Brand.otf); colour: red; }",
+        ];
+
+        for src in sources {
+            // When: the style sheet is parsed
+            let result = parse_into(&mut DocumentStyles::new(), src, None);
+
+            // Then: an error is returned
+            assert!(result.is_err(), "Expected '{}' to be rejected", src);
+        }
+    }
+}
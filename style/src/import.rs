@@ -0,0 +1,177 @@
+//! Resolution of `@import "base.lst";` rules.
+//! Imported style sheets are resolved relative to the importing style sheet
+//! and registered before the rules of the importing style sheet, so that the latter take precedence.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pest::iterators::Pair;
+
+use document::style::DocumentStyles;
+
+use crate::result::StyleParseResult;
+use crate::{parse_with_context, Rule};
+
+/// Keeps track of the style sheets currently being parsed
+/// to resolve relative imports and to detect import cycles.
+pub(crate) struct ImportContext {
+    stack: Vec<PathBuf>,
+}
+
+impl ImportContext {
+    pub(crate) fn new(path: Option<&Path>) -> StyleParseResult<Self> {
+        let mut stack = Vec::new();
+        if let Some(path) = path {
+            stack.push(canonicalize(path)?);
+        }
+
+        Ok(Self { stack })
+    }
+
+    /// Resolve the given path relative to the style sheet currently being parsed.
+    /// Paths within style sheets without a known location are resolved relative to the working directory.
+    pub(crate) fn resolve_relative(&self, path: &str) -> PathBuf {
+        match self.stack.last().and_then(|current| current.parent()) {
+            Some(directory) => directory.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+
+    /// Name of the style sheet currently being parsed, if known.
+    pub(crate) fn file_name(&self) -> Option<String> {
+        self.stack.last().map(|path| path.display().to_string())
+    }
+
+    fn enter(&mut self, path: PathBuf) -> StyleParseResult<()> {
+        if let Some(index) = self.stack.iter().position(|p| *p == path) {
+            let cycle = self.stack[index..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| format!("'{}'", p.display()))
+                .collect::<Vec<String>>()
+                .join(" -> ");
+
+            return Err(format!("Detected an import cycle: {}", cycle).into());
+        }
+
+        self.stack.push(path);
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.stack.pop();
+    }
+}
+
+pub(crate) fn parse_import(
+    pair: Pair<Rule>,
+    styles: &mut DocumentStyles,
+    context: &mut ImportContext,
+) -> StyleParseResult<()> {
+    let import_path = pair
+        .into_inner()
+        .next()
+        .ok_or("Expected a path to import")?
+        .as_str();
+
+    let path = canonicalize(&context.resolve_relative(import_path))?;
+    let src = fs::read_to_string(&path).map_err(|e| {
+        format!(
+            "Could not read imported style sheet '{}': {}",
+            path.display(),
+            e
+        )
+    })?;
+
+    context.enter(path)?;
+    let result = parse_with_context(styles, &src, context);
+    context.leave();
+
+    result
+}
+
+fn canonicalize(path: &Path) -> StyleParseResult<PathBuf> {
+    fs::canonicalize(path)
+        .map_err(|e| format!("Could not find style sheet '{}': {}", path.display(), e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use document::style::{NodeName, Style, StyleResolvingContext};
+    use unit::{Distance, DistanceUnit};
+
+    use crate::parse_file_into;
+
+    use super::*;
+
+    fn write_style_sheets(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            env::temp_dir().join(format!("letter-style-{}-{}", test_name, std::process::id()));
+        fs::create_dir_all(directory.join("theme")).unwrap();
+
+        for (name, src) in files {
+            fs::write(directory.join(name), src).unwrap();
+        }
+
+        directory
+    }
+
+    #[test]
+    fn should_register_imported_rules_before_own_rules() {
+        // Given: a style sheet importing another one relative to its own location
+        let directory = write_style_sheets(
+            "layering",
+            &[
+                (
+                    "document.lst",
+                    "@import \"theme/base.lst\"; paragraph { font.size: 12pt; }",
+                ),
+                (
+                    "theme/base.lst",
+                    "paragraph { font.size: 10pt; margin.top: 1cm; }",
+                ),
+            ],
+        );
+
+        // When: the style sheet is parsed
+        let mut styles = DocumentStyles::new();
+        parse_file_into(&mut styles, &directory.join("document.lst")).unwrap();
+
+        // Then: the rules of the importing style sheet come last and thus override the imported ones
+        let node_name: NodeName = "paragraph".into();
+        let font_size = styles
+            .resolve(&node_name, None, StyleResolvingContext::default())
+            .into_iter()
+            .rev()
+            .find_map(|style| match style {
+                Style::FontSize(distance) => distance.as_absolute(),
+                _ => None,
+            });
+        assert_eq!(font_size, Some(Distance::new(12.0, DistanceUnit::Points)));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn should_detect_import_cycles() {
+        // Given: style sheets importing each other
+        let directory = write_style_sheets(
+            "cycle",
+            &[
+                ("a.lst", "@import \"theme/b.lst\";"),
+                ("theme/b.lst", "@import \"../a.lst\";"),
+            ],
+        );
+
+        // When: one of the style sheets is parsed
+        let result = parse_file_into(&mut DocumentStyles::new(), &directory.join("a.lst"));
+
+        // Then: the cycle is reported
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("import cycle"), "{}", message);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
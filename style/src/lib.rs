@@ -3,20 +3,44 @@ extern crate pest;
 extern crate pest_derive;
 
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
+use pest::error::LineColLocation;
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 
+use document::diagnostic::Diagnostic;
+use document::structure::SourcePosition;
 use document::style::{
     ClassName, DocumentStyles, FontFamilySource, FontFamilyType, FontStyle, FontVariation,
-    FontVariationSettings, NodeName, PseudoClass, Style, StyleDefinition, TextAlignment,
+    FontVariationSettings, FontWeight, NodeName, PseudoClass, Style, StyleDefinition,
+    StyleDistance, TextAlignment, TextDecoration, TextPosition, TextTransform, WhiteSpace,
 };
 use unit::{Distance, DistanceUnit};
 
+use crate::calc::{parse_calc_distance, CALC_FUNCTION_PREFIX};
+use crate::color::parse_color;
+use crate::font_face::parse_font_face;
+use crate::import::{parse_import, ImportContext};
 use crate::result::StyleParseResult;
+use crate::shorthand::expand_shorthand;
+use crate::validation::{into_diagnostic, property_names};
+pub use crate::variables::{resolve_variables, substitute_variables};
 
+mod calc;
 mod color;
+mod font_face;
+mod import;
 mod result;
+mod shorthand;
+mod validation;
+mod variables;
+
+const PROPERTY_KEYS: [&str; 5] = ["size", "margin", "padding", "font", "inline"];
+const DISTANCE_UNITS: [&str; 11] = [
+    "mm", "cm", "dm", "m", "in", "pt", "em", "rem", "ex", "ch", "%",
+];
 
 #[derive(Parser)]
 #[grammar = "lst.pest"]
@@ -24,23 +48,108 @@ pub struct LetterStyleParser;
 
 pub fn parse(src: &str) -> StyleParseResult<DocumentStyles> {
     let mut styles = DocumentStyles::new();
+    parse_into(&mut styles, src, None)?;
+
+    Ok(styles)
+}
+
+/// Parse the given style sheet and register its rules in addition to the already present ones.
+/// Rules registered later take precedence, which allows layering several style sheets,
+/// for example defaults, then a corporate theme, then document specific overrides.
+/// Imports are resolved relative to the given path of the style sheet, if any.
+pub fn parse_into(
+    styles: &mut DocumentStyles,
+    src: &str,
+    path: Option<&Path>,
+) -> StyleParseResult<()> {
+    let mut context = ImportContext::new(path)?;
+
+    parse_with_context(styles, src, &mut context)
+}
+
+/// Read the style sheet at the given path and register its rules (see `parse_into`).
+pub fn parse_file_into(styles: &mut DocumentStyles, path: &Path) -> StyleParseResult<()> {
+    let src = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read style sheet '{}': {}", path.display(), e))?;
 
-    let pairs = LetterStyleParser::parse(Rule::Stylesheet, src)?;
+    parse_into(styles, &src, Some(path))
+}
+
+pub(crate) fn parse_with_context(
+    styles: &mut DocumentStyles,
+    src: &str,
+    context: &mut ImportContext,
+) -> StyleParseResult<()> {
+    let pairs =
+        LetterStyleParser::parse(Rule::Stylesheet, src).map_err(|e| syntax_error(e, context))?;
     for pair in pairs {
         if let Rule::Stylesheet = pair.as_rule() {
-            parse_stylesheet(pair.into_inner(), &mut styles)?;
+            validation::validate(pair.clone().into_inner(), context.file_name())?;
+            parse_stylesheet(pair.into_inner(), styles, context)?;
         }
     }
 
-    Ok(styles)
+    Ok(())
+}
+
+fn syntax_error(error: pest::error::Error<Rule>, context: &ImportContext) -> Diagnostic {
+    let (line, column) = match error.line_col {
+        LineColLocation::Pos(position) => position,
+        LineColLocation::Span(start, _) => start,
+    };
+
+    Diagnostic::new(format!("Syntax error: {}", error.variant.message()))
+        .with_file(context.file_name())
+        .at(SourcePosition::new(line, column))
 }
 
-fn parse_stylesheet(pairs: Pairs<Rule>, styles: &mut DocumentStyles) -> StyleParseResult<()> {
+/// Parse a property that has been deferred due to variable references in its value.
+/// All variables are substituted using the given lookup before parsing.
+pub fn parse_deferred_style<F>(key: &str, value: &str, lookup: &F) -> StyleParseResult<Vec<Style>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = Vec::new();
+
+    let value = substitute_variables(value, lookup)?;
+    let pairs = LetterStyleParser::parse(Rule::Value, &value)?;
+    for pair in pairs {
+        parse_styles_from_value(key, pair, &mut result)?;
+    }
+
+    Ok(result)
+}
+
+fn parse_stylesheet(
+    pairs: Pairs<Rule>,
+    styles: &mut DocumentStyles,
+    context: &mut ImportContext,
+) -> StyleParseResult<()> {
     for pair in pairs {
-        if let Rule::Block = pair.as_rule() {
-            parse_block(pair.into_inner(), styles)?;
+        let span = pair.as_span();
+        let result = match pair.as_rule() {
+            Rule::Import => parse_import(pair, styles, context),
+            Rule::Definitions => parse_definitions(pair.into_inner(), styles),
+            Rule::FontFace => parse_font_face(pair.into_inner(), styles, context),
+            Rule::Block => parse_block(pair.into_inner(), styles),
+            _ => Ok(()),
+        };
+
+        result.map_err(|e| into_diagnostic(e, span, context.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Custom properties declared in a `@define` block are registered on the
+/// document root and are thus inherited by every node.
+fn parse_definitions(pairs: Pairs<Rule>, styles: &mut DocumentStyles) -> StyleParseResult<()> {
+    for pair in pairs {
+        if let Rule::UnnamedBlock = pair.as_rule() {
+            let style_definition = parse_style_definition(pair.into_inner())?;
+            styles.register_root_style_definition(style_definition);
         }
     }
+
     Ok(())
 }
 
@@ -96,7 +205,7 @@ fn parse_styles_from_key_value_pair(pairs: Pairs<Rule>) -> StyleParseResult<Vec<
             }
             Rule::Value => {
                 if let Some(key) = &key {
-                    parse_styles_from_value(key, pair.into_inner(), &mut result)?;
+                    parse_styles_from_value_pair(key, pair, &mut result)?;
                 }
             }
             _ => unreachable!(),
@@ -106,23 +215,105 @@ fn parse_styles_from_key_value_pair(pairs: Pairs<Rule>) -> StyleParseResult<Vec<
     Ok(result)
 }
 
+fn parse_styles_from_value_pair(
+    key: &str,
+    pair: Pair<Rule>,
+    result: &mut Vec<Style>,
+) -> StyleParseResult<()> {
+    let value = pair.as_str();
+
+    if variables::is_custom_property(key) {
+        let value = value.strip_suffix(";").ok_or(format!(
+            "Custom property '{}' must have a simple value",
+            key
+        ))?;
+
+        result.push(Style::CustomProperty {
+            name: key.to_owned(),
+            value: value.trim().to_owned(),
+        });
+    } else if variables::contains_variable_references(value) {
+        let property_key = key.split_once('.').map_or(key, |(key, _)| key);
+        if !PROPERTY_KEYS.contains(&property_key) {
+            return Err(format!("Property with key '{}' is currently not supported", key).into());
+        }
+
+        result.push(Style::Deferred {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        });
+    } else {
+        parse_styles_from_value(key, pair, result)?;
+    }
+
+    Ok(())
+}
+
 fn parse_styles_from_value(
     key: &str,
-    pairs: Pairs<Rule>,
+    pair: Pair<Rule>,
     result: &mut Vec<Style>,
 ) -> StyleParseResult<()> {
+    let (key, properties) = match key.split_once('.') {
+        Some((key, property)) => (key, parse_dotted_key_to_map(key, property, pair)?),
+        None => (key, parse_value_to_map(key, pair)?),
+    };
+
     match key {
-        "size" => parse_size_styles(parse_unnamed_block_to_map(pairs)?, result)?,
-        "margin" => parse_margin_styles(parse_unnamed_block_to_map(pairs)?, result)?,
-        "padding" => parse_padding_styles(parse_unnamed_block_to_map(pairs)?, result)?,
-        "font" => parse_font_styles(parse_unnamed_block_to_map(pairs)?, result)?,
-        "inline" => parse_inline_styles(parse_unnamed_block_to_map(pairs)?, result)?,
-        _ => Err(format!("Property with key '{}' is currently not supported", key).to_owned())?,
+        "size" => parse_size_styles(properties, result)?,
+        "margin" => parse_margin_styles(properties, result)?,
+        "padding" => parse_padding_styles(properties, result)?,
+        "font" => parse_font_styles(properties, result)?,
+        "inline" => parse_inline_styles(properties, result)?,
+        _ => unreachable!(),
     }
 
     Ok(())
 }
 
+/// Parse either the block notation (`margin: { top: 1cm; }`) or
+/// the shorthand notation (`margin: 1cm 2cm;`) of a property.
+fn parse_value_to_map(key: &str, pair: Pair<Rule>) -> StyleParseResult<HashMap<String, String>> {
+    if !PROPERTY_KEYS.contains(&key) {
+        return Err(format!("Property with key '{}' is currently not supported", key).into());
+    }
+
+    let value = pair.as_str();
+    match value.strip_suffix(";") {
+        Some(value) => expand_shorthand(key, value),
+        None => parse_unnamed_block_to_map(pair.into_inner()),
+    }
+}
+
+/// Parse a flat dotted key like `font.size: 12pt;`.
+fn parse_dotted_key_to_map(
+    key: &str,
+    property: &str,
+    pair: Pair<Rule>,
+) -> StyleParseResult<HashMap<String, String>> {
+    let property_names = property_names(key).ok_or(format!(
+        "Property with key '{}' is currently not supported",
+        key
+    ))?;
+    if !property_names.contains(&property) {
+        return Err(format!(
+            "Unknown property '{}' in '{}.{}', expected one of: {}",
+            property,
+            key,
+            property,
+            property_names.join(", ")
+        )
+        .into());
+    }
+
+    let value = pair.as_str().strip_suffix(";").ok_or(format!(
+        "Property '{}.{}' must have a simple value like '{}.{}: ...;'",
+        key, property, key, property
+    ))?;
+
+    Ok(HashMap::from([(property.to_owned(), value.to_owned())]))
+}
+
 fn parse_inline_styles(
     properties: HashMap<String, String>,
     result: &mut Vec<Style>,
@@ -142,9 +333,79 @@ fn parse_inline_styles(
         result.push(Style::FirstLineIndent(distance));
     }
 
+    if properties.contains_key("letter-spacing") {
+        let distance = parse_distance_property(&properties, "letter-spacing")?;
+        result.push(Style::LetterSpacing(distance));
+    }
+
+    if properties.contains_key("word-spacing") {
+        let number = parse_number_property(&properties, "word-spacing")?;
+        result.push(Style::WordSpacing(number));
+    }
+
+    if properties.contains_key("text-transform") {
+        let transform = parse_text_transform_property(&properties, "text-transform")?;
+        result.push(Style::TextTransform(transform));
+    }
+
+    if let Some(value) = properties.get("white-space") {
+        let white_space = match value.trim() {
+            "normal" => WhiteSpace::Normal,
+            "pre" => WhiteSpace::Pre,
+            "pre-wrap" => WhiteSpace::PreWrap,
+            _ => {
+                return Err(format!(
+                    "Invalid value '{}' for property 'white-space'",
+                    value.trim()
+                )
+                .into())
+            }
+        };
+        result.push(Style::WhiteSpace(white_space));
+    }
+
     Ok(())
 }
 
+fn parse_text_transform_property(
+    properties: &HashMap<String, String>,
+    key: &str,
+) -> StyleParseResult<TextTransform> {
+    if let Some(value) = properties.get(key) {
+        let transform = match value.trim() {
+            "none" => TextTransform::None,
+            "uppercase" => TextTransform::Uppercase,
+            "lowercase" => TextTransform::Lowercase,
+            "capitalize" => TextTransform::Capitalize,
+            "small-caps" => TextTransform::SmallCaps,
+            _ => return Err(format!("Invalid value '{}' for property '{}'", value, key).into()),
+        };
+
+        return Ok(transform);
+    }
+
+    Err(format!("No value for property '{}' defined", key).into())
+}
+
+fn parse_text_decoration_property(
+    properties: &HashMap<String, String>,
+    key: &str,
+) -> StyleParseResult<TextDecoration> {
+    if let Some(value) = properties.get(key) {
+        let decoration = match value.trim() {
+            "none" => TextDecoration::None,
+            "underline" => TextDecoration::Underline,
+            "strike-through" => TextDecoration::StrikeThrough,
+            "overline" => TextDecoration::Overline,
+            _ => return Err(format!("Invalid value '{}' for property '{}'", value, key).into()),
+        };
+
+        return Ok(decoration);
+    }
+
+    Err(format!("No value for property '{}' defined", key).into())
+}
+
 fn parse_alignment_property(
     properties: &HashMap<String, String>,
     key: &str,
@@ -172,7 +433,7 @@ fn parse_alignment_property(
 
 fn parse_number_property(properties: &HashMap<String, String>, key: &str) -> StyleParseResult<f64> {
     if let Some(value) = properties.get(key) {
-        let number = value.parse::<f64>()?;
+        let number = value.trim().parse::<f64>()?;
 
         return Ok(number);
     }
@@ -182,7 +443,9 @@ fn parse_number_property(properties: &HashMap<String, String>, key: &str) -> Sty
         .into())
 }
 
-fn parse_unnamed_block_to_map(pairs: Pairs<Rule>) -> StyleParseResult<HashMap<String, String>> {
+pub(crate) fn parse_unnamed_block_to_map(
+    pairs: Pairs<Rule>,
+) -> StyleParseResult<HashMap<String, String>> {
     let mut result = HashMap::new();
 
     for pair in pairs {
@@ -328,22 +591,15 @@ fn parse_font_styles(
                         .to_owned();
                     result.push(Style::FontFamily(FontFamilySource::Path(url)));
                 } else {
-                    result.push(Style::FontFamily(FontFamilySource::Name(family.to_owned())));
+                    let name = family.trim_matches(|c| c == '"' || c == '\'');
+                    result.push(Style::FontFamily(FontFamilySource::Name(name.to_owned())));
                 }
             }
         };
     }
 
     if properties.contains_key("weight") {
-        let weight = properties.get("weight").unwrap().as_str().trim();
-        let weight = match weight {
-            "normal" => 400.0,
-            "bold" => 700.0,
-            "bolder" => 900.0,
-            "light" => 300.0,
-            "lighter" => 100.0,
-            _ => weight.parse::<f32>()?,
-        };
+        let weight = parse_font_weight(properties.get("weight").unwrap())?;
         result.push(Style::FontWeight(weight));
         font_weight = Some(weight);
     }
@@ -355,16 +611,8 @@ fn parse_font_styles(
     }
 
     if properties.contains_key("style") {
-        let style = properties.get("style").unwrap().as_str().trim();
-
-        match style {
-            "normal" => result.push(Style::FontStyle(FontStyle::Normal)),
-            "italic" => result.push(Style::FontStyle(FontStyle::Italic)),
-            "oblique" => result.push(Style::FontStyle(FontStyle::Oblique)),
-            _ => {
-                return Err(format!("Unknown font style '{}'", style).to_owned().into());
-            }
-        };
+        let style = parse_font_style(properties.get("style").unwrap())?;
+        result.push(Style::FontStyle(style));
     }
 
     if properties.contains_key("variation-settings") {
@@ -413,24 +661,64 @@ fn parse_font_styles(
         }));
     }
 
+    if let Some(value) = properties.get("position") {
+        let position = match value.trim() {
+            "normal" => TextPosition::Normal,
+            "super" => TextPosition::Superscript,
+            "sub" => TextPosition::Subscript,
+            _ => {
+                return Err(
+                    format!("Invalid value '{}' for property 'position'", value.trim()).into(),
+                )
+            }
+        };
+        result.push(Style::TextPosition(position));
+    }
+
+    if let Some(value) = properties.get("color") {
+        result.push(Style::Color(parse_color(value)?));
+    }
+
+    if properties.contains_key("decoration") {
+        let decoration = parse_text_decoration_property(&properties, "decoration")?;
+        result.push(Style::TextDecoration(decoration));
+    }
+
+    if let Some(value) = properties.get("decoration-color") {
+        result.push(Style::TextDecorationColor(parse_color(value)?));
+    }
+
     Ok(())
 }
 
+pub(crate) fn parse_font_weight(value: &str) -> StyleParseResult<FontWeight> {
+    let weight = match value.trim() {
+        "normal" => 400.0,
+        "bold" => 700.0,
+        "bolder" => 900.0,
+        "light" => 300.0,
+        "lighter" => 100.0,
+        weight => weight.parse::<f32>()?,
+    };
+
+    Ok(weight)
+}
+
+pub(crate) fn parse_font_style(value: &str) -> StyleParseResult<FontStyle> {
+    match value.trim() {
+        "normal" => Ok(FontStyle::Normal),
+        "italic" => Ok(FontStyle::Italic),
+        "oblique" => Ok(FontStyle::Oblique),
+        style => Err(format!("Unknown font style '{}'", style).into()),
+    }
+}
+
 fn parse_distance_property(
     properties: &HashMap<String, String>,
     key: &str,
-) -> StyleParseResult<Distance> {
+) -> StyleParseResult<StyleDistance> {
     if let Some(value) = properties.get(key) {
-        let (number_str, _) = value
-            .split_once(|c: char| c.is_alphabetic())
-            .ok_or("No unit defined for width")?;
-        let number = number_str.parse::<f64>()?;
-        let unit = value
-            .strip_prefix(number_str)
-            .ok_or("No unit defined for width")?;
-        let unit = DistanceUnit::from_shortform(&unit).ok_or("No unit defined for width")?;
-
-        return Ok(Distance::new(number, unit));
+        return parse_distance(value);
     }
 
     Err(format!("No value for property '{}' defined", key)
@@ -438,6 +726,41 @@ fn parse_distance_property(
         .into())
 }
 
+pub(crate) fn parse_distance(value: &str) -> StyleParseResult<StyleDistance> {
+    let value = value.trim();
+    if value.starts_with(CALC_FUNCTION_PREFIX) {
+        return parse_calc_distance(value);
+    }
+
+    let (number_str, _) = value
+        .split_once(|c: char| c.is_alphabetic() || c == '%')
+        .ok_or(format!("Missing unit in distance '{}'", value))?;
+    let number = number_str
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid number in distance '{}'", value))?;
+    let unit = &value[number_str.len()..];
+
+    let distance = match unit {
+        "em" => StyleDistance::Em(number),
+        "rem" => StyleDistance::Rem(number),
+        "ex" => StyleDistance::Ex(number),
+        "ch" => StyleDistance::Ch(number),
+        "%" => StyleDistance::Percent(number),
+        _ => {
+            let unit = DistanceUnit::from_shortform(unit).ok_or(format!(
+                "Unknown unit '{}' in distance '{}', expected one of: {}",
+                unit,
+                value,
+                DISTANCE_UNITS.join(", ")
+            ))?;
+            StyleDistance::Absolute(Distance::new(number, unit))
+        }
+    };
+
+    Ok(distance)
+}
+
 fn parse_selector(pairs: Pairs<Rule>) -> StyleParseResult<Selector> {
     let selectables = pairs
         .map(|pair| parse_selectable(pair.into_inner()))
@@ -514,3 +837,70 @@ struct Selectable {
     class_name: Option<ClassName>,
     pseudo_class: Option<PseudoClass>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_font_relative_distances() {
+        // Given: distances relative to the font
+        let values = ["1.5em", "2rem", "0.5ex", "3ch"];
+
+        // When: the distances are parsed
+        let distances: Vec<StyleDistance> = values
+            .iter()
+            .map(|value| parse_distance(value).unwrap())
+            .collect();
+
+        // Then: the units are kept for resolving them during layout
+        assert_eq!(
+            distances,
+            vec![
+                StyleDistance::Em(1.5),
+                StyleDistance::Rem(2.0),
+                StyleDistance::Ex(0.5),
+                StyleDistance::Ch(3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_percentage() {
+        // Given: a percentage surrounded by white space
+        let value = " 50% ";
+
+        // When: the distance is parsed
+        let distance = parse_distance(value).unwrap();
+
+        // Then: the percentage is kept
+        assert_eq!(distance, StyleDistance::Percent(50.0));
+    }
+
+    #[test]
+    fn should_parse_negative_relative_distance() {
+        // Given: a negative distance relative to the font size
+        let value = "-0.25em";
+
+        // When: the distance is parsed
+        let distance = parse_distance(value).unwrap();
+
+        // Then: the sign is kept
+        assert_eq!(distance, StyleDistance::Em(-0.25));
+    }
+
+    #[test]
+    fn should_fail_for_unknown_or_missing_unit() {
+        // Given: distances with an unknown unit or without unit
+        let values = ["2vw", "12"];
+
+        // When: the distances are parsed
+        let results: Vec<bool> = values
+            .iter()
+            .map(|value| parse_distance(value).is_err())
+            .collect();
+
+        // Then: parsing fails
+        assert_eq!(results, vec![true, true]);
+    }
+}
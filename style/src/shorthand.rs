@@ -0,0 +1,241 @@
+//! Expansion of shorthand property values like `margin: 1cm 2cm;` or `font: 600 12pt "Inter";`
+//! into the properties of the equivalent block notation.
+
+use std::collections::HashMap;
+
+use crate::parse_distance;
+use crate::result::StyleParseResult;
+
+const FONT_STYLE_KEYWORDS: [&str; 3] = ["normal", "italic", "oblique"];
+const FONT_WEIGHT_KEYWORDS: [&str; 5] = ["normal", "bold", "bolder", "light", "lighter"];
+
+/// Expand the shorthand value of the property with the given key.
+/// The result maps the properties of the block notation to their values,
+/// for example `margin: 1cm 2cm;` results in `top: 1cm`, `right: 2cm`, `bottom: 1cm` and `left: 2cm`.
+pub(crate) fn expand_shorthand(
+    key: &str,
+    value: &str,
+) -> StyleParseResult<HashMap<String, String>> {
+    let values = split_values(value)?;
+    if values.is_empty() {
+        return Err(format!("Property '{}' must not be empty", key).into());
+    }
+
+    match key {
+        "margin" | "padding" => expand_box_shorthand(key, &values),
+        "size" => expand_size_shorthand(&values),
+        "font" => expand_font_shorthand(value, &values),
+        _ => Err(format!(
+            "Property '{}' has no shorthand notation, use a block or dotted keys (for example '{}.<property>: ...;') instead",
+            key, key
+        )
+        .into()),
+    }
+}
+
+/// Expand one to four values following the CSS order top, right, bottom and left.
+fn expand_box_shorthand(key: &str, values: &[&str]) -> StyleParseResult<HashMap<String, String>> {
+    let (top, right, bottom, left) = match *values {
+        [all] => (all, all, all, all),
+        [vertical, horizontal] => (vertical, horizontal, vertical, horizontal),
+        [top, horizontal, bottom] => (top, horizontal, bottom, horizontal),
+        [top, right, bottom, left] => (top, right, bottom, left),
+        _ => {
+            return Err(format!(
+                "Shorthand property '{}' expects 1 to 4 values (top, right, bottom, left) but got {} in '{}'",
+                key,
+                values.len(),
+                values.join(" ")
+            )
+            .into())
+        }
+    };
+
+    Ok(HashMap::from([
+        ("top".to_owned(), top.to_owned()),
+        ("right".to_owned(), right.to_owned()),
+        ("bottom".to_owned(), bottom.to_owned()),
+        ("left".to_owned(), left.to_owned()),
+    ]))
+}
+
+/// Expand `size: <width> [<height>];`.
+fn expand_size_shorthand(values: &[&str]) -> StyleParseResult<HashMap<String, String>> {
+    let (width, height) = match *values {
+        [width] => (width, None),
+        [width, height] => (width, Some(height)),
+        _ => {
+            return Err(format!(
+                "Shorthand property 'size' expects 1 or 2 values (width, height) \
+                but got {} in '{}'",
+                values.len(),
+                values.join(" ")
+            )
+            .into())
+        }
+    };
+
+    let mut result = HashMap::from([("width".to_owned(), width.to_owned())]);
+    if let Some(height) = height {
+        result.insert("height".to_owned(), height.to_owned());
+    }
+
+    Ok(result)
+}
+
+/// Expand `font: [<style>] [<weight>] <size> [<family>];`.
+fn expand_font_shorthand(
+    value: &str,
+    values: &[&str],
+) -> StyleParseResult<HashMap<String, String>> {
+    let mut result = HashMap::new();
+
+    let size_index = values
+        .iter()
+        .position(|value| parse_distance(value).is_ok())
+        .ok_or(format!(
+            "Shorthand property 'font' requires a font size (for example 'font: 600 12pt \"Inter\";') in '{}'",
+            value.trim()
+        ))?;
+
+    for &value in &values[..size_index] {
+        let is_style = FONT_STYLE_KEYWORDS.contains(&value) && !result.contains_key("style");
+        let is_weight = FONT_WEIGHT_KEYWORDS.contains(&value) || value.parse::<f32>().is_ok();
+
+        let key = if is_style {
+            "style"
+        } else if is_weight {
+            "weight"
+        } else {
+            return Err(format!(
+                "Unexpected value '{}' before the font size in shorthand property 'font', expected a font style or weight",
+                value
+            )
+            .into());
+        };
+
+        if result.contains_key(key) {
+            return Err(format!(
+                "Font {} is defined more than once in shorthand property 'font'",
+                key
+            )
+            .into());
+        }
+        result.insert(key.to_owned(), value.to_owned());
+    }
+
+    result.insert("size".to_owned(), values[size_index].to_owned());
+
+    let family = &values[size_index + 1..];
+    if !family.is_empty() {
+        result.insert("family".to_owned(), family.join(" "));
+    }
+
+    Ok(result)
+}
+
+/// Split a value at whitespace that is neither within brackets nor quotes.
+fn split_values(value: &str) -> StyleParseResult<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = None;
+
+    for (index, c) in value.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                if depth == 0 {
+                    return Err(format!("Unexpected closing bracket in '{}'", value.trim()).into());
+                }
+                depth -= 1;
+            }
+            (None, c) if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    result.push(&value[start..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if start.is_none() {
+            start = Some(index);
+        }
+    }
+
+    if quote.is_some() {
+        return Err(format!("Missing closing quotation mark in '{}'", value.trim()).into());
+    }
+    if depth > 0 {
+        return Err(format!("Missing closing bracket in '{}'", value.trim()).into());
+    }
+    if let Some(start) = start {
+        result.push(&value[start..]);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_expand_box_shorthand_in_css_order() {
+        // Given: a margin shorthand with three values
+        let value = "1cm calc(100% - 2cm) 3mm";
+
+        // When: the shorthand is expanded
+        let result = expand_shorthand("margin", value).unwrap();
+
+        // Then: the horizontal value is used for left and right
+        assert_eq!(result["top"], "1cm");
+        assert_eq!(result["right"], "calc(100% - 2cm)");
+        assert_eq!(result["bottom"], "3mm");
+        assert_eq!(result["left"], "calc(100% - 2cm)");
+    }
+
+    #[test]
+    fn should_expand_font_shorthand() {
+        // Given: a font shorthand with style, weight, size and a quoted family name
+        let value = "italic 600 12pt \"Source Sans\"";
+
+        // When: the shorthand is expanded
+        let result = expand_shorthand("font", value).unwrap();
+
+        // Then: all parts are assigned to their properties
+        assert_eq!(result["style"], "italic");
+        assert_eq!(result["weight"], "600");
+        assert_eq!(result["size"], "12pt");
+        assert_eq!(result["family"], "\"Source Sans\"");
+    }
+
+    #[test]
+    fn should_fail_for_invalid_shorthands() {
+        // Given: invalid shorthand values
+        let shorthands = [
+            ("margin", "1cm 2cm 3cm 4cm 5cm"),
+            ("size", "1cm 2cm 3cm"),
+            ("font", "600 \"Inter\""),
+            ("font", "wide 12pt"),
+            ("inline", "justify"),
+        ];
+
+        for (key, value) in shorthands {
+            // When: the shorthand is expanded
+            let result = expand_shorthand(key, value);
+
+            // Then: an error is returned
+            assert!(
+                result.is_err(),
+                "Expected '{}: {};' to be rejected",
+                key,
+                value
+            );
+        }
+    }
+}
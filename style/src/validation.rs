@@ -0,0 +1,479 @@
+//! Validation pass over a parsed style sheet reporting all problems found
+//! (unknown properties, invalid values, ...) with their location in the style sheet.
+
+use std::error::Error;
+
+use pest::iterators::Pair;
+use pest::Span;
+
+use document::diagnostic::{Diagnostic, Diagnostics};
+use document::structure::SourcePosition;
+
+use crate::color::parse_color;
+use crate::result::StyleParseResult;
+use crate::shorthand::expand_shorthand;
+use crate::variables::{contains_variable_references, is_custom_property};
+use crate::{font_face, parse_distance, parse_font_weight, Rule, PROPERTY_KEYS};
+
+/// Type of the value of a property used to validate it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ValueType {
+    Distance,
+    Number,
+    FontWeight,
+    Keyword(&'static [&'static str]),
+    Color,
+    Text,
+}
+
+const ALIGNMENTS: [&str; 4] = ["left", "center", "right", "justify"];
+const FONT_STYLES: [&str; 3] = ["normal", "italic", "oblique"];
+const WHITE_SPACES: [&str; 3] = ["normal", "pre", "pre-wrap"];
+const TEXT_POSITIONS: [&str; 3] = ["normal", "super", "sub"];
+const TEXT_DECORATIONS: [&str; 4] = ["none", "underline", "strike-through", "overline"];
+const TEXT_TRANSFORMS: [&str; 5] = ["none", "uppercase", "lowercase", "capitalize", "small-caps"];
+
+/// Properties that may be given in the block (or dotted) notation of the property with the given key.
+pub(crate) fn properties_of(key: &str) -> Option<&'static [(&'static str, ValueType)]> {
+    use ValueType::*;
+
+    let properties: &[(&str, ValueType)] = match key {
+        "size" => &[("width", Distance), ("height", Distance)],
+        "margin" | "padding" => &[
+            ("top", Distance),
+            ("right", Distance),
+            ("bottom", Distance),
+            ("left", Distance),
+        ],
+        "font" => &[
+            ("size", Distance),
+            ("family", Text),
+            ("weight", FontWeight),
+            ("stretch", Number),
+            ("style", Keyword(&FONT_STYLES)),
+            ("variation-settings", Text),
+            ("position", Keyword(&TEXT_POSITIONS)),
+            ("color", Color),
+            ("decoration", Keyword(&TEXT_DECORATIONS)),
+            ("decoration-color", Color),
+        ],
+        "inline" => &[
+            ("line-height", Number),
+            ("alignment", Keyword(&ALIGNMENTS)),
+            ("first-line-indent", Distance),
+            ("letter-spacing", Distance),
+            ("word-spacing", Number),
+            ("text-transform", Keyword(&TEXT_TRANSFORMS)),
+            ("white-space", Keyword(&WHITE_SPACES)),
+        ],
+        _ => return None,
+    };
+
+    Some(properties)
+}
+
+pub(crate) fn property_names(key: &str) -> Option<Vec<&'static str>> {
+    properties_of(key).map(|properties| properties.iter().map(|(name, _)| *name).collect())
+}
+
+/// Validate the top-level rules of a style sheet.
+pub(crate) fn validate<'i>(
+    pairs: impl Iterator<Item = Pair<'i, Rule>>,
+    file: Option<String>,
+) -> StyleParseResult<()> {
+    let mut validator = Validator {
+        file,
+        diagnostics: Vec::new(),
+    };
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::Definitions => validator.validate_definitions(pair),
+            Rule::FontFace => validator.validate_font_face(pair),
+            Rule::Block => validator.validate_block(pair),
+            _ => {}
+        }
+    }
+
+    if validator.diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(Box::new(Diagnostics(validator.diagnostics)))
+    }
+}
+
+/// Attach the location of the given span to an error that does not carry a location yet.
+pub(crate) fn into_diagnostic(
+    error: Box<dyn Error>,
+    span: Span,
+    file: Option<String>,
+) -> Box<dyn Error> {
+    if error.is::<Diagnostic>() || error.is::<Diagnostics>() {
+        return error;
+    }
+
+    Box::new(
+        Diagnostic::new(error.to_string())
+            .with_file(file)
+            .at(position(span)),
+    )
+}
+
+pub(crate) fn position(span: Span) -> SourcePosition {
+    let (line, column) = span.start_pos().line_col();
+
+    SourcePosition::new(line, column)
+}
+
+struct Validator {
+    file: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn validate_definitions(&mut self, pair: Pair<Rule>) {
+        for (key, _) in key_value_pairs(pair) {
+            if !is_custom_property(key.as_str()) {
+                self.report(
+                    key.as_span(),
+                    format!(
+                        "Only custom properties (for example '--accent: #e4002b;') \
+                        may be declared in a @define block, got '{}'",
+                        key.as_str()
+                    ),
+                    None,
+                );
+            }
+        }
+    }
+
+    fn validate_font_face(&mut self, pair: Pair<Rule>) {
+        for (key, _) in key_value_pairs(pair) {
+            let name = key.as_str();
+            if !font_face::PROPERTY_NAMES.contains(&name) {
+                self.report_unknown_property(
+                    key.as_span(),
+                    name,
+                    "@font-face",
+                    &font_face::PROPERTY_NAMES,
+                );
+            }
+        }
+    }
+
+    fn validate_block(&mut self, pair: Pair<Rule>) {
+        for (key, value) in key_value_pairs(pair) {
+            self.validate_property(key, value);
+        }
+    }
+
+    fn validate_property(&mut self, key: Pair<Rule>, value: Pair<Rule>) {
+        let key_name = key.as_str();
+        let has_simple_value = is_simple_value(&value);
+
+        if is_custom_property(key_name) {
+            if !has_simple_value {
+                self.report(
+                    value.as_span(),
+                    format!("Custom property '{}' must have a simple value", key_name),
+                    None,
+                );
+            }
+            return;
+        }
+
+        let (group, property) = match key_name.split_once('.') {
+            Some((group, property)) => (group, Some(property)),
+            None => (key_name, None),
+        };
+
+        if !PROPERTY_KEYS.contains(&group) {
+            self.report_unknown_property(key.as_span(), group, "style definition", &PROPERTY_KEYS);
+            return;
+        }
+
+        match property {
+            Some(property) => {
+                if !self.validate_property_name(key.as_span(), group, property) {
+                    return;
+                }
+
+                if has_simple_value {
+                    self.validate_value(value.as_span(), group, property, simple_value(&value));
+                } else {
+                    self.report(
+                        value.as_span(),
+                        format!(
+                            "Property '{}' must have a simple value like '{}: ...;'",
+                            key_name, key_name
+                        ),
+                        None,
+                    );
+                }
+            }
+            None if has_simple_value => self.validate_shorthand(value, group),
+            None => {
+                for (key, value) in key_value_pairs(value) {
+                    if !self.validate_property_name(key.as_span(), group, key.as_str()) {
+                        continue;
+                    }
+
+                    if is_simple_value(&value) {
+                        self.validate_value(
+                            value.as_span(),
+                            group,
+                            key.as_str(),
+                            simple_value(&value),
+                        );
+                    } else {
+                        self.report(
+                            value.as_span(),
+                            format!(
+                                "Property '{}.{}' must have a simple value",
+                                group,
+                                key.as_str()
+                            ),
+                            None,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_shorthand(&mut self, value: Pair<Rule>, group: &str) {
+        let value_str = simple_value(&value);
+        if contains_variable_references(value_str) {
+            return;
+        }
+
+        match expand_shorthand(group, value_str) {
+            Ok(properties) => {
+                let mut properties: Vec<(String, String)> = properties.into_iter().collect();
+                properties.sort();
+
+                for (property, property_value) in properties {
+                    self.validate_value(value.as_span(), group, &property, &property_value);
+                }
+            }
+            Err(e) => self.report(value.as_span(), e.to_string(), None),
+        }
+    }
+
+    /// Returns whether the property name is known.
+    fn validate_property_name(&mut self, span: Span, group: &str, property: &str) -> bool {
+        let names = property_names(group).unwrap_or_default();
+        if names.contains(&property) {
+            return true;
+        }
+
+        self.report_unknown_property(span, property, &format!("'{}'", group), &names);
+        false
+    }
+
+    fn validate_value(&mut self, span: Span, group: &str, property: &str, value: &str) {
+        if contains_variable_references(value) {
+            return;
+        }
+
+        let value_type = properties_of(group)
+            .and_then(|properties| properties.iter().find(|(name, _)| *name == property))
+            .map(|(_, value_type)| *value_type);
+        let value = value.trim();
+
+        let result: Result<(), (String, Option<String>)> = match value_type {
+            Some(ValueType::Distance) => parse_distance(value)
+                .map(|_| ())
+                .map_err(|e| (e.to_string(), None)),
+            Some(ValueType::Number) => value
+                .parse::<f64>()
+                .map(|_| ())
+                .map_err(|_| (format!("Expected a number but got '{}'", value), None)),
+            Some(ValueType::FontWeight) => parse_font_weight(value).map(|_| ()).map_err(|_| {
+                (
+                    format!(
+                        "Expected a number or one of: normal, bold, bolder, light, lighter but got '{}'",
+                        value
+                    ),
+                    None,
+                )
+            }),
+            Some(ValueType::Keyword(keywords)) => check_keyword(value, keywords),
+            Some(ValueType::Color) => parse_color(value)
+                .map(|_| ())
+                .map_err(|e| (e.to_string(), None)),
+            Some(ValueType::Text) | None => Ok(()),
+        };
+
+        if let Err((message, help)) = result {
+            self.report(
+                span,
+                format!("Invalid value for '{}.{}': {}", group, property, message),
+                help,
+            );
+        }
+    }
+
+    fn report_unknown_property(&mut self, span: Span, name: &str, scope: &str, known: &[&str]) {
+        self.report(
+            span,
+            format!("Unknown property '{}' in {}", name, scope),
+            Some(match suggest(name, known) {
+                Some(suggestion) => format!("did you mean '{}'?", suggestion),
+                None => format!("expected one of: {}", known.join(", ")),
+            }),
+        );
+    }
+
+    fn report(&mut self, span: Span, message: String, help: Option<String>) {
+        self.diagnostics.push(
+            Diagnostic::new(message)
+                .with_file(self.file.clone())
+                .at(position(span))
+                .with_help(help),
+        );
+    }
+}
+
+fn check_keyword(value: &str, keywords: &[&str]) -> Result<(), (String, Option<String>)> {
+    if keywords.contains(&value) {
+        return Ok(());
+    }
+
+    Err((
+        format!(
+            "Expected one of: {} but got '{}'",
+            keywords.join(", "),
+            value
+        ),
+        suggest(value, keywords).map(|suggestion| format!("did you mean '{}'?", suggestion)),
+    ))
+}
+
+/// Key and value pairs of the unnamed block contained in the given pair.
+fn key_value_pairs(pair: Pair<Rule>) -> Vec<(Pair<Rule>, Pair<Rule>)> {
+    pair.into_inner()
+        .filter(|pair| pair.as_rule() == Rule::UnnamedBlock)
+        .flat_map(|block| block.into_inner())
+        .filter(|pair| pair.as_rule() == Rule::KeyValuePair)
+        .filter_map(|pair| {
+            let mut inner = pair.into_inner();
+            Some((inner.next()?, inner.next()?))
+        })
+        .collect()
+}
+
+fn is_simple_value(value: &Pair<Rule>) -> bool {
+    value.as_str().ends_with(';')
+}
+
+fn simple_value<'i>(value: &Pair<'i, Rule>) -> &'i str {
+    value.as_str().strip_suffix(';').unwrap_or(value.as_str())
+}
+
+/// Find the candidate closest to the given (probably misspelled) name.
+fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between the given strings, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters (optimal string alignment).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j - 1] + substitution_cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+
+            let is_transposition = i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1];
+            if is_transposition {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    fn diagnostics_for(src: &str) -> String {
+        parse(src)
+            .err()
+            .expect("Expected style sheet to be invalid")
+            .to_string()
+    }
+
+    #[test]
+    fn should_suggest_similar_property_names() {
+        // Given: a style sheet with a misspelled property in a block
+        let src = "paragraph {\n  margin: {\n    tpo: 1cm;\n  }\n}";
+
+        // When: the style sheet is parsed
+        let message = diagnostics_for(src);
+
+        // Then: the property is reported at its location with a suggestion
+        assert_eq!(
+            message,
+            "3:5: Unknown property 'tpo' in 'margin'\n  help: did you mean 'top'?"
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_values_with_property_name() {
+        // Given: a style sheet with a distance without unit for the height
+        let src = "image { size.height: 3; }";
+
+        // When: the style sheet is parsed
+        let message = diagnostics_for(src);
+
+        // Then: the error refers to the height rather than any other property
+        assert_eq!(
+            message,
+            "1:22: Invalid value for 'size.height': Missing unit in distance '3'"
+        );
+    }
+
+    #[test]
+    fn should_report_all_problems_at_once() {
+        // Given: a style sheet with several problems
+        let src = "heading { colour: red; font: { style: italc; size: 12xy; } }";
+
+        // When: the style sheet is parsed
+        let message = diagnostics_for(src);
+
+        // Then: all problems are reported
+        assert_eq!(
+            message
+                .lines()
+                .filter(|line| !line.starts_with(' '))
+                .count(),
+            3
+        );
+        assert!(message.contains("did you mean 'italic'?"), "{}", message);
+        assert!(message.contains("Unknown unit 'xy'"), "{}", message);
+    }
+}
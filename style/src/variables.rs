@@ -0,0 +1,328 @@
+//! Substitution of variable references in style values.
+//! A variable is either referenced by `$name` or by `var(--name)`,
+//! where the latter may specify a fallback value: `var(--name, 12pt)`.
+
+use std::collections::HashMap;
+
+use document::meta_data::DocumentVariables;
+use document::style::Style;
+
+use crate::parse_deferred_style;
+use crate::result::StyleParseResult;
+
+const CUSTOM_PROPERTY_PREFIX: &str = "--";
+const VAR_FUNCTION_PREFIX: &str = "var(";
+
+/// Resolve the custom property declarations and deferred properties of a node.
+/// Custom properties are added to the (inherited) custom properties of the node first,
+/// so that the order of declarations within a style definition does not matter.
+/// The returned styles no longer contain any custom property or deferred property.
+pub fn resolve_variables(
+    styles: &[&Style],
+    custom_properties: &mut HashMap<String, String>,
+    variables: &DocumentVariables,
+) -> StyleParseResult<Vec<Style>> {
+    for style in styles {
+        if let Style::CustomProperty { name, value } = style {
+            let value = substitute_variables(value, &|name: &str| {
+                lookup_variable(name, custom_properties, variables)
+            })?;
+
+            custom_properties.insert(name.to_owned(), value);
+        }
+    }
+
+    let mut result = Vec::new();
+    for style in styles {
+        match style {
+            Style::CustomProperty { .. } => {}
+            Style::Deferred { key, value } => {
+                let styles = parse_deferred_style(key, value, &|name: &str| {
+                    lookup_variable(name, custom_properties, variables)
+                })?;
+
+                result.extend(styles);
+            }
+            _ => result.push((*style).clone()),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Custom properties take precedence over variables defined in the documents meta data.
+fn lookup_variable(
+    name: &str,
+    custom_properties: &HashMap<String, String>,
+    variables: &DocumentVariables,
+) -> Option<String> {
+    custom_properties
+        .get(&format!("{}{}", CUSTOM_PROPERTY_PREFIX, name))
+        .cloned()
+        .or_else(|| variables.get(name))
+}
+
+pub(crate) fn is_custom_property(key: &str) -> bool {
+    key.len() > CUSTOM_PROPERTY_PREFIX.len() && key.starts_with(CUSTOM_PROPERTY_PREFIX)
+}
+
+pub(crate) fn contains_variable_references(value: &str) -> bool {
+    find_next_reference(value).is_some()
+}
+
+/// Replace all variable references in the given value.
+/// The lookup is called with the bare variable name, for example `accent`
+/// for both `$accent` and `var(--accent)`.
+pub fn substitute_variables<F>(value: &str, lookup: &F) -> StyleParseResult<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(index) = find_next_reference(rest) {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(after_dollar) = rest.strip_prefix('$') {
+            let name_length = after_dollar
+                .find(|c: char| !is_name_character(c))
+                .unwrap_or(after_dollar.len());
+            let name = &after_dollar[..name_length];
+            if name.is_empty() {
+                return Err("Expected a variable name after '$'".into());
+            }
+
+            let value = lookup(name).ok_or(format!("Variable '${}' is not defined", name))?;
+            result.push_str(&value);
+
+            rest = &after_dollar[name_length..];
+        } else {
+            let arguments_start = VAR_FUNCTION_PREFIX.len();
+            let arguments_end = find_closing_bracket(&rest[arguments_start..])
+                .ok_or("Missing closing bracket for 'var('")?
+                + arguments_start;
+
+            let value = substitute_var_function(&rest[arguments_start..arguments_end], lookup)?;
+            result.push_str(&value);
+
+            rest = &rest[arguments_end + 1..];
+        }
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn substitute_var_function<F>(arguments: &str, lookup: &F) -> StyleParseResult<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let (name, fallback) = match arguments.split_once(',') {
+        Some((name, fallback)) => (name, Some(fallback.trim())),
+        None => (arguments, None),
+    };
+    let name = name
+        .trim()
+        .strip_prefix(CUSTOM_PROPERTY_PREFIX)
+        .ok_or(format!(
+            "Expected a custom property name starting with '{}' in 'var({})'",
+            CUSTOM_PROPERTY_PREFIX, arguments
+        ))?;
+
+    match (lookup(name), fallback) {
+        (Some(value), _) => Ok(value),
+        (None, Some(fallback)) => substitute_variables(fallback, lookup),
+        (None, None) => Err(format!(
+            "Variable '{}{}' is not defined",
+            CUSTOM_PROPERTY_PREFIX, name
+        )
+        .into()),
+    }
+}
+
+fn find_next_reference(value: &str) -> Option<usize> {
+    let dollar_index = value.find('$');
+    let var_function_index = value
+        .match_indices(VAR_FUNCTION_PREFIX)
+        .map(|(index, _)| index)
+        .find(|index| !matches!(value[..*index].chars().last(), Some(c) if is_name_character(c)));
+
+    match (dollar_index, var_function_index) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Find the index of the bracket closing an already opened bracket.
+fn find_closing_bracket(value: &str) -> Option<usize> {
+    let mut depth = 1;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn is_name_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use document::style::TextDecoration;
+
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "accent" => Some("#e4002b".to_owned()),
+            "body-size" => Some("11pt".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn should_substitute_dollar_references() {
+        // Given: a value referencing a variable using the dollar sign
+        let value = "$body-size;";
+
+        // When: substituting the variables
+        let result = substitute_variables(value, &lookup).unwrap();
+
+        // Then: the reference is replaced by the variables value
+        assert_eq!(result, "11pt;");
+    }
+
+    #[test]
+    fn should_substitute_var_function_references() {
+        // Given: a block value referencing a variable using the var function
+        let value = "{ size: var(--body-size); }";
+
+        // When: substituting the variables
+        let result = substitute_variables(value, &lookup).unwrap();
+
+        // Then: the reference is replaced by the variables value
+        assert_eq!(result, "{ size: 11pt; }");
+    }
+
+    #[test]
+    fn should_use_fallback_for_undefined_variables() {
+        // Given: a value referencing an undefined variable with a fallback
+        let value = "var(--unknown, var(--accent))";
+
+        // When: substituting the variables
+        let result = substitute_variables(value, &lookup).unwrap();
+
+        // Then: the fallback is used
+        assert_eq!(result, "#e4002b");
+    }
+
+    #[test]
+    fn should_fail_for_undefined_variables() {
+        // Given: values referencing an undefined variable without a fallback
+        let values = ["$unknown", "var(--unknown)"];
+
+        for value in values {
+            // When: substituting the variables
+            let result = substitute_variables(value, &lookup);
+
+            // Then: an error is returned
+            assert!(result.is_err());
+        }
+    }
+
+    fn custom_property(name: &str, value: &str) -> Style {
+        Style::CustomProperty {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    fn deferred(key: &str, value: &str) -> Style {
+        Style::Deferred {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_resolve_deferred_styles_declared_before_custom_property() {
+        // Given: a deferred style followed by the custom property it references
+        let styles = [
+            deferred("font.decoration", "var(--decoration);"),
+            custom_property("--decoration", "underline"),
+        ];
+        let styles: Vec<&Style> = styles.iter().collect();
+
+        // When: resolving the variables
+        let mut custom_properties = HashMap::new();
+        let result =
+            resolve_variables(&styles, &mut custom_properties, &DocumentVariables::new()).unwrap();
+
+        // Then: the deferred style is parsed and the custom property is remembered
+        assert!(matches!(
+            result.as_slice(),
+            [Style::TextDecoration(TextDecoration::Underline)]
+        ));
+        assert_eq!(
+            custom_properties.get("--decoration").map(String::as_str),
+            Some("underline")
+        );
+    }
+
+    #[test]
+    fn should_prefer_custom_properties_over_document_variables() {
+        // Given: a document variable and an inherited custom property of the same name
+        let mut variables = DocumentVariables::new();
+        variables.set("decoration", "overline");
+        variables.set("fallback", "strike-through");
+        let mut custom_properties =
+            HashMap::from([("--decoration".to_owned(), "underline".to_owned())]);
+        let styles = [
+            deferred("font.decoration", "$decoration;"),
+            deferred("font.decoration", "$fallback;"),
+        ];
+        let styles: Vec<&Style> = styles.iter().collect();
+
+        // When: resolving the variables
+        let result = resolve_variables(&styles, &mut custom_properties, &variables).unwrap();
+
+        // Then: the custom property wins and document variables are used otherwise
+        assert!(matches!(
+            result.as_slice(),
+            [
+                Style::TextDecoration(TextDecoration::Underline),
+                Style::TextDecoration(TextDecoration::StrikeThrough)
+            ]
+        ));
+    }
+
+    #[test]
+    fn should_keep_regular_styles() {
+        // Given: a style without variables
+        let styles = [Style::TextDecoration(TextDecoration::Overline)];
+        let styles: Vec<&Style> = styles.iter().collect();
+
+        // When: resolving the variables
+        let result =
+            resolve_variables(&styles, &mut HashMap::new(), &DocumentVariables::new()).unwrap();
+
+        // Then: the style is kept as is
+        assert!(matches!(
+            result.as_slice(),
+            [Style::TextDecoration(TextDecoration::Overline)]
+        ));
+    }
+}
@@ -3,8 +3,14 @@ use unit::Distance;
 #[derive(Debug)]
 pub struct GlyphDetails {
     pub codepoint: u32,
+
+    /// Byte offset of the cluster the glyph belongs to in the shaped text.
     pub cluster: u32,
 
+    /// Text of the cluster the glyph belongs to, for example `fi` for a ligature.
+    /// Only the first glyph of a cluster carries its text, the other glyphs of the cluster are empty.
+    pub text: String,
+
     /// Horizontal advance from the text shaping process.
     /// This includes distance adjustments like kerning.
     pub x_advance: Distance,
@@ -1,58 +1,135 @@
-use harfbuzz_rs::{shape, UnicodeBuffer};
+use std::collections::HashSet;
+
+use harfbuzz_rs::{shape, Feature, Tag, UnicodeBuffer};
 
 use font::LetterFont;
 pub use glyph::GlyphDetails;
+pub use options::{ScriptPosition, ShapingOptions};
 use unit::{Distance, DistanceUnit};
 
-use crate::glyph_shaping::result::TextShaperResult;
+pub use crate::glyph_shaping::result::{ShapedRun, TextShaperResult};
 use crate::result::TypesetResult;
 
 mod glyph;
+mod options;
 mod result;
 
+/// Size of synthesized small capitals relative to the font size, similar to what browsers use.
+const SMALL_CAPS_SCALE: f64 = 0.7;
+
 // TODO Extract shape_text to some kind of shaper-service that can be mocked in tests
 pub fn shape_text(
     text: &str,
     font_size: Distance,
     font: &LetterFont,
+    options: &ShapingOptions,
 ) -> TypesetResult<TextShaperResult> {
+    let (mut features, font_size, baseline_shift) = match options.script_position {
+        Some(position) => script_features(text, font_size, font, position),
+        None => (Vec::new(), font_size, Distance::zero()),
+    };
+
+    // Fonts without small capitals get uppercase letters of a smaller size instead of lowercase ones
+    let small_caps_tag = Tag::new('s', 'm', 'c', 'p');
+    let synthesize_small_caps =
+        options.small_caps && !font.has_substitution_feature(small_caps_tag);
+    if options.small_caps && !synthesize_small_caps {
+        features.push(Feature::new(small_caps_tag, 1, ..));
+    }
+
+    let runs = if synthesize_small_caps {
+        small_caps_runs(text)
+    } else {
+        vec![(0, text, false)]
+    };
+    let runs: Vec<ShapedRun> = runs
+        .into_iter()
+        .map(|(start, run_text, is_lowercase)| {
+            let run_font_size = if is_lowercase {
+                font_size * SMALL_CAPS_SCALE
+            } else {
+                font_size
+            };
+            shape_run(
+                start,
+                run_text,
+                is_lowercase,
+                run_font_size,
+                font,
+                options,
+                &features,
+            )
+        })
+        .collect();
+
+    let width = runs
+        .iter()
+        .fold(Distance::zero(), |width, run| width + run.width);
+
+    Ok(TextShaperResult {
+        width,
+        runs,
+        baseline_shift,
+    })
+}
+
+/// Shape a run of the text starting at the given byte offset, optionally in uppercase.
+/// The glyphs refer to the characters of the original text, even if their uppercase form is longer.
+fn shape_run(
+    start: usize,
+    text: &str,
+    uppercase: bool,
+    font_size: Distance,
+    font: &LetterFont,
+    options: &ShapingOptions,
+    features: &[Feature],
+) -> ShapedRun {
     // TODO This will parse the font each invocation which is expensive -> Refactor to only create font and buffer once
     let internal_font = font.to_internal();
     let units_per_em = internal_font.face().upem() as usize;
+    let unit = DistanceUnit::FontUnits {
+        units_per_em,
+        font_size: font_size.value(DistanceUnit::Millimeter),
+    };
 
-    let buffer = UnicodeBuffer::new().add_str(text);
-    let output = shape(&internal_font, buffer, &[]);
+    let (shaped_text, source_offsets) = if uppercase {
+        let (upper, offsets) = to_uppercase_with_offsets(text);
+        (upper, Some(offsets))
+    } else {
+        (text.to_owned(), None)
+    };
+    let buffer = shape(
+        font.to_internal(),
+        UnicodeBuffer::new().add_str(&shaped_text),
+        features,
+    );
 
-    let positions = output.get_glyph_positions();
-    let infos = output.get_glyph_infos();
+    let positions = buffer.get_glyph_positions();
+    let infos = buffer.get_glyph_infos();
+    let clusters: Vec<u32> = infos
+        .iter()
+        .map(|info| match &source_offsets {
+            Some(offsets) => offsets
+                .get(info.cluster as usize)
+                .copied()
+                .unwrap_or(text.len() as u32),
+            None => info.cluster,
+        })
+        .collect();
+    let texts = cluster_texts(text, &clusters);
 
-    let mut width = Distance::new(
-        0.0,
-        DistanceUnit::FontUnits {
-            units_per_em,
-            font_size: font_size.value(DistanceUnit::Millimeter),
-        },
-    );
+    let mut width = Distance::new(0.0, unit);
     let mut glyphs = Vec::new();
-    for (position, info) in positions.iter().zip(infos) {
+    for (((position, info), cluster), text) in positions.iter().zip(infos).zip(clusters).zip(texts)
+    {
         let codepoint = info.codepoint;
-        let font_x_advance = Distance::new(
-            internal_font.get_glyph_h_advance(codepoint) as f64,
-            DistanceUnit::FontUnits {
-                units_per_em,
-                font_size: font_size.value(DistanceUnit::Millimeter),
-            },
-        );
-        let x_advance = Distance::new(
-            position.x_advance as f64,
-            DistanceUnit::FontUnits {
-                units_per_em,
-                font_size: font_size.value(DistanceUnit::Millimeter),
-            },
-        );
+        let font_x_advance =
+            Distance::new(internal_font.get_glyph_h_advance(codepoint) as f64, unit);
+        let x_advance = Distance::new(position.x_advance as f64, unit) + options.letter_spacing;
         let glyph_details = GlyphDetails {
             codepoint,
-            cluster: info.cluster,
+            cluster: start as u32 + cluster,
+            text,
             x_advance,
             font_x_advance,
         };
@@ -61,5 +138,309 @@ pub fn shape_text(
         glyphs.push(glyph_details);
     }
 
-    Ok(TextShaperResult { width, glyphs })
+    ShapedRun {
+        text: text.to_owned(),
+        glyphs,
+        font_size,
+        width,
+    }
+}
+
+/// Split the text into runs of lowercase and other characters given by their byte offset,
+/// text and whether they are lowercase.
+fn small_caps_runs(text: &str) -> Vec<(usize, &str, bool)> {
+    let mut runs: Vec<(usize, &str, bool)> = Vec::new();
+    for (offset, c) in text.char_indices() {
+        let is_lowercase = c.is_lowercase();
+        let end = offset + c.len_utf8();
+        match runs.last_mut() {
+            Some((start, run_text, run_is_lowercase)) if *run_is_lowercase == is_lowercase => {
+                *run_text = &text[*start..end];
+            }
+            _ => runs.push((offset, &text[offset..end], is_lowercase)),
+        }
+    }
+
+    runs
+}
+
+/// Uppercase the text and map each byte of the uppercase text to the offset of the character it stems from,
+/// since the uppercase form of some characters is longer (for example `ß` becomes `SS`).
+fn to_uppercase_with_offsets(text: &str) -> (String, Vec<u32>) {
+    let mut upper = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    for (offset, c) in text.char_indices() {
+        for upper_c in c.to_uppercase() {
+            upper.push(upper_c);
+            offsets.extend(std::iter::repeat_n(offset as u32, upper_c.len_utf8()));
+        }
+    }
+
+    (upper, offsets)
+}
+
+/// Text of each glyph given the clusters of the glyphs in the order of the shaped output,
+/// where the first glyph of a cluster carries the text of the whole cluster.
+fn cluster_texts(text: &str, clusters: &[u32]) -> Vec<String> {
+    let mut cluster_starts: Vec<usize> = clusters.iter().map(|cluster| *cluster as usize).collect();
+    cluster_starts.sort_unstable();
+    cluster_starts.dedup();
+
+    let mut seen_clusters = HashSet::new();
+    clusters
+        .iter()
+        .map(|cluster| {
+            let start = *cluster as usize;
+            if !seen_clusters.insert(start) {
+                return String::new();
+            }
+
+            let end = cluster_starts
+                .get(cluster_starts.partition_point(|cluster| *cluster <= start))
+                .copied()
+                .unwrap_or(text.len());
+
+            text.get(start..end).unwrap_or_default().to_owned()
+        })
+        .collect()
+}
+
+/// Features, font size and baseline shift to shape the text as superscript or subscript with.
+/// Uses the `sups` or `subs` feature of the font if it substitutes the glyphs of the whole text,
+/// otherwise the font size and baseline shift are taken from the superscript or subscript metrics
+/// of the font, thus the text is not rendered in mixed sizes.
+fn script_features(
+    text: &str,
+    font_size: Distance,
+    font: &LetterFont,
+    position: ScriptPosition,
+) -> (Vec<Feature>, Distance, Distance) {
+    let (tag, metrics) = match position {
+        ScriptPosition::Superscript => (Tag::new('s', 'u', 'p', 's'), font.superscript_metrics()),
+        ScriptPosition::Subscript => (Tag::new('s', 'u', 'b', 's'), font.subscript_metrics()),
+    };
+
+    if font.has_substitution_feature(tag) {
+        let features = vec![Feature::new(tag, 1, ..)];
+        let buffer = shape(
+            font.to_internal(),
+            UnicodeBuffer::new().add_str(text),
+            &features,
+        );
+        let glyphs: Vec<(u32, u32)> = buffer
+            .get_glyph_infos()
+            .iter()
+            .map(|info| (info.codepoint, info.cluster))
+            .collect();
+
+        let internal_font = font.to_internal();
+        if is_substituted_completely(text, &glyphs, |c| internal_font.get_nominal_glyph(c)) {
+            return (features, font_size, Distance::zero());
+        }
+    }
+
+    let units_per_em = font.units_per_em();
+    let baseline_shift = Distance::new(
+        metrics.offset as f64,
+        DistanceUnit::FontUnits {
+            units_per_em,
+            font_size: font_size.value(DistanceUnit::Millimeter),
+        },
+    );
+    let font_size = font_size * (metrics.size as f64 / units_per_em as f64);
+
+    (Vec::new(), font_size, baseline_shift)
+}
+
+/// Whether a feature substituted the glyph of every visible character of the text, given the glyphs
+/// as code point and cluster pairs. A glyph counts as substituted when it differs from the glyph
+/// the character of its cluster is mapped to by the font without any feature.
+fn is_substituted_completely(
+    text: &str,
+    glyphs: &[(u32, u32)],
+    nominal_glyph: impl Fn(char) -> Option<u32>,
+) -> bool {
+    glyphs.iter().all(|(codepoint, cluster)| {
+        match text
+            .get(*cluster as usize..)
+            .and_then(|rest| rest.chars().next())
+        {
+            Some(c) if !c.is_whitespace() => nominal_glyph(c) != Some(*codepoint),
+            _ => true,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use font::FontManager;
+
+    use super::*;
+
+    fn nominal_glyph(c: char) -> Option<u32> {
+        Some(c as u32)
+    }
+
+    #[test]
+    fn should_assign_text_of_ligature_to_its_glyph() {
+        // Given: The text "office" shaped with a ligature for "ffi"
+        let clusters = [0, 1, 4, 5];
+
+        // When: Determining the text of each glyph
+        let texts = cluster_texts("office", &clusters);
+
+        // Then: The ligature glyph carries the text of all its characters
+        assert_eq!(texts, vec!["o", "ffi", "c", "e"]);
+    }
+
+    #[test]
+    fn should_assign_text_of_cluster_to_first_glyph_only() {
+        // Given: A character shaped into a base glyph and a combining mark of the same cluster
+        let clusters = [0, 0, 2];
+
+        // When: Determining the text of each glyph
+        let texts = cluster_texts("éx", &clusters);
+
+        // Then: The first glyph of the cluster carries its text while the mark stays empty
+        assert_eq!(texts, vec!["é", "", "x"]);
+    }
+
+    #[test]
+    fn should_assign_texts_of_right_to_left_clusters() {
+        // Given: Hebrew text with two bytes per letter, whose glyphs are output in visual order
+        // from right to left, with the second and third letter shaped into a single glyph
+        let text = "שלום";
+        let clusters = [6, 2, 0];
+
+        // When: Determining the text of each glyph
+        let texts = cluster_texts(text, &clusters);
+
+        // Then: Each glyph carries the text up to the start of the following cluster in logical order
+        assert_eq!(texts, vec!["ם", "לו", "ש"]);
+    }
+
+    #[test]
+    fn should_assign_original_characters_to_uppercase_glyphs() {
+        // Given: "aß" shaped in uppercase as "ASS" with one glyph per letter
+        let (upper, offsets) = to_uppercase_with_offsets("aß");
+        let clusters: Vec<u32> = [0, 1, 2].iter().map(|cluster| offsets[*cluster]).collect();
+
+        // When: Determining the text of each glyph
+        let texts = cluster_texts("aß", &clusters);
+
+        // Then: The glyphs of the longer uppercase form share the cluster of the original character
+        assert_eq!(upper, "ASS");
+        assert_eq!(texts, vec!["a", "ß", ""]);
+    }
+
+    #[test]
+    fn should_split_text_into_lowercase_and_other_runs() {
+        // Given: A text mixing lowercase letters, uppercase letters, digits and white space
+        let text = "Straße 2b";
+
+        // When: Splitting the text into runs for synthesized small capitals
+        let runs = small_caps_runs(text);
+
+        // Then: The runs alternate between lowercase and other characters
+        assert_eq!(
+            runs,
+            vec![
+                (0, "S", false),
+                (1, "traße", true),
+                (7, " 2", false),
+                (9, "b", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_synthesize_small_caps_for_font_without_feature() {
+        // Given: The bundled font, which has no small capitals
+        let font_manager = FontManager::new();
+        let font = font_manager
+            .get_font(&font_manager.default_font_id())
+            .unwrap();
+        let font_size = Distance::new(10.0, DistanceUnit::Points);
+        let options = ShapingOptions {
+            small_caps: true,
+            ..ShapingOptions::default()
+        };
+
+        // When: Shaping a text in small capitals
+        let result = shape_text("Straße", font_size, font, &options).unwrap();
+
+        // Then: Lowercase letters are shaped at a smaller size and keep their original text
+        let runs: Vec<(&str, f64)> = result
+            .runs
+            .iter()
+            .map(|run| (run.text.as_str(), run.font_size.value(DistanceUnit::Points)))
+            .collect();
+        assert_eq!(runs, vec![("S", 10.0), ("traße", 7.0)]);
+
+        // And: The glyphs of each run carry the characters of the run
+        for run in &result.runs {
+            let glyph_text: String = run.glyphs.iter().map(|glyph| glyph.text.as_str()).collect();
+            assert!(run.glyphs.is_empty() || glyph_text == run.text);
+        }
+    }
+
+    #[test]
+    fn should_assign_empty_text_to_clusters_outside_of_text() {
+        // Given: A cluster pointing into the middle of a multibyte character
+        let clusters = [0, 1];
+
+        // When: Determining the text of each glyph
+        let texts = cluster_texts("ä", &clusters);
+
+        // Then: Clusters not at character boundaries get no text
+        assert_eq!(texts, vec!["", ""]);
+    }
+
+    #[test]
+    fn should_detect_completely_substituted_text() {
+        // Given: Superscript glyphs for every letter of the text
+        let glyphs = [(1000, 0), (1001, 1)];
+
+        // When: Checking whether the text is substituted completely
+        let result = is_substituted_completely("ab", &glyphs, nominal_glyph);
+
+        // Then: The text is substituted completely
+        assert!(result);
+    }
+
+    #[test]
+    fn should_detect_partially_substituted_text() {
+        // Given: A superscript glyph for the first letter only
+        let glyphs = [(1000, 0), ('b' as u32, 1)];
+
+        // When: Checking whether the text is substituted completely
+        let result = is_substituted_completely("ab", &glyphs, nominal_glyph);
+
+        // Then: The text is not substituted completely as the second letter keeps its regular glyph
+        assert!(!result);
+    }
+
+    #[test]
+    fn should_ignore_white_space_when_checking_substitution() {
+        // Given: Superscript glyphs for every letter while the space keeps its regular glyph
+        let glyphs = [(1000, 0), (' ' as u32, 1), (1001, 2)];
+
+        // When: Checking whether the text is substituted completely
+        let result = is_substituted_completely("a b", &glyphs, nominal_glyph);
+
+        // Then: The text is substituted completely
+        assert!(result);
+    }
+
+    #[test]
+    fn should_check_substitution_of_characters_with_multiple_bytes() {
+        // Given: A superscript glyph for the second character following a multibyte character
+        let glyphs = [('ä' as u32, 0), (1001, 2)];
+
+        // When: Checking whether the text is substituted completely
+        let result = is_substituted_completely("äb", &glyphs, nominal_glyph);
+
+        // Then: The regular glyph of the multibyte character is detected
+        assert!(!result);
+    }
 }
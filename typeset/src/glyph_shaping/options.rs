@@ -0,0 +1,34 @@
+use unit::Distance;
+
+/// Adjustments to apply while shaping a text.
+#[derive(Debug, Clone, Copy)]
+pub struct ShapingOptions {
+    /// Additional space added to the advance of each glyph.
+    pub letter_spacing: Distance,
+
+    /// Whether to render lowercase letters as small capitals.
+    /// Uses the OpenType `smcp` feature if the font supports it,
+    /// otherwise lowercase letters are shaped in uppercase with a smaller font size instead.
+    pub small_caps: bool,
+
+    /// Whether to render the text as superscript or subscript.
+    /// Uses the OpenType `sups` or `subs` feature if the font supports it,
+    /// otherwise the text is shaped with a smaller font size and shifted baseline instead.
+    pub script_position: Option<ScriptPosition>,
+}
+
+impl Default for ShapingOptions {
+    fn default() -> Self {
+        Self {
+            letter_spacing: Distance::zero(),
+            small_caps: false,
+            script_position: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptPosition {
+    Superscript,
+    Subscript,
+}
@@ -4,5 +4,23 @@ use crate::glyph_shaping::GlyphDetails;
 
 pub struct TextShaperResult {
     pub width: Distance,
+
+    /// Runs of glyphs sharing a font size in the order of the text.
+    /// Texts are shaped as a single run unless small capitals had to be synthesized.
+    pub runs: Vec<ShapedRun>,
+
+    /// Shift of the baseline with positive values pointing upwards.
+    pub baseline_shift: Distance,
+}
+
+/// Part of the shaped text rendered with the same font size.
+pub struct ShapedRun {
+    /// Text of the run as given, even if it was shaped in uppercase.
+    pub text: String,
     pub glyphs: Vec<GlyphDetails>,
+
+    /// Font size to render the glyphs with, which is smaller than the requested one
+    /// when superscript or subscript glyphs or small capitals had to be synthesized.
+    pub font_size: Distance,
+    pub width: Distance,
 }